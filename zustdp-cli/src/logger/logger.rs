@@ -0,0 +1,16 @@
+use zustdpipe::{
+    apps::interface::AppDefinition,
+    logger::{log4rs::Log4rsLogger, manager::init_logger},
+};
+
+use crate::error::CliGeneralError;
+
+/// Initializes the logger according to the logging section of an application
+/// # Arguments
+/// * `app_definition` - definition of the application
+pub fn init_app_logger(app_definition: &AppDefinition) -> Result<Log4rsLogger, CliGeneralError> {
+    match init_logger(app_definition.logging.as_ref()) {
+        Ok(logger) => Ok(logger),
+        Err(error) => Err(CliGeneralError::ErrorMessage(format!("{:#?}", error))),
+    }
+}
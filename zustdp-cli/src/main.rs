@@ -3,16 +3,24 @@ use std::{fmt, process::ExitCode};
 ///! ZuSearch Command Line Interface (CLI)
 use structopt::StructOpt;
 
-use crate::{run::run::run_job, validate::validate::validate_application_definition_file};
+use crate::{
+    resume::resume::resume_job, run::run::run_job, schedule::schedule::run_scheduler,
+    validate::validate::validate_application_definition_file,
+};
 
 pub mod error;
+pub mod logger;
+pub mod resume;
 pub mod run;
+pub mod schedule;
 pub mod validate;
 
 #[derive(StructOpt, Debug)]
 enum Command {
     Validate,
     Run(RunParameter),
+    Resume(ResumeParameter),
+    Schedule,
 }
 
 #[derive(StructOpt, Debug)]
@@ -21,6 +29,12 @@ struct RunParameter {
     name: String,
 }
 
+#[derive(StructOpt, Debug)]
+struct ResumeParameter {
+    #[structopt(short = "-i", long = "--id")]
+    id: String,
+}
+
 /// Command line arguments
 
 #[derive(StructOpt)]
@@ -64,5 +78,21 @@ fn main() -> ExitCode {
                 }
             }
         }
+        Command::Resume(parameter) => {
+            match resume_job(&args.application_definition_file, &parameter.id) {
+                Ok(()) => ExitCode::from(0),
+                Err(error) => {
+                    println!("{:#?}", error);
+                    ExitCode::from(1)
+                }
+            }
+        }
+        Command::Schedule => match run_scheduler(&args.application_definition_file) {
+            Ok(()) => ExitCode::from(0),
+            Err(error) => {
+                println!("{:#?}", error);
+                ExitCode::from(1)
+            }
+        },
     }
 }
@@ -0,0 +1,69 @@
+use zustdpipe::{
+    apps::interface::{AppList, AppManager},
+    jobs::interface::{JobList, JobManager},
+    logger::log4rs::Log4rsLogger,
+    state::interface::{StateManager, StateStore},
+};
+
+use crate::error::CliGeneralError;
+use crate::logger::logger::init_app_logger;
+use crate::run::run::print_job_result;
+
+/// Resumes a failed job of an application from its last checkpoint
+/// # Arguments
+/// * `application_definition_file` - path to the definition of the application
+/// * `job_id` - unique instance id of the job printed when the job has been run
+pub fn resume_job(
+    application_definition_file: &std::path::PathBuf,
+    job_id: &String,
+) -> Result<(), CliGeneralError> {
+    let f = match std::fs::File::open(application_definition_file) {
+        Ok(file) => file,
+        Err(_error) => {
+            return Err(CliGeneralError::ErrorMessage(format!(
+                "Cannot open file: {}",
+                application_definition_file.as_path().display()
+            )))
+        }
+    };
+    let mut appmgr: AppList = AppManager::new();
+    if let Err(error) = appmgr.add(f) {
+        return Err(CliGeneralError::ErrorMessage(error.to_string()));
+    }
+    let app_definition = &appmgr.get(0);
+    println!("Application name: {}", &app_definition.general.name);
+    let _logger: Log4rsLogger = init_app_logger(app_definition)?;
+    println!("Trying to resume job {}", job_id);
+
+    // checkpoints of jobs run by another process are only available if they have been persisted
+    let state_definition = match &app_definition.state {
+        Some(state_definition) => state_definition,
+        None => {
+            return Err(CliGeneralError::ErrorMessage(format!(
+                "Job {} cannot be resumed, because the application does not define where its state is stored",
+                job_id
+            )))
+        }
+    };
+    let mut jobmgr: JobList = match JobManager::new(&app_definition.modules) {
+        Ok(jobmgr) => jobmgr,
+        Err(error) => {
+            return Err(CliGeneralError::ErrorMessage(format!("{:#?}", error)));
+        }
+    };
+    let state_store: StateStore = match StateManager::open(state_definition) {
+        Ok(state_store) => state_store,
+        Err(error) => {
+            return Err(CliGeneralError::ErrorMessage(format!("{:#?}", error)));
+        }
+    };
+    jobmgr.set_state_store(state_store);
+
+    match jobmgr.resume_job(job_id) {
+        Ok(job_result) => {
+            print_job_result(&job_result);
+            Ok(())
+        }
+        Err(error) => Err(CliGeneralError::ErrorMessage(format!("{:#?}", error))),
+    }
+}
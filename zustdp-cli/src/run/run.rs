@@ -1,10 +1,13 @@
 use zustdpipe::{
     apps::interface::{AppList, AppManager},
-    jobs::interface::{JobList, JobManager},
+    jobs::interface::{JobList, JobManager, JobResult},
+    logger::log4rs::Log4rsLogger,
     pipeline,
+    state::interface::{StateManager, StateStore},
 };
 
 use crate::error::CliGeneralError;
+use crate::logger::logger::init_app_logger;
 
 pub fn run_job(
     application_definition_file: &std::path::PathBuf,
@@ -23,6 +26,7 @@ pub fn run_job(
     appmgr.add(f).unwrap();
     let app_definition = &appmgr.get(0);
     println!("Application name: {}", &app_definition.general.name);
+    let _logger: Log4rsLogger = init_app_logger(app_definition)?;
     println!("Trying to run {}", job_name);
 
     let mut jobmgr: JobList = match JobManager::new(&app_definition.modules) {
@@ -31,6 +35,15 @@ pub fn run_job(
             return Err(CliGeneralError::ErrorMessage(format!("{:#?}", error)));
         }
     };
+    if let Some(state_definition) = &app_definition.state {
+        let state_store: StateStore = match StateManager::open(state_definition) {
+            Ok(state_store) => state_store,
+            Err(error) => {
+                return Err(CliGeneralError::ErrorMessage(format!("{:#?}", error)));
+            }
+        };
+        jobmgr.set_state_store(state_store);
+    }
 
     let job_definitions = &app_definition.jobs;
     let job_definition = match job_definitions.get(job_name) {
@@ -42,11 +55,42 @@ pub fn run_job(
             )))
         }
     };
-    match jobmgr.run_job(&app_definition.pipelines, job_definition) {
-        Ok(job_id) => {
-            println!("Job id: {}", job_id);
+    let job_id = match jobmgr.create_job(&app_definition.pipelines, job_definition) {
+        Ok(job_id) => job_id,
+        Err(error) => return Err(CliGeneralError::ErrorMessage(format!("{:#?}", error))),
+    };
+    match jobmgr.start_job(&job_id, Vec::new()) {
+        Ok(job_result) => {
+            print_job_result(&job_result);
             Ok(())
         }
+        // the job can only be resumed by another process if its checkpoint has been persisted
+        Err(error) if app_definition.state.is_some() => {
+            Err(CliGeneralError::ErrorMessage(format!(
+                "{:#?}\nJob {} can be resumed using the command resume --id {}",
+                error, job_id, job_id
+            )))
+        }
         Err(error) => Err(CliGeneralError::ErrorMessage(format!("{:#?}", error))),
     }
 }
+
+/// Prints the outputs and the consumed fuel of a job
+/// # Arguments
+/// * `job_result` - result of the job
+pub fn print_job_result(job_result: &JobResult) {
+    println!("Job id: {}", job_result.id);
+    for (process_name, serialized_data) in &job_result.outputs {
+        println!(
+            "Output of process {} size (bytes): {}",
+            process_name,
+            serialized_data.len()
+        );
+    }
+    for (process_name, fuel_consumed) in &job_result.fuel_consumed {
+        println!(
+            "Fuel consumed by process {}: {}",
+            process_name, fuel_consumed
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use std::sync::atomic::AtomicBool;
+
+use time::OffsetDateTime;
+use zustdpipe::{
+    apps::interface::{AppList, AppManager},
+    jobs::interface::{JobList, JobManager, JobScheduleList, JobScheduler},
+    logger::log4rs::Log4rsLogger,
+    state::interface::{StateManager, StateStore},
+};
+
+use crate::error::CliGeneralError;
+use crate::logger::logger::init_app_logger;
+
+/// Runs all jobs of an application that are triggered by a cron expression or an interval until the process is terminated
+pub fn run_scheduler(
+    application_definition_file: &std::path::PathBuf,
+) -> Result<(), CliGeneralError> {
+    let f = match std::fs::File::open(application_definition_file) {
+        Ok(file) => file,
+        Err(_error) => {
+            return Err(CliGeneralError::ErrorMessage(format!(
+                "Cannot open file: {}",
+                application_definition_file.as_path().display()
+            )))
+        }
+    };
+    let mut appmgr: AppList = AppManager::new();
+    if let Err(error) = appmgr.add(f) {
+        return Err(CliGeneralError::ErrorMessage(error.to_string()));
+    }
+    let app_definition = &appmgr.get(0);
+    println!("Application name: {}", &app_definition.general.name);
+    let _logger: Log4rsLogger = init_app_logger(app_definition)?;
+
+    let mut jobmgr: JobList = match JobManager::new(&app_definition.modules) {
+        Ok(jobmgr) => jobmgr,
+        Err(error) => {
+            return Err(CliGeneralError::ErrorMessage(format!("{:#?}", error)));
+        }
+    };
+    if let Some(state_definition) = &app_definition.state {
+        let state_store: StateStore = match StateManager::open(state_definition) {
+            Ok(state_store) => state_store,
+            Err(error) => {
+                return Err(CliGeneralError::ErrorMessage(format!("{:#?}", error)));
+            }
+        };
+        jobmgr.set_state_store(state_store);
+    }
+    let mut schedule_list: JobScheduleList =
+        match JobScheduler::new(&app_definition.jobs, OffsetDateTime::now_utc()) {
+            Ok(schedule_list) => schedule_list,
+            Err(error) => {
+                return Err(CliGeneralError::ErrorMessage(format!("{:#?}", error)));
+            }
+        };
+    for scheduled_job in &schedule_list.scheduled_jobs {
+        println!(
+            "Scheduled job {} (next run: {})",
+            scheduled_job.name, scheduled_job.next_run
+        );
+    }
+    let stop = AtomicBool::new(false);
+    schedule_list.run(
+        &mut jobmgr,
+        &app_definition.jobs,
+        &app_definition.pipelines,
+        &stop,
+    );
+    Ok(())
+}
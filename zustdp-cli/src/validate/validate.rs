@@ -1,4 +1,10 @@
 use zustdpipe::apps::interface::{AppList, AppManager};
+use zustdpipe::error::error::GeneralError;
+use zustdpipe::jobs::dag::execution_order;
+use zustdpipe::jobs::interface::{JobRunError, JobValidationError};
+use zustdpipe::jobs::manager::validate_pipeline_schemas;
+use zustdpipe::modules::interface::{ModuleDefinitionError, ModuleManager, ModuleManagerList};
+use zustdpipe::modules::library::interface::LibraryDefinitionError;
 
 use crate::error::CliGeneralError;
 
@@ -19,14 +25,91 @@ pub fn validate_application_definition_file(
     appmgr.add(f).unwrap();
     println!("Application name: {}", appmgr.get(0).general.name);
 
+    // modules are loaded to check that they implement the interface expected by the host
+    let mut module_manager_list: ModuleManagerList =
+        match ModuleManager::new(&appmgr.get(0).modules) {
+            Ok(module_manager_list) => module_manager_list,
+            Err(error) => {
+                return Err(CliGeneralError::ErrorMessage(format!(
+                    "Invalid module definition: {:#?}",
+                    error
+                )))
+            }
+        };
+    let mut invalid_modules = 0;
+    let mut invalid_pipelines = 0;
     println!("Number of pipelines: {}", &appmgr.get(0).pipelines.len());
     for (name, definition) in &appmgr.get(0).pipelines {
         println!("Pipeline name: {}", &name);
-        for process in &definition.process {
-            for (process_name, process_definition) in process.iter() {
-                println!("Process name: {}", process_name);
+        let process_nodes = match execution_order(definition) {
+            Ok(process_nodes) => process_nodes,
+            Err(error) => {
+                return Err(CliGeneralError::ErrorMessage(format!(
+                    "Invalid pipeline {}: {:#?}",
+                    &name, error
+                )))
             }
+        };
+        for process_node in &process_nodes {
+            println!(
+                "Process name: {} (inputs: {})",
+                process_node.name,
+                process_node.inputs.join(", ")
+            );
+            match module_manager_list.validate_module(&process_node.definition) {
+                Ok(()) => (),
+                Err(ModuleDefinitionError::ModuleCannotBeInstantiated(
+                    LibraryDefinitionError::AbiViolation(violations),
+                )) => {
+                    invalid_modules += 1;
+                    println!(
+                        "Module {} does not implement the module interface:",
+                        process_node.definition.module.name
+                    );
+                    for GeneralError::ErrorMessage(violation) in violations {
+                        println!("  {}", violation);
+                    }
+                }
+                Err(ModuleDefinitionError::ProcessParametersInvalid(violations)) => {
+                    invalid_modules += 1;
+                    println!(
+                        "Parameters of process {} do not match the manifest of module {}:",
+                        process_node.name, process_node.definition.module.name
+                    );
+                    for GeneralError::ErrorMessage(violation) in violations {
+                        println!("  {}", violation);
+                    }
+                }
+                Err(error) => {
+                    invalid_modules += 1;
+                    println!(
+                        "Module {} is invalid: {:#?}",
+                        process_node.definition.module.name, error
+                    );
+                }
+            };
         }
+        // the output of each process must match the input expected by the processes it is connected to
+        match validate_pipeline_schemas(&process_nodes, &mut module_manager_list) {
+            Ok(()) => (),
+            Err(JobRunError::JobValidationError(JobValidationError::SchemaMismatch(
+                violations,
+            ))) => {
+                invalid_pipelines += 1;
+                println!("Schemas of connected processes do not match:");
+                for GeneralError::ErrorMessage(violation) in violations {
+                    println!("  {}", violation);
+                }
+            }
+            // invalid modules have already been reported
+            Err(_error) => (),
+        };
+    }
+    if invalid_modules > 0 || invalid_pipelines > 0 {
+        return Err(CliGeneralError::ErrorMessage(format!(
+            "{} invalid module(s) and {} invalid pipeline(s) found",
+            invalid_modules, invalid_pipelines
+        )));
     }
 
     Ok(())
@@ -1,15 +1,17 @@
-use zustdpipe_modules_library::interfaces::process::interface::{Parameters, Result, Process};
+use arrow::record_batch::RecordBatch;
+use zustdpipe_modules_library::interfaces::process::interface::{Parameters, Process, ProcessError};
+use zustdpipe_modules_library::modules::log::{log, LogLevel};
 
 pub struct EchoProcess {}
 
 impl Process for EchoProcess {
     fn new() -> Self {
-        println!("init");
         return EchoProcess {};
     }
 
-    fn execute(&self, _param: Parameters) -> Option<Result> {
-        println!("Hallo");
-        None
+    /// Returns the data unchanged
+    fn execute(&mut self, _parameters: &Parameters, data: Vec<RecordBatch>) -> Result<Vec<RecordBatch>, ProcessError> {
+        log(LogLevel::Debug, &format!("Echoing {} record batches", data.len()));
+        Ok(data)
     }
 }
@@ -1,40 +1,111 @@
+use super::echoprocess::EchoProcess;
 
+// declare the parameters of the module, so ZuStDPipe can validate pipelines using it
+zustdpipe_modules_library::zustdp_module_manifest!("../manifest.yml");
 
+// export the process as the raw entry function into the WebAssembly module
+zustdpipe_modules_library::export_process!(EchoProcess);
 
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
 
+    use arrow::array::{ArrayRef, MapBuilder, StringArray, StringBuilder};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::ipc::reader::StreamReader;
+    use arrow::ipc::writer::StreamWriter;
+    use arrow::record_batch::RecordBatch;
+    use zustdpipe_modules_library::modules::wasm::{
+        zustdp_module_wasm_allocate, zustdp_module_wasm_last_error,
+    };
 
+    /// Serializes a batch in Arrow IPC format into memory allocated by the module
+    fn allocate_batch(batch: &RecordBatch) -> (*mut u8, usize) {
+        let mut stream_writer = StreamWriter::try_new(Vec::new(), &batch.schema()).unwrap();
+        stream_writer.write(batch).unwrap();
+        stream_writer.finish().unwrap();
+        let serialized_batch = stream_writer.into_inner().unwrap();
+        let ptr = zustdp_module_wasm_allocate(serialized_batch.len()) as *mut u8;
+        unsafe { std::ptr::copy_nonoverlapping(serialized_batch.as_ptr(), ptr, serialized_batch.len()) };
+        (ptr, serialized_batch.len())
+    }
 
+    /// Creates a batch with a single text column
+    fn text_batch(value: &str) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![Field::new("text", DataType::Utf8, false)]));
+        RecordBatch::try_new(schema, vec![Arc::new(StringArray::from(vec![value])) as ArrayRef]).unwrap()
+    }
 
+    /// Creates a batch with the parameters of a process in the format ZuStDPipe hands them over
+    fn parameters_batch(key: &str, value: &str) -> RecordBatch {
+        let mut map_builder = MapBuilder::new(None, StringBuilder::new(), StringBuilder::new());
+        map_builder.keys().append_value(key);
+        map_builder.values().append_value(value);
+        map_builder.append(true).unwrap();
+        let parameters = Arc::new(map_builder.finish()) as ArrayRef;
+        RecordBatch::try_from_iter(vec![("parameters", parameters)]).unwrap()
+    }
 
+    /// Reads the content of a result descriptor returned by the module
+    fn read_result(result_offset: usize) -> &'static [u8] {
+        // the result descriptor contains pointer and length as u64
+        let descriptor = unsafe { std::slice::from_raw_parts(result_offset as *const u8, 16) };
+        let result_ptr = u64::from_le_bytes(descriptor[0..8].try_into().unwrap()) as usize;
+        let result_len = u64::from_le_bytes(descriptor[8..16].try_into().unwrap()) as usize;
+        unsafe { std::slice::from_raw_parts(result_ptr as *const u8, result_len) }
+    }
 
+    #[test]
+    // Test that the entry function generated by export_process! returns the data unchanged
+    fn test_raw_process_entry() {
+        let (meta_data_ptr, meta_data_len) = allocate_batch(&parameters_batch("text", "This is a test"));
+        let (data_ptr, data_len) = allocate_batch(&text_batch("Hello, World!"));
+        let result_offset =
+            super::zustdp_module_wasm_raw_process_entry(meta_data_ptr, meta_data_len, data_ptr, data_len);
+        assert_ne!(result_offset, 0);
+        let batches: Vec<RecordBatch> = StreamReader::try_new(read_result(result_offset), None)
+            .unwrap()
+            .map(|batch| batch.unwrap())
+            .collect();
+        assert_eq!(batches.len(), 1);
+        let column = batches[0].column(0).as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(column.value(0), "Hello, World!");
+    }
 
-use super::echoprocess::EchoProcess;
-
-use zustdpipe_modules_library::interfaces::process::interface::Process;
-use zustdpipe_modules_library::modules;
-
-/// This is the raw entry function into any WebAssembly module
-/// It takes care that the module parameters and data are passed through the serialization framework and that the answer from the module is provided back as a pointer
-#[no_mangle]
-pub extern "C" fn zustdp_module_wasm_raw_process_entry(
-    meta_data_offset: *mut u32,
-    meta_data_size: u32,
-    data_offset: *mut u32,
-    data_size: u32,
-) -> u32 {
-    let _input_arrow_meta_data= match modules::wasm::convert_raw_memory_to_arrow(meta_data_offset,meta_data_size) {
-        Some(data) => data,
-        None => return 0
-    };
-    let _input_arrow_data= match modules::wasm::convert_raw_memory_to_arrow(data_offset,data_size) {
-        Some(data) => data,
-        None => return 0
-    };
-    // check parametes
-    //for item in input_arrow_meta_data
-    // call function
-    let _process: EchoProcess = Process::new();
+    #[test]
+    // Test that an error is reported if the parameters cannot be read
+    fn test_raw_process_entry_error() {
+        let (meta_data_ptr, meta_data_len) = allocate_batch(&text_batch("This is a test"));
+        let (data_ptr, data_len) = allocate_batch(&text_batch("Hello, World!"));
+        let result_offset =
+            super::zustdp_module_wasm_raw_process_entry(meta_data_ptr, meta_data_len, data_ptr, data_len);
+        assert_eq!(result_offset, 0);
+        let error_offset = zustdp_module_wasm_last_error();
+        assert_ne!(error_offset, 0);
+        let message = std::str::from_utf8(read_result(error_offset)).unwrap();
+        assert!(message.starts_with("Invalid parameter"));
+    }
 
-    // convert result arrow to raw memory
-    return 0;
+    #[test]
+    // Test pushing record batches one by one to the stream functions generated by export_process!
+    fn test_stream() {
+        let (meta_data_ptr, meta_data_len) = allocate_batch(&parameters_batch("text", "This is a test"));
+        assert_eq!(super::zustdp_module_wasm_stream_start(meta_data_ptr, meta_data_len), 0);
+        for value in ["first", "second"] {
+            let (data_ptr, data_len) = allocate_batch(&text_batch(value));
+            assert_eq!(super::zustdp_module_wasm_stream_push(data_ptr, data_len), 0);
+            // the output must be pulled before the next record batch is accepted
+            assert_eq!(super::zustdp_module_wasm_stream_push(data_ptr, data_len), 1);
+            let result_offset = super::zustdp_module_wasm_stream_pull();
+            assert_ne!(result_offset, 0);
+            let batches: Vec<RecordBatch> = StreamReader::try_new(read_result(result_offset), None)
+                .unwrap()
+                .map(|batch| batch.unwrap())
+                .collect();
+            let column = batches[0].column(0).as_any().downcast_ref::<StringArray>().unwrap();
+            assert_eq!(column.value(0), value);
+            assert_eq!(super::zustdp_module_wasm_stream_pull(), 0);
+        }
+        assert_eq!(super::zustdp_module_wasm_stream_end(), 0);
+    }
 }
@@ -1 +1 @@
-pub mod process;
\ No newline at end of file
+pub mod process;
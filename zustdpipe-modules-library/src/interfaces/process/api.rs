@@ -1 +1,96 @@
 //! Application Programming Interface (API) for Steps in ZuStDPipe - this is provided by ZuStDPipe
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use super::interface::{Parameters, ProcessError};
+
+impl Parameters {
+    /// Creates parameters
+    /// # Arguments
+    /// * `values` - values of the parameters (key is the name of the parameter)
+    pub fn new(values: HashMap<String, String>) -> Self {
+        Parameters { values: values }
+    }
+
+    /// Returns the value of a parameter
+    /// # Arguments
+    /// * `name` - name of the parameter
+    /// returns the value or None if the parameter is not defined
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(|value| value.as_str())
+    }
+
+    /// Returns the value of a parameter converted to the given type (e.g. i64, f64, bool)
+    /// # Arguments
+    /// * `name` - name of the parameter
+    /// returns the value, None if the parameter is not defined or an error if it cannot be converted
+    pub fn get_as<T: FromStr>(&self, name: &str) -> Result<Option<T>, ProcessError> {
+        match self.get(name) {
+            Some(value) => match value.parse::<T>() {
+                Ok(value) => Ok(Some(value)),
+                Err(_err) => Err(ProcessError::InvalidParameter(format!(
+                    "Parameter `{}` has invalid value \"{}\"",
+                    name, value
+                ))),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the value of a parameter that must be defined converted to the given type (e.g. String, i64, f64, bool)
+    /// # Arguments
+    /// * `name` - name of the parameter
+    /// returns the value or an error if the parameter is not defined or cannot be converted
+    pub fn get_required<T: FromStr>(&self, name: &str) -> Result<T, ProcessError> {
+        match self.get_as::<T>(name)? {
+            Some(value) => Ok(value),
+            None => Err(ProcessError::InvalidParameter(format!(
+                "Parameter `{}` is missing",
+                name
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for ProcessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProcessError::InvalidParameter(message) => write!(f, "Invalid parameter: {}", message),
+            ProcessError::InvalidData(message) => write!(f, "Invalid data: {}", message),
+            ProcessError::ExecutionError(message) => write!(f, "Execution error: {}", message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::interfaces::process::interface::{Parameters, ProcessError};
+
+    #[test]
+    // Test converting parameters to typed values
+    fn test_typed_parameters() {
+        let mut values: HashMap<String, String> = HashMap::new();
+        values.insert("text".to_string(), "Hello, World!".to_string());
+        values.insert("count".to_string(), "3".to_string());
+        let parameters = Parameters::new(values);
+        assert_eq!(parameters.get("text"), Some("Hello, World!"));
+        assert_eq!(parameters.get_as::<i64>("count").unwrap(), Some(3));
+        assert_eq!(parameters.get_as::<i64>("limit").unwrap(), None);
+        assert_eq!(
+            parameters.get_required::<String>("text").unwrap(),
+            "Hello, World!"
+        );
+        match parameters.get_as::<i64>("text") {
+            Err(ProcessError::InvalidParameter(_)) => (),
+            _ => panic!("Invalid value not detected"),
+        }
+        match parameters.get_required::<bool>("enabled") {
+            Err(ProcessError::InvalidParameter(message)) => assert!(message.contains("missing")),
+            _ => panic!("Missing parameter not detected"),
+        }
+    }
+}
@@ -1,21 +1,42 @@
 //! Module Interface (MI) for a Process in ZuStDPipe - this needs to be implemented by any plugable process
 // The data is exchanged using Arrow-IPC
 
-use arrow::ipc::RecordBatch;
+use std::collections::HashMap;
 
+use arrow::record_batch::RecordBatch;
 
-pub struct Parameters<'a> {
-    pub metadata: RecordBatch<'a>,
-    pub data: RecordBatch<'a>
+/// Parameters of the process as defined in the pipeline
+pub struct Parameters {
+    pub values: HashMap<String, String>,
 }
 
-pub struct Result<'a> {
-   pub data: RecordBatch<'a> 
+/// Error of a process. It is reported to ZuStDPipe and ends up in the status of the job
+#[derive(Debug, Clone)]
+pub enum ProcessError {
+    /// a parameter is missing or has an invalid value
+    InvalidParameter(String),
+    /// the data cannot be processed
+    InvalidData(String),
+    /// processing failed for another reason
+    ExecutionError(String),
 }
 
-
 /// Implementation of the process
+/// Use `export_process!` to export it as the entry point of a WASM module
+/// Use `export_stateful_process!` instead if the process keeps state across all data of a job. Only then the same process is used for all data of a job and init and finish are called
 pub trait Process {
     fn new() -> Self;
-     fn execute(&self,params: Parameters) -> Option<Result>;
-}
\ No newline at end of file
+    /// Called once before the first data of a job is processed
+    fn init(&mut self, _parameters: &Parameters) -> Result<(), ProcessError> {
+        Ok(())
+    }
+    fn execute(
+        &mut self,
+        parameters: &Parameters,
+        data: Vec<RecordBatch>,
+    ) -> Result<Vec<RecordBatch>, ProcessError>;
+    /// Called once after all data of a job has been processed. Returns the final output (e.g. of an aggregation)
+    fn finish(&mut self) -> Result<Vec<RecordBatch>, ProcessError> {
+        Ok(Vec::new())
+    }
+}
@@ -1,3 +1,3 @@
 pub mod interfaces;
 pub mod modules;
-pub mod serialization;
\ No newline at end of file
+pub mod serialization;
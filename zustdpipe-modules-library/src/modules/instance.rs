@@ -0,0 +1,197 @@
+//! The process of a module. It is created on first use and lives as long as the WASM module instance, so it can keep state across all data of a job
+
+use std::cell::RefCell;
+use std::thread::LocalKey;
+
+use arrow::datatypes::SchemaRef;
+
+use crate::interfaces::process::interface::{Process, ProcessError};
+use crate::modules::wasm::{
+    convert_arrow_to_parameters, convert_arrow_to_raw_memory, convert_batches_to_arrow,
+    convert_raw_memory_to_arrow, set_last_error, STATUS_ERROR, STATUS_OK,
+};
+
+/// Process of a module
+pub type ProcessInstance<P> = &'static LocalKey<RefCell<Option<P>>>;
+
+/// Calls a function with the process of the module. The process is created if it does not exist yet
+/// # Arguments
+/// * `process_instance` - process of the module
+/// * `f` - function to call
+/// returns the result of the function
+pub fn with_process<P: Process, R>(
+    process_instance: ProcessInstance<P>,
+    f: impl FnOnce(&mut P) -> R,
+) -> R {
+    process_instance.with(|process| {
+        let mut process = process.borrow_mut();
+        let process = process.get_or_insert_with(|| Process::new());
+        f(process)
+    })
+}
+
+/// Initializes the process before the first data of a job is processed
+/// # Arguments
+/// * `process_instance` - process of the module
+/// * `meta_data_offset` - pointer to the parameters in Arrow IPC format
+/// * `meta_data_size` - size of the parameters
+/// returns a status code
+pub fn module_init<P: Process>(
+    process_instance: ProcessInstance<P>,
+    meta_data_offset: *mut u8,
+    meta_data_size: usize,
+) -> i32 {
+    let input_arrow_meta_data = match convert_raw_memory_to_arrow(meta_data_offset, meta_data_size)
+    {
+        Some(data) => data,
+        None => {
+            set_last_error(&ProcessError::InvalidParameter(
+                "Cannot read parameters".to_string(),
+            ));
+            return STATUS_ERROR;
+        }
+    };
+    let result = match convert_arrow_to_parameters(input_arrow_meta_data) {
+        Ok(parameters) => with_process(process_instance, |process| process.init(&parameters)),
+        Err(error) => Err(error),
+    };
+    match result {
+        Ok(()) => STATUS_OK,
+        Err(error) => {
+            set_last_error(&error);
+            STATUS_ERROR
+        }
+    }
+}
+
+/// Finishes the process after all data of a job has been processed
+/// # Arguments
+/// * `process_instance` - process of the module
+/// returns a pointer to the result descriptor of the final output (see `convert_arrow_to_raw_memory`) or 0 if there is none. If the process failed then the error can be read using `zustdp_module_wasm_last_error`
+pub fn module_finish<P: Process>(process_instance: ProcessInstance<P>) -> usize {
+    let result = with_process(process_instance, |process| process.finish());
+    let batches = match result {
+        Ok(batches) => batches,
+        Err(error) => {
+            set_last_error(&error);
+            return 0;
+        }
+    };
+    let schema: SchemaRef = match batches.first() {
+        Some(batch) => batch.schema(),
+        None => return 0,
+    };
+    let result_ptr = match convert_batches_to_arrow(&schema, &batches) {
+        Ok(stream_writer) => convert_arrow_to_raw_memory(stream_writer),
+        Err(error) => Err(error),
+    };
+    match result_ptr {
+        Ok(result_ptr) => result_ptr,
+        Err(error) => {
+            set_last_error(&error);
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::sync::Arc;
+
+    use arrow::array::{ArrayRef, Int64Array, MapBuilder, StringBuilder};
+    use arrow::ipc::reader::StreamReader;
+    use arrow::ipc::writer::StreamWriter;
+    use arrow::record_batch::RecordBatch;
+
+    use crate::interfaces::process::interface::{Parameters, Process, ProcessError};
+    use crate::modules::wasm::{zustdp_module_wasm_allocate, STATUS_OK};
+
+    /// Counts the record batches of a job and returns the count when it is finished
+    struct CountProcess {
+        count: i64,
+    }
+
+    impl Process for CountProcess {
+        fn new() -> Self {
+            CountProcess { count: -1 }
+        }
+
+        fn init(&mut self, _parameters: &Parameters) -> Result<(), ProcessError> {
+            self.count = 0;
+            Ok(())
+        }
+
+        fn execute(
+            &mut self,
+            _parameters: &Parameters,
+            data: Vec<RecordBatch>,
+        ) -> Result<Vec<RecordBatch>, ProcessError> {
+            self.count += data.len() as i64;
+            Ok(Vec::new())
+        }
+
+        fn finish(&mut self) -> Result<Vec<RecordBatch>, ProcessError> {
+            let count = Arc::new(Int64Array::from(vec![self.count])) as ArrayRef;
+            Ok(vec![
+                RecordBatch::try_from_iter(vec![("count", count)]).unwrap()
+            ])
+        }
+    }
+
+    thread_local!(
+        static COUNT_PROCESS: RefCell<Option<CountProcess>> = RefCell::new(None);
+    );
+
+    #[test]
+    // Test that the process is initialized, keeps its state across calls and returns its final output when it is finished
+    fn test_module_lifecycle() {
+        let mut map_builder = MapBuilder::new(None, StringBuilder::new(), StringBuilder::new());
+        map_builder.append(true).unwrap();
+        let parameters = Arc::new(map_builder.finish()) as ArrayRef;
+        let parameters_batch =
+            RecordBatch::try_from_iter(vec![("parameters", parameters)]).unwrap();
+        let mut stream_writer =
+            StreamWriter::try_new(Vec::new(), &parameters_batch.schema()).unwrap();
+        stream_writer.write(&parameters_batch).unwrap();
+        stream_writer.finish().unwrap();
+        let serialized_parameters = stream_writer.into_inner().unwrap();
+        let meta_data_ptr = zustdp_module_wasm_allocate(serialized_parameters.len()) as *mut u8;
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                serialized_parameters.as_ptr(),
+                meta_data_ptr,
+                serialized_parameters.len(),
+            )
+        };
+        assert_eq!(
+            super::module_init(&COUNT_PROCESS, meta_data_ptr, serialized_parameters.len()),
+            STATUS_OK
+        );
+        let parameters = Parameters::new(Default::default());
+        for _ in 0..3 {
+            super::with_process(&COUNT_PROCESS, |process| {
+                process.execute(&parameters, vec![parameters_batch.clone()])
+            })
+            .unwrap();
+        }
+        let result_offset = super::module_finish(&COUNT_PROCESS);
+        assert_ne!(result_offset, 0);
+        // the result descriptor contains pointer and length as u64
+        let descriptor = unsafe { std::slice::from_raw_parts(result_offset as *const u8, 16) };
+        let result_ptr = u64::from_le_bytes(descriptor[0..8].try_into().unwrap()) as usize;
+        let result_len = u64::from_le_bytes(descriptor[8..16].try_into().unwrap()) as usize;
+        let serialized_result =
+            unsafe { std::slice::from_raw_parts(result_ptr as *const u8, result_len) };
+        let batches: Vec<RecordBatch> = StreamReader::try_new(serialized_result, None)
+            .unwrap()
+            .map(|batch| batch.unwrap())
+            .collect();
+        let count = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        assert_eq!(count.value(0), 3);
+    }
+}
@@ -0,0 +1,39 @@
+//! Logging of messages by the module. ZuStDPipe provides the logging as an imported host function and adds the name of the module and the id of the job as context, so the messages end up in the log of ZuStDPipe
+
+/// Level of a log message
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogLevel {
+    Error = 1,
+    Warn = 2,
+    Info = 3,
+    Debug = 4,
+    Trace = 5,
+}
+
+#[cfg(target_family = "wasm")]
+#[link(wasm_import_module = "zustdp_host")]
+extern "C" {
+    fn zustdp_host_log(level: i32, message_ptr: *const u8, message_len: usize) -> i32;
+}
+
+/// Logs a message using the log of ZuStDPipe
+/// # Arguments
+/// * `level` - level of the message
+/// * `message` - message
+/// returns true if the message has been logged
+#[cfg(target_family = "wasm")]
+pub fn log(level: LogLevel, message: &str) -> bool {
+    let code: i32 = unsafe { zustdp_host_log(level as i32, message.as_ptr(), message.len()) };
+    code == crate::modules::wasm::STATUS_OK
+}
+
+/// Logs a message to stderr if the module is not compiled to WebAssembly, e.g. to run its tests natively
+/// # Arguments
+/// * `level` - level of the message
+/// * `message` - message
+/// returns true if the message has been logged
+#[cfg(not(target_family = "wasm"))]
+pub fn log(level: LogLevel, message: &str) -> bool {
+    eprintln!("{:?}: {}", level, message);
+    true
+}
@@ -0,0 +1,22 @@
+//! Embed the manifest of a module into the custom section "zustdp_manifest" of the WASM module. ZuStDPipe reads it to validate the definition of processes using the module
+
+/// Name of the custom section containing the manifest
+pub const MANIFEST_SECTION_NAME: &str = "zustdp_manifest";
+
+/// Embeds a manifest file (YAML) into the custom section "zustdp_manifest" of the module
+/// The manifest declares the name, version, input/output schema and parameters of the module
+/// # Arguments
+/// * `path` - path to the manifest file relative to the source file using the macro
+///
+/// # Examples
+/// ```ignore
+/// zustdpipe_modules_library::zustdp_module_manifest!("../manifest.yml");
+/// ```
+#[macro_export]
+macro_rules! zustdp_module_manifest {
+    ($path:literal) => {
+        #[link_section = "zustdp_manifest"]
+        #[used]
+        static ZUSTDP_MODULE_MANIFEST: [u8; include_bytes!($path).len()] = *include_bytes!($path);
+    };
+}
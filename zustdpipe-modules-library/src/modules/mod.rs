@@ -1 +1,6 @@
-pub mod wasm;
\ No newline at end of file
+pub mod instance;
+pub mod log;
+pub mod manifest;
+pub mod state;
+pub mod stream;
+pub mod wasm;
@@ -0,0 +1,117 @@
+//! Access to the state ZuStDPipe keeps for the module across job runs. The state is a key value store provided by ZuStDPipe as imported host functions
+//! Each step of a pipeline has its own state, so different steps using the same module do not see each other's keys
+
+#[cfg(target_family = "wasm")]
+use crate::modules::wasm::{zustdp_module_wasm_deallocate, STATUS_OK};
+#[cfg(not(target_family = "wasm"))]
+use std::cell::RefCell;
+#[cfg(not(target_family = "wasm"))]
+use std::collections::HashMap;
+
+#[cfg(target_family = "wasm")]
+#[link(wasm_import_module = "zustdp_host")]
+extern "C" {
+    fn zustdp_host_state_get(key_ptr: *const u8, key_len: usize) -> usize;
+    fn zustdp_host_state_put(
+        key_ptr: *const u8,
+        key_len: usize,
+        value_ptr: *const u8,
+        value_len: usize,
+    ) -> i32;
+    fn zustdp_host_state_delete(key_ptr: *const u8, key_len: usize) -> i32;
+}
+
+/// Returns the value of a key from the state
+/// # Arguments
+/// * `key` - key
+/// returns the value or None if the key does not exist
+#[cfg(target_family = "wasm")]
+pub fn state_get(key: &[u8]) -> Option<Vec<u8>> {
+    let descriptor_offset: usize = unsafe { zustdp_host_state_get(key.as_ptr(), key.len()) };
+    if descriptor_offset == 0 {
+        return None;
+    }
+    // the result descriptor contains an u64 pointer to the value and another u64 containing its size (both little endian)
+    let descriptor: &[u8] =
+        unsafe { std::slice::from_raw_parts(descriptor_offset as *const u8, 16) };
+    let value_ptr = u64::from_le_bytes(descriptor[0..8].try_into().unwrap()) as usize as *const u8;
+    let value_len = u64::from_le_bytes(descriptor[8..16].try_into().unwrap()) as usize;
+    let value: Vec<u8> = unsafe { std::slice::from_raw_parts(value_ptr, value_len) }.to_vec();
+    // the memory has been allocated by ZuStDPipe using zustdp_module_wasm_allocate
+    zustdp_module_wasm_deallocate(value_ptr);
+    zustdp_module_wasm_deallocate(descriptor_offset as *const u8);
+    Some(value)
+}
+
+/// Sets the value of a key in the state
+/// # Arguments
+/// * `key` - key
+/// * `value` - value
+/// returns true if the value has been set
+#[cfg(target_family = "wasm")]
+pub fn state_put(key: &[u8], value: &[u8]) -> bool {
+    let code: i32 =
+        unsafe { zustdp_host_state_put(key.as_ptr(), key.len(), value.as_ptr(), value.len()) };
+    code == STATUS_OK
+}
+
+/// Deletes a key from the state
+/// # Arguments
+/// * `key` - key
+/// returns true if the key has been deleted or did not exist
+#[cfg(target_family = "wasm")]
+pub fn state_delete(key: &[u8]) -> bool {
+    let code: i32 = unsafe { zustdp_host_state_delete(key.as_ptr(), key.len()) };
+    code == STATUS_OK
+}
+
+#[cfg(not(target_family = "wasm"))]
+thread_local! {
+    /// State of the module if it is not compiled to WebAssembly
+    static NATIVE_STATE: RefCell<HashMap<Vec<u8>, Vec<u8>>> = RefCell::new(HashMap::new());
+}
+
+/// Returns the value of a key from an in-memory state if the module is not compiled to WebAssembly, e.g. to run its tests natively
+/// # Arguments
+/// * `key` - key
+/// returns the value or None if the key does not exist
+#[cfg(not(target_family = "wasm"))]
+pub fn state_get(key: &[u8]) -> Option<Vec<u8>> {
+    NATIVE_STATE.with(|state| state.borrow().get(key).cloned())
+}
+
+/// Sets the value of a key in an in-memory state if the module is not compiled to WebAssembly, e.g. to run its tests natively
+/// # Arguments
+/// * `key` - key
+/// * `value` - value
+/// returns true if the value has been set
+#[cfg(not(target_family = "wasm"))]
+pub fn state_put(key: &[u8], value: &[u8]) -> bool {
+    NATIVE_STATE.with(|state| state.borrow_mut().insert(key.to_vec(), value.to_vec()));
+    true
+}
+
+/// Deletes a key from an in-memory state if the module is not compiled to WebAssembly, e.g. to run its tests natively
+/// # Arguments
+/// * `key` - key
+/// returns true if the key has been deleted or did not exist
+#[cfg(not(target_family = "wasm"))]
+pub fn state_delete(key: &[u8]) -> bool {
+    NATIVE_STATE.with(|state| state.borrow_mut().remove(key));
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{state_delete, state_get, state_put};
+
+    #[test]
+    // Test that the state can be used when the module is not compiled to WebAssembly
+    fn test_native_state() {
+        assert_eq!(state_get(b"count"), None);
+        assert!(state_put(b"count", &1u64.to_le_bytes()));
+        assert_eq!(state_get(b"count"), Some(1u64.to_le_bytes().to_vec()));
+        assert!(state_delete(b"count"));
+        assert_eq!(state_get(b"count"), None);
+    }
+}
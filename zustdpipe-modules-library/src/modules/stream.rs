@@ -0,0 +1,150 @@
+//! Process data as a stream: ZuStDPipe pushes the record batches one by one and pulls the output of each record batch before pushing the next one, so the memory of the module stays bounded
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::thread::LocalKey;
+
+use crate::interfaces::process::interface::{Parameters, Process, ProcessError};
+use crate::modules::instance::{with_process, ProcessInstance};
+use crate::modules::wasm::{
+    convert_arrow_to_batches, convert_arrow_to_parameters, convert_batches_to_arrow,
+    convert_bytes_to_raw_memory, convert_raw_memory_to_arrow, set_last_error, STATUS_ERROR,
+    STATUS_OK,
+};
+
+/// Status code of the push function if there is pending output that must be pulled first
+pub const STREAM_STATUS_OUTPUT_PENDING: i32 = 1;
+
+/// State of a stream processed by the process of the module
+pub struct ProcessStream {
+    parameters: Parameters,
+    /// output not yet pulled by ZuStDPipe in Arrow IPC format
+    pending: VecDeque<Vec<u8>>,
+}
+
+/// Starts a stream. A running stream is discarded
+/// # Arguments
+/// * `stream` - state of the stream
+/// * `meta_data_offset` - pointer to the parameters in Arrow IPC format
+/// * `meta_data_size` - size of the parameters
+/// returns a status code
+pub fn stream_start(
+    stream: &'static LocalKey<RefCell<Option<ProcessStream>>>,
+    meta_data_offset: *mut u8,
+    meta_data_size: usize,
+) -> i32 {
+    let input_arrow_meta_data = match convert_raw_memory_to_arrow(meta_data_offset, meta_data_size)
+    {
+        Some(data) => data,
+        None => {
+            return stream_error(ProcessError::InvalidParameter(
+                "Cannot read parameters".to_string(),
+            ))
+        }
+    };
+    let parameters = match convert_arrow_to_parameters(input_arrow_meta_data) {
+        Ok(parameters) => parameters,
+        Err(error) => return stream_error(error),
+    };
+    stream.with(|stream| {
+        *stream.borrow_mut() = Some(ProcessStream {
+            parameters: parameters,
+            pending: VecDeque::new(),
+        })
+    });
+    STATUS_OK
+}
+
+/// Pushes a record batch to the stream and executes the process on it
+/// # Arguments
+/// * `process_instance` - process of the module
+/// * `stream` - state of the stream
+/// * `data_offset` - pointer to an Arrow IPC stream containing the record batch
+/// * `data_size` - size of the data
+/// returns a status code. If the output of the previous record batch has not been pulled yet then the record batch is not accepted
+pub fn stream_push<P: Process>(
+    process_instance: ProcessInstance<P>,
+    stream: &'static LocalKey<RefCell<Option<ProcessStream>>>,
+    data_offset: *mut u8,
+    data_size: usize,
+) -> i32 {
+    let result = stream.with(|stream| -> Result<i32, ProcessError> {
+        let mut stream = stream.borrow_mut();
+        let stream = match stream.as_mut() {
+            Some(stream) => stream,
+            None => {
+                return Err(ProcessError::ExecutionError(
+                    "Stream has not been started".to_string(),
+                ))
+            }
+        };
+        // back-pressure
+        if !stream.pending.is_empty() {
+            return Ok(STREAM_STATUS_OUTPUT_PENDING);
+        }
+        let input_arrow_data = match convert_raw_memory_to_arrow(data_offset, data_size) {
+            Some(data) => data,
+            None => return Err(ProcessError::InvalidData("Cannot read data".to_string())),
+        };
+        let (_input_schema, data) = convert_arrow_to_batches(input_arrow_data)?;
+        let result = with_process(process_instance, |process| {
+            process.execute(&stream.parameters, data)
+        })?;
+        // each output record batch can be pulled on its own
+        for batch in result {
+            let stream_writer = convert_batches_to_arrow(&batch.schema(), &[batch])?;
+            match stream_writer.into_inner() {
+                Ok(serialized_batch) => stream.pending.push_back(serialized_batch),
+                Err(err) => {
+                    return Err(ProcessError::InvalidData(format!(
+                        "Cannot write result: {}",
+                        err
+                    )))
+                }
+            };
+        }
+        Ok(STATUS_OK)
+    });
+    match result {
+        Ok(code) => code,
+        Err(error) => stream_error(error),
+    }
+}
+
+/// Pulls the next pending output of the stream
+/// # Arguments
+/// * `stream` - state of the stream
+/// returns a pointer to the result descriptor (see `convert_arrow_to_raw_memory`) or 0 if there is no pending output
+pub fn stream_pull(stream: &'static LocalKey<RefCell<Option<ProcessStream>>>) -> usize {
+    let serialized_batch = stream.with(|stream| match stream.borrow_mut().as_mut() {
+        Some(stream) => stream.pending.pop_front(),
+        None => None,
+    });
+    match serialized_batch {
+        Some(serialized_batch) => convert_bytes_to_raw_memory(serialized_batch),
+        None => 0,
+    }
+}
+
+/// Ends the stream. Pending output can still be pulled
+/// # Arguments
+/// * `stream` - state of the stream
+/// returns a status code
+pub fn stream_end(stream: &'static LocalKey<RefCell<Option<ProcessStream>>>) -> i32 {
+    let started = stream.with(|stream| stream.borrow().is_some());
+    if !started {
+        return stream_error(ProcessError::ExecutionError(
+            "Stream has not been started".to_string(),
+        ));
+    }
+    STATUS_OK
+}
+
+/// Stores the error of a stream function
+/// # Arguments
+/// * `error` - error of the process
+/// returns the status code for errors
+fn stream_error(error: ProcessError) -> i32 {
+    set_last_error(&error);
+    STATUS_ERROR
+}
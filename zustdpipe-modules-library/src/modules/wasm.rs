@@ -4,13 +4,16 @@ use std::cell::Cell;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::io::BufReader;
-use std::io::BufWriter;
 use std::mem::ManuallyDrop;
 
-
+use arrow::array::{Array, MapArray, StringArray};
+use arrow::datatypes::SchemaRef;
 use arrow::ipc::reader::StreamReader;
 use arrow::ipc::writer::StreamWriter;
+use arrow::record_batch::RecordBatch;
 
+use crate::interfaces::process::interface::{Parameters, Process, ProcessError};
+use crate::modules::instance::{with_process, ProcessInstance};
 
 // Global variable to keep track of allocated memory
 // Note: This is really an execption as allocate by the app to the module should have only for parameters
@@ -20,22 +23,43 @@ thread_local!(
         RefCell::new(HashMap::new());
 );
 
+// Error of the last failed call of the raw process entry. ZuStDPipe reads it using zustdp_module_wasm_last_error
+thread_local!(
+    static LAST_ERROR: RefCell<Option<String>> = RefCell::new(None);
+);
+
+/// Version of the interface between ZuStDPipe and the module implemented by this library
+/// ZuStDPipe checks it when loading a module, so it does not misread the data returned by a module implementing another version
+/// Version 2 returns a result descriptor of two little endian u64 independent of the width of pointers, so modules can be compiled to wasm32 and wasm64
+pub const ZUSTDP_MODULE_ABI_VERSION: u32 = 2;
+
+/// Status code of a function if it was successful
+pub const STATUS_OK: i32 = 0;
+/// Status code of a function if it failed. The error can be read using `zustdp_module_wasm_last_error`
+pub const STATUS_ERROR: i32 = -1;
 
 enum MemoryAreasReturnCode {
     Success = 0,
     ErrorMemmoryNotAllocated = -1,
 }
 
+/// Returns the version of the interface between ZuStDPipe and the module
+/// returns the version implemented by this library
+#[no_mangle]
+pub extern "C" fn zustdp_module_abi_version() -> u32 {
+    ZUSTDP_MODULE_ABI_VERSION
+}
+
 /// Allocate some memory for the application to write data for the module
 /// Note: It is up to the application (and not the WASM module) to provide enough pages, so the module does not run out of memory
 /// # Arguments
 /// * `size` - size of memory to allocaten
 /// returns a pointer to the allocated memory area
 #[no_mangle]
-pub extern "C" fn zustdp_module_wasm_allocate(size: u32) -> *const u8 {
+pub extern "C" fn zustdp_module_wasm_allocate(size: usize) -> *const u8 {
     // create a Box with empty memory
-    let alloc_box = ManuallyDrop::new(vec![0u8; size as usize].into_boxed_slice());
-    return allocate(size as usize, alloc_box);
+    let alloc_box = ManuallyDrop::new(vec![0u8; size].into_boxed_slice());
+    allocate(size, alloc_box)
 }
 
 /// Deallocates existing memory for the purpose of the application
@@ -53,10 +77,18 @@ pub extern "C" fn zustdp_module_wasm_deallocate(ptr: *const u8) -> i32 {
         None => return MemoryAreasReturnCode::ErrorMemmoryNotAllocated as i32,
     };
     // return success
-    return MemoryAreasReturnCode::Success as i32;
+    MemoryAreasReturnCode::Success as i32
 }
 
-
+/// Returns the error of the last failed call of the raw process entry
+/// returns a pointer to a memory location containing an u64 pointer to the error message (UTF-8) and another u64 containing its size or 0 if there is no error
+#[no_mangle]
+pub extern "C" fn zustdp_module_wasm_last_error() -> usize {
+    match LAST_ERROR.with(|last_error| last_error.borrow_mut().take()) {
+        Some(message) => convert_bytes_to_raw_memory(message.into_bytes()),
+        None => 0,
+    }
+}
 
 /// Allocate some memory for the application to write data for the module
 /// Note: It is up to the application (and not the WASM module) to provide enough pages, so the module does not run out of memory
@@ -68,7 +100,7 @@ pub fn allocate(size: usize, alloc_box: ManuallyDrop<Box<[u8]>>) -> *const u8 {
     let result_ptr: *const u8 = alloc_box.as_ptr();
     // save allocated memory to avoid it is cleaned up after function exits
     MEMORY_AREAS.with(|mem_map| mem_map.borrow_mut().insert(result_ptr, (size, alloc_box)));
-    return result_ptr;
+    result_ptr
 }
 
 /// Validates if a pointer has been properly allocated in this module
@@ -81,37 +113,56 @@ pub fn validate_pointer(ptr: *const u8) -> usize {
         Some(x) => cell.set(x.0),
         None => cell.set(0),
     });
-    return cell.get();
+    cell.get()
 }
 
-
 /// Converts a raw memory pointer to data in Arrow format to an arrow StreamReader
 /// # Arguments
 /// * `raw_memory_offset` - pointer to the data
 /// * `raw_memory_size` - size of the data
 /// returns a StreamReader on the Arrow Data or None if there is an error related to the memory location
-pub fn convert_raw_memory_to_arrow(    raw_memory_offset: *mut u32,
-    raw_memory_size: u32) -> Option<StreamReader<BufReader<&'static [u8]>>> {
-        let expected_size_raw_memory: usize = validate_pointer(raw_memory_offset as *const u8);
-        if (expected_size_raw_memory == 0) | (expected_size_raw_memory != expected_size_raw_memory as usize) {
-            return None;
-        };
-        match StreamReader::try_new(unsafe{std::slice::from_raw_parts(raw_memory_offset as *mut u8, raw_memory_size as usize)}, None) {
-            Ok(reader) => Some(reader),
-            Err(_error) => None
-        }
+pub fn convert_raw_memory_to_arrow(
+    raw_memory_offset: *mut u8,
+    raw_memory_size: usize,
+) -> Option<StreamReader<BufReader<&'static [u8]>>> {
+    let expected_size_raw_memory: usize = validate_pointer(raw_memory_offset as *const u8);
+    if (expected_size_raw_memory == 0) | (expected_size_raw_memory != raw_memory_size) {
+        return None;
+    };
+    match StreamReader::try_new(
+        unsafe { std::slice::from_raw_parts(raw_memory_offset as *const u8, raw_memory_size) },
+        None,
+    ) {
+        Ok(reader) => Some(reader),
+        Err(_error) => None,
+    }
 }
 
-
-/// Converts an arrow StreamWriter to a raw memory pointer 
+/// Converts an arrow StreamWriter to a raw memory pointer
 /// # Arguments
 /// * `stream_writer` - Arrow StreamWriter
-/// returns a u32 pointing to a memory location containing an u32 pointer and another u32 containing the size of the data
-pub fn convert_arrow_to_raw_memory(stream_writer: StreamWriter<Vec<u8>>) -> u32 {
-    let serialized_result_batch: Vec<u8> = stream_writer.into_inner().unwrap();
+/// returns a pointer to a memory location containing the result descriptor: an u64 pointer and another u64 containing the size of the data (both little endian) or an error if the data cannot be written
+/// The descriptor has the same layout for wasm32 and wasm64, so ZuStDPipe does not depend on the width of pointers of the module
+pub fn convert_arrow_to_raw_memory(
+    stream_writer: StreamWriter<Vec<u8>>,
+) -> Result<usize, ProcessError> {
+    match stream_writer.into_inner() {
+        Ok(serialized_result_batch) => Ok(convert_bytes_to_raw_memory(serialized_result_batch)),
+        Err(err) => Err(ProcessError::InvalidData(format!(
+            "Cannot write result: {}",
+            err
+        ))),
+    }
+}
+
+/// Converts data to a raw memory pointer
+/// # Arguments
+/// * `data` - data to hand over to ZuStDPipe
+/// returns a pointer to a memory location containing the result descriptor: an u64 pointer and another u64 containing the size of the data (both little endian)
+pub fn convert_bytes_to_raw_memory(data: Vec<u8>) -> usize {
     // allocate memory for the answer
     let serialized_result_batch_alloc: ManuallyDrop<Box<[u8]>> =
-        ManuallyDrop::new(serialized_result_batch.into_boxed_slice());
+        ManuallyDrop::new(data.into_boxed_slice());
     let serialized_result_batch_alloc_len: usize = serialized_result_batch_alloc.len();
 
     let serialized_result_batch_ptr = allocate(
@@ -120,10 +171,10 @@ pub fn convert_arrow_to_raw_memory(stream_writer: StreamWriter<Vec<u8>>) -> u32
     );
     // return position of WASM memory where we can find a offset, length pair
     let mut vec_meta: Vec<u8> = Vec::new();
-    let serialized_result_batch_ptr_array: [u8; (usize::BITS / 8) as usize] =
-        (serialized_result_batch_ptr as usize).to_le_bytes();
-    let serialized_result_batch_alloc_len: [u8; (usize::BITS / 8) as usize] =
-        serialized_result_batch_alloc_len.to_le_bytes();
+    let serialized_result_batch_ptr_array: [u8; (u64::BITS / 8) as usize] =
+        (serialized_result_batch_ptr as usize as u64).to_le_bytes();
+    let serialized_result_batch_alloc_len: [u8; (u64::BITS / 8) as usize] =
+        (serialized_result_batch_alloc_len as u64).to_le_bytes();
     for byte in serialized_result_batch_ptr_array {
         vec_meta.push(byte);
     }
@@ -137,6 +188,313 @@ pub fn convert_arrow_to_raw_memory(stream_writer: StreamWriter<Vec<u8>>) -> u32
         ManuallyDrop::new(serialized_result_batch_meta),
     );
 
-    return serialized_result_batch_meta_ptr as u32;
+    serialized_result_batch_meta_ptr as usize
+}
+
+/// Converts the parameters of a process in Arrow format (a map column "parameters") to Parameters
+/// # Arguments
+/// * `stream_reader` - Arrow StreamReader on the parameters
+/// returns the parameters
+pub fn convert_arrow_to_parameters(
+    stream_reader: StreamReader<BufReader<&'static [u8]>>,
+) -> Result<Parameters, ProcessError> {
+    let mut values: HashMap<String, String> = HashMap::new();
+    for batch in stream_reader {
+        let batch = match batch {
+            Ok(batch) => batch,
+            Err(err) => {
+                return Err(ProcessError::InvalidParameter(format!(
+                    "Cannot read parameters: {}",
+                    err
+                )))
+            }
+        };
+        let parameters = match batch
+            .column_by_name("parameters")
+            .and_then(|column| column.as_any().downcast_ref::<MapArray>())
+        {
+            Some(parameters) => parameters,
+            None => {
+                return Err(ProcessError::InvalidParameter(
+                    "Parameters are not a map".to_string(),
+                ))
+            }
+        };
+        let keys = parameters.keys().as_any().downcast_ref::<StringArray>();
+        let map_values = parameters.values().as_any().downcast_ref::<StringArray>();
+        match (keys, map_values) {
+            (Some(keys), Some(map_values)) => {
+                for index in 0..keys.len() {
+                    values.insert(
+                        keys.value(index).to_string(),
+                        map_values.value(index).to_string(),
+                    );
+                }
+            }
+            _ => {
+                return Err(ProcessError::InvalidParameter(
+                    "Parameters are not a map of strings".to_string(),
+                ))
+            }
+        };
+    }
+    Ok(Parameters::new(values))
+}
+
+/// Reads all record batches of an Arrow StreamReader
+/// # Arguments
+/// * `stream_reader` - Arrow StreamReader on the data
+/// returns the schema and the record batches
+pub fn convert_arrow_to_batches(
+    stream_reader: StreamReader<BufReader<&'static [u8]>>,
+) -> Result<(SchemaRef, Vec<RecordBatch>), ProcessError> {
+    let schema: SchemaRef = stream_reader.schema();
+    let mut batches: Vec<RecordBatch> = Vec::new();
+    for batch in stream_reader {
+        match batch {
+            Ok(batch) => batches.push(batch),
+            Err(err) => {
+                return Err(ProcessError::InvalidData(format!(
+                    "Cannot read data: {}",
+                    err
+                )))
+            }
+        };
+    }
+    Ok((schema, batches))
+}
 
+/// Writes record batches in Arrow IPC format
+/// # Arguments
+/// * `schema` - schema of the record batches
+/// * `batches` - record batches
+/// returns an Arrow StreamWriter containing the record batches
+pub fn convert_batches_to_arrow(
+    schema: &SchemaRef,
+    batches: &[RecordBatch],
+) -> Result<StreamWriter<Vec<u8>>, ProcessError> {
+    let mut stream_writer = match StreamWriter::try_new(Vec::new(), schema) {
+        Ok(stream_writer) => stream_writer,
+        Err(err) => {
+            return Err(ProcessError::InvalidData(format!(
+                "Cannot write result: {}",
+                err
+            )))
+        }
+    };
+    for batch in batches {
+        match stream_writer.write(batch) {
+            Ok(()) => (),
+            Err(err) => {
+                return Err(ProcessError::InvalidData(format!(
+                    "Cannot write result: {}",
+                    err
+                )))
+            }
+        };
+    }
+    match stream_writer.finish() {
+        Ok(()) => Ok(stream_writer),
+        Err(err) => Err(ProcessError::InvalidData(format!(
+            "Cannot write result: {}",
+            err
+        ))),
+    }
+}
+
+/// Executes a process on data in raw memory
+/// # Arguments
+/// * `process_instance` - process of the module
+/// * `meta_data_offset` - pointer to the parameters in Arrow IPC format
+/// * `meta_data_size` - size of the parameters
+/// * `data_offset` - pointer to the data in Arrow IPC format
+/// * `data_size` - size of the data
+/// returns a pointer to the result descriptor
+fn execute_raw_process<P: Process>(
+    process_instance: ProcessInstance<P>,
+    meta_data_offset: *mut u8,
+    meta_data_size: usize,
+    data_offset: *mut u8,
+    data_size: usize,
+) -> Result<usize, ProcessError> {
+    let input_arrow_meta_data = match convert_raw_memory_to_arrow(meta_data_offset, meta_data_size)
+    {
+        Some(data) => data,
+        None => {
+            return Err(ProcessError::InvalidParameter(
+                "Cannot read parameters".to_string(),
+            ))
+        }
+    };
+    let input_arrow_data = match convert_raw_memory_to_arrow(data_offset, data_size) {
+        Some(data) => data,
+        None => return Err(ProcessError::InvalidData("Cannot read data".to_string())),
+    };
+    let parameters = convert_arrow_to_parameters(input_arrow_meta_data)?;
+    let (input_schema, data) = convert_arrow_to_batches(input_arrow_data)?;
+    let result = with_process(process_instance, |process| {
+        process.execute(&parameters, data)
+    })?;
+    // the schema of the result is the one of the input if the process does not return any data
+    let result_schema: SchemaRef = match result.first() {
+        Some(batch) => batch.schema(),
+        None => input_schema,
+    };
+    let stream_writer = convert_batches_to_arrow(&result_schema, &result)?;
+    convert_arrow_to_raw_memory(stream_writer)
+}
+
+/// Stores the error of a failed call, so ZuStDPipe can read it using `zustdp_module_wasm_last_error`
+/// # Arguments
+/// * `error` - error of the process
+pub fn set_last_error(error: &ProcessError) {
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(error.to_string()));
+}
+
+/// Raw entry function of a module implementing a process. It decodes the parameters and data, executes the process and encodes its result
+/// Use `export_process!` to export it from a module
+/// # Arguments
+/// * `process_instance` - process of the module
+/// * `meta_data_offset` - pointer to the parameters in Arrow IPC format
+/// * `meta_data_size` - size of the parameters
+/// * `data_offset` - pointer to the data in Arrow IPC format
+/// * `data_size` - size of the data
+/// returns a pointer to the result descriptor (see `convert_arrow_to_raw_memory`) or 0 if the process failed. The error can be read using `zustdp_module_wasm_last_error`
+pub fn raw_process_entry<P: Process>(
+    process_instance: ProcessInstance<P>,
+    meta_data_offset: *mut u8,
+    meta_data_size: usize,
+    data_offset: *mut u8,
+    data_size: usize,
+) -> usize {
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = None);
+    match execute_raw_process::<P>(
+        process_instance,
+        meta_data_offset,
+        meta_data_size,
+        data_offset,
+        data_size,
+    ) {
+        Ok(result_ptr) => result_ptr,
+        Err(error) => {
+            set_last_error(&error);
+            0
+        }
+    }
+}
+
+/// Exports a type implementing the `Process` trait as the raw entry function (`zustdp_module_wasm_raw_process_entry`) and the stream functions (`zustdp_module_wasm_stream_*`) of a module
+/// ZuStDPipe may process independent record batches in several instances of such a module in parallel. Use `export_stateful_process!` if the process keeps state across all data of a job
+/// # Arguments
+/// * `process` - type implementing the `Process` trait
+///
+/// # Examples
+/// ```ignore
+/// zustdpipe_modules_library::export_process!(EchoProcess);
+/// ```
+#[macro_export]
+macro_rules! export_process {
+    ($process:ty) => {
+        thread_local!(
+            static ZUSTDP_PROCESS: std::cell::RefCell<Option<$process>> =
+                std::cell::RefCell::new(None);
+        );
+
+        /// This is the raw entry function into the WebAssembly module generated by `export_process!`
+        #[no_mangle]
+        pub extern "C" fn zustdp_module_wasm_raw_process_entry(
+            meta_data_offset: *mut u8,
+            meta_data_size: usize,
+            data_offset: *mut u8,
+            data_size: usize,
+        ) -> usize {
+            $crate::modules::wasm::raw_process_entry::<$process>(
+                &ZUSTDP_PROCESS,
+                meta_data_offset,
+                meta_data_size,
+                data_offset,
+                data_size,
+            )
+        }
+
+        thread_local!(
+            static ZUSTDP_PROCESS_STREAM: std::cell::RefCell<
+                Option<$crate::modules::stream::ProcessStream>,
+            > = std::cell::RefCell::new(None);
+        );
+
+        /// Starts a stream. Generated by `export_process!`
+        #[no_mangle]
+        pub extern "C" fn zustdp_module_wasm_stream_start(
+            meta_data_offset: *mut u8,
+            meta_data_size: usize,
+        ) -> i32 {
+            $crate::modules::stream::stream_start(
+                &ZUSTDP_PROCESS_STREAM,
+                meta_data_offset,
+                meta_data_size,
+            )
+        }
+
+        /// Pushes a record batch to the stream. Generated by `export_process!`
+        #[no_mangle]
+        pub extern "C" fn zustdp_module_wasm_stream_push(
+            data_offset: *mut u8,
+            data_size: usize,
+        ) -> i32 {
+            $crate::modules::stream::stream_push::<$process>(
+                &ZUSTDP_PROCESS,
+                &ZUSTDP_PROCESS_STREAM,
+                data_offset,
+                data_size,
+            )
+        }
+
+        /// Pulls the next pending output of the stream. Generated by `export_process!`
+        #[no_mangle]
+        pub extern "C" fn zustdp_module_wasm_stream_pull() -> usize {
+            $crate::modules::stream::stream_pull(&ZUSTDP_PROCESS_STREAM)
+        }
+
+        /// Ends the stream. Generated by `export_process!`
+        #[no_mangle]
+        pub extern "C" fn zustdp_module_wasm_stream_end() -> i32 {
+            $crate::modules::stream::stream_end(&ZUSTDP_PROCESS_STREAM)
+        }
+    };
+}
+
+/// Exports a type implementing the `Process` trait like `export_process!` and additionally the lifecycle functions (`zustdp_module_init`, `zustdp_module_finish`) of a module
+/// ZuStDPipe processes all data of a job in a single instance of such a module, so the process can keep state (e.g. an aggregation) across all record batches and return it when it is finished
+/// # Arguments
+/// * `process` - type implementing the `Process` trait
+///
+/// # Examples
+/// ```ignore
+/// zustdpipe_modules_library::export_stateful_process!(CountProcess);
+/// ```
+#[macro_export]
+macro_rules! export_stateful_process {
+    ($process:ty) => {
+        $crate::export_process!($process);
+
+        /// Initializes the process before the first data of a job is processed. Generated by `export_stateful_process!`
+        #[no_mangle]
+        pub extern "C" fn zustdp_module_init(
+            meta_data_offset: *mut u8,
+            meta_data_size: usize,
+        ) -> i32 {
+            $crate::modules::instance::module_init::<$process>(
+                &ZUSTDP_PROCESS,
+                meta_data_offset,
+                meta_data_size,
+            )
+        }
+
+        /// Finishes the process after all data of a job has been processed. Generated by `export_stateful_process!`
+        #[no_mangle]
+        pub extern "C" fn zustdp_module_finish() -> usize {
+            $crate::modules::instance::module_finish::<$process>(&ZUSTDP_PROCESS)
+        }
+    };
 }
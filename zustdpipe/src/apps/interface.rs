@@ -1,8 +1,10 @@
 use super::error;
 
 use crate::jobs::interface::JobDefinition;
+use crate::logger::interface::LoggingDefinition;
 use crate::modules::interface::ModulesDefinition;
 use crate::pipeline::interface::PipelineDefinition;
+use crate::state::interface::StateDefinition;
 use serde::{Deserialize, Serialize};
 use std::io;
 
@@ -30,6 +32,12 @@ pub struct AppDefinition {
     pub modules: ModulesDefinition,
     pub jobs: HashMap<String, JobDefinition>,
     pub pipelines: HashMap<String, PipelineDefinition>,
+    /// where the state of stateful modules is stored. If not defined then the state is only kept in memory while the app runs
+    #[serde(default)]
+    pub state: Option<StateDefinition>,
+    /// where messages are logged. If not defined then messages of level info and above are logged to stdout
+    #[serde(default)]
+    pub logging: Option<LoggingDefinition>,
 }
 
 /// General properties of an app in its definition
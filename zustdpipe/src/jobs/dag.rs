@@ -0,0 +1,200 @@
+//! Processes of a pipeline form a directed acyclic graph (DAG). A process receives the output of all processes listed in its inputs.
+//! If a process does not define any inputs then it receives the output of the process defined before it in the pipeline.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::error::error::GeneralError;
+use crate::pipeline::interface::{PipelineDefinition, ProcessDefinition};
+
+use super::interface::JobValidationError;
+
+/// A process of a pipeline with the resolved names of the processes providing its input
+#[derive(Clone, Debug)]
+pub struct ProcessNode {
+    pub name: String,
+    pub definition: ProcessDefinition,
+    pub inputs: Vec<String>,
+}
+
+/// Returns all processes of a pipeline in an order in which they can be executed, ie each process comes after all processes providing its input
+/// Processes without dependencies between each other keep the order in which they are defined in the pipeline. Processes defined in the same list item are ordered by their name, so the order is the same each time the pipeline is read
+/// # Arguments
+/// * `pipeline_definition` - definition of the pipeline
+/// returns the processes in topological order or an error if an input does not exist or the processes contain a cycle
+pub fn execution_order(
+    pipeline_definition: &PipelineDefinition,
+) -> Result<Vec<ProcessNode>, JobValidationError> {
+    // resolve the inputs of all processes
+    let mut nodes: Vec<ProcessNode> = Vec::new();
+    let mut names: HashSet<String> = HashSet::new();
+    for process in &pipeline_definition.process {
+        let mut process_names: Vec<&String> = process.keys().collect();
+        process_names.sort();
+        for process_name in process_names {
+            let process_definition = &process[process_name];
+            if !names.insert(process_name.clone()) {
+                return Err(JobValidationError::DuplicateProcessName(
+                    GeneralError::ErrorMessage(format!(
+                        "Process \"{}\" is defined more than once in the pipeline",
+                        process_name
+                    )),
+                ));
+            }
+            let inputs = match &process_definition.inputs {
+                Some(inputs) => inputs.clone(),
+                None => match nodes.last() {
+                    Some(previous_node) => vec![previous_node.name.clone()],
+                    None => Vec::new(),
+                },
+            };
+            nodes.push(ProcessNode {
+                name: process_name.clone(),
+                definition: process_definition.clone(),
+                inputs: inputs,
+            });
+        }
+    }
+    for node in &nodes {
+        for input in &node.inputs {
+            if !names.contains(input) {
+                return Err(JobValidationError::ProcessInputNotFound(
+                    GeneralError::ErrorMessage(format!(
+                        "Input \"{}\" of process \"{}\" is not a process of the pipeline",
+                        input, node.name
+                    )),
+                ));
+            }
+        }
+    }
+    // sort them topologically (Kahn's algorithm)
+    let mut missing_inputs: HashMap<&String, usize> = nodes
+        .iter()
+        .map(|node| (&node.name, node.inputs.iter().collect::<HashSet<_>>().len()))
+        .collect();
+    let mut order: Vec<ProcessNode> = Vec::new();
+    let mut done: HashSet<String> = HashSet::new();
+    while order.len() < nodes.len() {
+        let next_node = nodes
+            .iter()
+            .find(|node| !done.contains(&node.name) && missing_inputs[&node.name] == 0);
+        let next_node = match next_node {
+            Some(next_node) => next_node,
+            None => {
+                let cycle: Vec<&str> = nodes
+                    .iter()
+                    .filter(|node| !done.contains(&node.name))
+                    .map(|node| node.name.as_str())
+                    .collect();
+                return Err(JobValidationError::PipelineCycleDetected(
+                    GeneralError::ErrorMessage(format!(
+                        "Processes contain a cycle: {}",
+                        cycle.join(", ")
+                    )),
+                ));
+            }
+        };
+        done.insert(next_node.name.clone());
+        for node in &nodes {
+            if node.inputs.contains(&next_node.name) {
+                if let Some(count) = missing_inputs.get_mut(&node.name) {
+                    *count -= 1;
+                }
+            }
+        }
+        order.push(next_node.clone());
+    }
+    Ok(order)
+}
+
+/// Returns the names of all processes whose output is not the input of any other process. Their output is the output of the pipeline
+/// # Arguments
+/// * `nodes` - processes of the pipeline
+pub fn sinks(nodes: &[ProcessNode]) -> Vec<String> {
+    nodes
+        .iter()
+        .filter(|node| {
+            !nodes
+                .iter()
+                .any(|other_node| other_node.inputs.contains(&node.name))
+        })
+        .map(|node| node.name.clone())
+        .collect()
+}
+
+/// Returns the position of the last process consuming the output of each process. Afterwards the output is not needed anymore
+/// Processes whose output is not the input of any other process are not contained
+/// # Arguments
+/// * `nodes` - processes of the pipeline in execution order
+pub fn last_consumers(nodes: &[ProcessNode]) -> HashMap<String, usize> {
+    let mut last_consumers: HashMap<String, usize> = HashMap::new();
+    for (position, node) in nodes.iter().enumerate() {
+        for input in &node.inputs {
+            last_consumers.insert(input.clone(), position);
+        }
+    }
+    last_consumers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{execution_order, last_consumers, sinks};
+    use crate::jobs::interface::JobValidationError;
+    use crate::pipeline::interface::PipelineDefinition;
+
+    const LINEAR_PIPELINE_STRING: &str = "process:\n - read:\n    module:\n     name: read.wasm\n     type: wasm\n    parameters: []\n - parse:\n    module:\n     name: parse.wasm\n     type: wasm\n    parameters: []\n";
+    const DAG_PIPELINE_STRING: &str = "process:\n - index:\n    module:\n     name: index.wasm\n     type: wasm\n    inputs: [parse]\n    parameters: []\n - bloomfilter:\n    module:\n     name: bloomfilter.wasm\n     type: wasm\n    inputs: [parse]\n    parameters: []\n - parse:\n    module:\n     name: parse.wasm\n     type: wasm\n    inputs: []\n    parameters: []\n - merge:\n    module:\n     name: merge.wasm\n     type: wasm\n    inputs: [index, bloomfilter]\n    parameters: []\n";
+    const SAME_ITEM_PIPELINE_STRING: &str = "process:\n - read:\n    module:\n     name: read.wasm\n     type: wasm\n    parameters: []\n   parse:\n    module:\n     name: parse.wasm\n     type: wasm\n    inputs: []\n    parameters: []\n   index:\n    module:\n     name: index.wasm\n     type: wasm\n    inputs: []\n    parameters: []\n";
+    const CYCLE_PIPELINE_STRING: &str = "process:\n - parse:\n    module:\n     name: parse.wasm\n     type: wasm\n    inputs: [index]\n    parameters: []\n - index:\n    module:\n     name: index.wasm\n     type: wasm\n    inputs: [parse]\n    parameters: []\n";
+
+    fn names(pipeline_string: &str) -> Result<Vec<String>, JobValidationError> {
+        let pipeline_definition: PipelineDefinition =
+            serde_yaml::from_str(pipeline_string).unwrap();
+        let nodes = execution_order(&pipeline_definition)?;
+        Ok(nodes.iter().map(|node| node.name.clone()).collect())
+    }
+
+    #[test]
+    // Test that processes without inputs are executed in the order of their definition
+    fn test_execution_order_linear() {
+        assert_eq!(
+            names(LINEAR_PIPELINE_STRING).unwrap(),
+            vec!["read", "parse"]
+        );
+    }
+
+    #[test]
+    // Test that processes with fan-out and fan-in are executed after their inputs
+    fn test_execution_order_dag() {
+        let pipeline_definition: PipelineDefinition =
+            serde_yaml::from_str(DAG_PIPELINE_STRING).unwrap();
+        let nodes = execution_order(&pipeline_definition).unwrap();
+        let order: Vec<&str> = nodes.iter().map(|node| node.name.as_str()).collect();
+        assert_eq!(order, vec!["parse", "index", "bloomfilter", "merge"]);
+        assert_eq!(sinks(&nodes), vec!["merge"]);
+        let last_consumers = last_consumers(&nodes);
+        assert_eq!(last_consumers.len(), 3);
+        assert_eq!(last_consumers["parse"], 2);
+        assert_eq!(last_consumers["index"], 3);
+        assert_eq!(last_consumers["bloomfilter"], 3);
+    }
+
+    #[test]
+    // Test that processes defined in the same list item are always executed in the same order, although their definitions are not ordered
+    fn test_execution_order_same_item() {
+        for _ in 0..10 {
+            assert_eq!(
+                names(SAME_ITEM_PIPELINE_STRING).unwrap(),
+                vec!["index", "parse", "read"]
+            );
+        }
+    }
+
+    #[test]
+    // Test that a cycle between processes is detected
+    fn test_execution_order_cycle() {
+        match names(CYCLE_PIPELINE_STRING) {
+            Err(JobValidationError::PipelineCycleDetected(_)) => (),
+            _ => panic!("Cycle in pipeline not detected"),
+        }
+    }
+}
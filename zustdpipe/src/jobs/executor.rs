@@ -0,0 +1,1015 @@
+//! Executes a process of a pipeline. Independent record batches of the input of a process are distributed to several module instances running in parallel threads.
+//! The record batches are read from the input and the outputs of the module instances are written one by one, so only a bounded number of them is kept in memory at the same time.
+//! This bounds the memory used to exchange data with the modules, but not the memory of a job: the input of the job and the output of each process are kept in memory in Arrow IPC format. The output of a process is dropped once the last process consuming it has been executed.
+
+use std::cell::RefCell;
+use std::io::Write;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::thread;
+
+use arrow::datatypes::SchemaRef;
+use arrow::ipc::reader::StreamReader;
+use arrow::ipc::writer::StreamWriter;
+use arrow::record_batch::RecordBatch;
+
+use crate::error::error::GeneralError;
+use crate::modules::library::interface::Library;
+
+use super::interface::JobRunError;
+
+/// Number of messages a module instance running in parallel may send before they are written to the output. Together with the record batch waiting for each module instance it bounds the memory used by the process
+const WORKER_CHANNEL_CAPACITY: usize = 4;
+
+/// Output of a process
+pub struct ProcessOutput {
+    /// output of the process in Arrow IPC format
+    pub serialized_data: Vec<u8>,
+    /// fuel consumed by all module instances executing the process
+    pub fuel_consumed: u64,
+}
+
+/// Message sent by a module instance processing record batches in parallel
+enum WorkerMessage {
+    /// output of the module instance in Arrow IPC format
+    Output(Vec<u8>),
+    /// the module instance sent all output of its current record batch
+    BatchDone,
+    /// the module instance processed all of its record batches. Its following output (e.g. the end of a stream) is written after the output of all record batches
+    BatchesDone,
+}
+
+/// Executes a process on the given data
+/// If more than one thread is configured and the data contains more than one record batch then each record batch is processed independently.
+/// The record batches are distributed to several module instances and each module instance runs in its own thread. Modules supporting streams process their record batches as a stream.
+/// The output contains the output of all record batches in the order of the input record batches
+/// Modules with lifecycle hooks keep state across record batches. They are executed by a single module instance that is initialized once before the first record batch and finished once after the last one. A non-empty final output is the output of the process instead of the output of the record batches
+/// # Arguments
+/// * `get_module_instance` - returns a new module instance that executes the process. Each instance has its own state
+/// * `threads` - maximum number of threads (and thus module instances) to execute the process
+/// * `serialized_metadata` - metadata (e.g. parameters) of the process in Arrow IPC format
+/// * `serialized_inputs` - data to process as Arrow IPC streams with the same schema, e.g. the outputs of several processes. They are processed in the given order
+/// * `output` - receives the output of the process as one Arrow IPC stream
+/// returns the fuel consumed
+pub fn execute_process<F, W>(
+    mut get_module_instance: F,
+    threads: usize,
+    serialized_metadata: &[u8],
+    serialized_inputs: &[&[u8]],
+    output: W,
+) -> Result<u64, JobRunError>
+where
+    F: FnMut() -> Result<Box<dyn Library>, JobRunError>,
+    W: Write,
+{
+    let mut output_writer = ArrowOutputWriter::new(output);
+    let mut input_batches = ArrowBatchReader::new(serialized_inputs);
+    let mut module_instance = get_module_instance()?;
+    // the state of a module with lifecycle hooks cannot be split across several module instances
+    let threads = if module_instance.has_lifecycle_hooks() {
+        1
+    } else {
+        threads
+    };
+    // the first record batches are read ahead to decide if they can be processed in parallel
+    let mut prefetched_batches: Vec<Vec<u8>> = Vec::new();
+    while threads > 1 && prefetched_batches.len() < threads {
+        match input_batches.next() {
+            Some(Ok(serialized_batch)) => prefetched_batches.push(serialized_batch),
+            Some(Err(error)) => return Err(JobRunError::JobSerializationError(error)),
+            None => break,
+        };
+    }
+    let module_instance_count = prefetched_batches.len();
+    let input_batches = prefetched_batches.into_iter().map(Ok).chain(input_batches);
+    let fuel_consumed = if module_instance_count > 1 {
+        let mut module_instances: Vec<Box<dyn Library>> = vec![module_instance];
+        for _ in 1..module_instance_count {
+            module_instances.push(get_module_instance()?);
+        }
+        execute_parallel(
+            &mut module_instances,
+            serialized_metadata,
+            input_batches,
+            &mut output_writer,
+        )?;
+        module_instances
+            .iter()
+            .map(|module_instance| module_instance.fuel_consumed())
+            .sum()
+    } else {
+        // nothing to parallelize
+        execute_instance(
+            module_instance.as_mut(),
+            serialized_metadata,
+            serialized_inputs,
+            input_batches,
+            &mut output_writer,
+        )?;
+        module_instance.fuel_consumed()
+    };
+    match output_writer.finish() {
+        Ok(()) => Ok(fuel_consumed),
+        Err(error) => Err(JobRunError::JobSerializationError(error)),
+    }
+}
+
+/// Executes a process on the given data using a single module instance
+/// A module with lifecycle hooks is initialized before and finished after processing the data. Its non-empty final output replaces the output of the record batches
+/// # Arguments
+/// * `module_instance` - module instance executing the process
+/// * `serialized_metadata` - metadata (e.g. parameters) of the process in Arrow IPC format
+/// * `serialized_inputs` - data to process as Arrow IPC streams
+/// * `input_batches` - record batches of the data to process as Arrow IPC streams containing one record batch each
+/// * `output_writer` - receives the output of the module instance
+fn execute_instance<I, W>(
+    module_instance: &mut dyn Library,
+    serialized_metadata: &[u8],
+    serialized_inputs: &[&[u8]],
+    input_batches: I,
+    output_writer: &mut ArrowOutputWriter<W>,
+) -> Result<(), JobRunError>
+where
+    I: Iterator<Item = Result<Vec<u8>, GeneralError>>,
+    W: Write,
+{
+    if !module_instance.has_lifecycle_hooks() {
+        return process_data(
+            module_instance,
+            serialized_metadata,
+            serialized_inputs,
+            input_batches,
+            output_writer,
+        );
+    }
+    if let Err(error) = module_instance.init(serialized_metadata.to_vec()) {
+        return Err(JobRunError::JobModuleInstantiationError(error));
+    }
+    // the output of the record batches is kept until it is known if the final output replaces it
+    let mut serialized_batch_answers: Vec<u8> = Vec::new();
+    let mut batch_output_writer = ArrowOutputWriter::new(&mut serialized_batch_answers);
+    process_data(
+        module_instance,
+        serialized_metadata,
+        serialized_inputs,
+        input_batches,
+        &mut batch_output_writer,
+    )?;
+    if let Err(error) = batch_output_writer.finish() {
+        return Err(JobRunError::JobSerializationError(error));
+    }
+    let serialized_answer = match module_instance.finish() {
+        Ok(Some(serialized_final_answer)) if !serialized_final_answer.is_empty() => {
+            serialized_final_answer
+        }
+        Ok(_no_final_answer) => serialized_batch_answers,
+        Err(error) => return Err(JobRunError::JobProcessError(error)),
+    };
+    match output_writer.write(serialized_answer) {
+        Ok(()) => Ok(()),
+        Err(error) => Err(JobRunError::JobSerializationError(error)),
+    }
+}
+
+/// Processes the given data using a single module instance
+/// Modules supporting streams get the record batches one by one, so their memory stays bounded. Modules with lifecycle hooks get the record batches one by one, so they can keep state across them. Other modules get all data at once
+/// # Arguments
+/// * `module_instance` - module instance executing the process
+/// * `serialized_metadata` - metadata (e.g. parameters) of the process in Arrow IPC format
+/// * `serialized_inputs` - data to process as Arrow IPC streams
+/// * `input_batches` - record batches of the data to process as Arrow IPC streams containing one record batch each
+/// * `output_writer` - receives the output of the module instance
+fn process_data<I, W>(
+    module_instance: &mut dyn Library,
+    serialized_metadata: &[u8],
+    serialized_inputs: &[&[u8]],
+    mut input_batches: I,
+    output_writer: &mut ArrowOutputWriter<W>,
+) -> Result<(), JobRunError>
+where
+    I: Iterator<Item = Result<Vec<u8>, GeneralError>>,
+    W: Write,
+{
+    if module_instance.supports_streaming() {
+        let mut input_error: Option<GeneralError> = None;
+        let output_error: RefCell<Option<GeneralError>> = RefCell::new(None);
+        // the next record batch is only read once the module is ready to receive it
+        let mut serialized_batches = input_batches.map_while(|serialized_batch| {
+            if output_error.borrow().is_some() {
+                return None;
+            }
+            match serialized_batch {
+                Ok(serialized_batch) => Some(serialized_batch),
+                Err(error) => {
+                    input_error = Some(error);
+                    None
+                }
+            }
+        });
+        let stream_result = module_instance.exec_stream(
+            serialized_metadata.to_vec(),
+            &mut serialized_batches,
+            &mut |serialized_answer| {
+                if output_error.borrow().is_some() {
+                    return;
+                }
+                if let Err(error) = output_writer.write(serialized_answer) {
+                    output_error.replace(Some(error));
+                }
+            },
+        );
+        if let Some(error) = input_error.or(output_error.into_inner()) {
+            return Err(JobRunError::JobSerializationError(error));
+        }
+        return match stream_result {
+            Ok(()) => Ok(()),
+            Err(error) => Err(JobRunError::JobProcessError(error)),
+        };
+    }
+    if module_instance.has_lifecycle_hooks() {
+        let mut batch_count = 0;
+        for serialized_batch in input_batches.by_ref() {
+            let serialized_batch = match serialized_batch {
+                Ok(serialized_batch) => serialized_batch,
+                Err(error) => return Err(JobRunError::JobSerializationError(error)),
+            };
+            process_batch(
+                module_instance,
+                serialized_metadata,
+                serialized_batch,
+                output_writer,
+            )?;
+            batch_count += 1;
+        }
+        if batch_count > 0 {
+            return Ok(());
+        }
+    }
+    // all data at once, which includes data without any record batch
+    let serialized_data = match merge_arrow_streams(serialized_inputs) {
+        Ok(serialized_data) => serialized_data,
+        Err(error) => return Err(JobRunError::JobSerializationError(error)),
+    };
+    process_batch(
+        module_instance,
+        serialized_metadata,
+        serialized_data,
+        output_writer,
+    )
+}
+
+/// Processes data in a single call of a module instance
+/// # Arguments
+/// * `module_instance` - module instance executing the process
+/// * `serialized_metadata` - metadata (e.g. parameters) of the process in Arrow IPC format
+/// * `serialized_data` - data to process in Arrow IPC format
+/// * `output_writer` - receives the output of the module instance
+fn process_batch<W: Write>(
+    module_instance: &mut dyn Library,
+    serialized_metadata: &[u8],
+    serialized_data: Vec<u8>,
+    output_writer: &mut ArrowOutputWriter<W>,
+) -> Result<(), JobRunError> {
+    let serialized_answer =
+        match module_instance.exec_func(serialized_metadata.to_vec(), serialized_data) {
+            Ok(serialized_answer) => serialized_answer,
+            Err(error) => return Err(JobRunError::JobProcessError(error)),
+        };
+    match output_writer.write(serialized_answer) {
+        Ok(()) => Ok(()),
+        Err(error) => Err(JobRunError::JobSerializationError(error)),
+    }
+}
+
+/// Executes a process on the given record batches using several module instances in parallel
+/// The record batches are read in a separate thread and distributed round robin to the module instances, so their output can be written in the order of the input. Each module instance only receives its next record batch once it processed the previous one
+/// # Arguments
+/// * `module_instances` - module instances executing the process. Each one runs in its own thread
+/// * `serialized_metadata` - metadata (e.g. parameters) of the process in Arrow IPC format
+/// * `input_batches` - record batches of the data to process as Arrow IPC streams containing one record batch each
+/// * `output_writer` - receives the output of all module instances
+fn execute_parallel<I, W>(
+    module_instances: &mut [Box<dyn Library>],
+    serialized_metadata: &[u8],
+    input_batches: I,
+    output_writer: &mut ArrowOutputWriter<W>,
+) -> Result<(), JobRunError>
+where
+    I: Iterator<Item = Result<Vec<u8>, GeneralError>> + Send,
+    W: Write,
+{
+    let module_instance_count = module_instances.len();
+    thread::scope(|scope| {
+        let mut batch_senders: Vec<SyncSender<Vec<u8>>> = Vec::new();
+        let mut message_receivers: Vec<Receiver<WorkerMessage>> = Vec::new();
+        let mut workers = Vec::new();
+        for module_instance in module_instances.iter_mut() {
+            let (batch_sender, batch_receiver) = mpsc::sync_channel::<Vec<u8>>(1);
+            let (message_sender, message_receiver) =
+                mpsc::sync_channel::<WorkerMessage>(WORKER_CHANNEL_CAPACITY);
+            workers.push(scope.spawn(move || {
+                execute_worker(
+                    module_instance.as_mut(),
+                    serialized_metadata,
+                    batch_receiver,
+                    message_sender,
+                )
+            }));
+            batch_senders.push(batch_sender);
+            message_receivers.push(message_receiver);
+        }
+        let dispatcher = scope.spawn(move || -> Result<(), GeneralError> {
+            for (index, serialized_batch) in input_batches.enumerate() {
+                // a module instance that stopped reports why it stopped
+                if batch_senders[index % module_instance_count]
+                    .send(serialized_batch?)
+                    .is_err()
+                {
+                    break;
+                }
+            }
+            Ok(())
+        });
+        let output_result = collect_parallel_output(&message_receivers, output_writer);
+        // module instances still running stop once they cannot send their output anymore
+        drop(message_receivers);
+        let mut worker_results: Vec<Result<(), JobRunError>> = workers
+            .into_iter()
+            .map(|worker| match worker.join() {
+                Ok(result) => result,
+                Err(_err) => Err(JobRunError::JobStartError(GeneralError::ErrorMessage(
+                    "Thread executing process panicked".to_string(),
+                ))),
+            })
+            .collect();
+        worker_results.push(match dispatcher.join() {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(error)) => Err(JobRunError::JobSerializationError(error)),
+            Err(_err) => Err(JobRunError::JobStartError(GeneralError::ErrorMessage(
+                "Thread reading process input panicked".to_string(),
+            ))),
+        });
+        // the error of a module instance or the input is the cause if the output is incomplete
+        for worker_result in worker_results {
+            worker_result?;
+        }
+        output_result
+    })
+}
+
+/// Writes the output of module instances processing record batches in parallel in the order of the input record batches
+/// The output of the record batches is followed by the remaining output of each module instance, e.g. its final output
+/// # Arguments
+/// * `message_receivers` - receive the messages of each module instance. Record batch n has been processed by module instance n modulo the number of module instances
+/// * `output_writer` - receives the output
+fn collect_parallel_output<W: Write>(
+    message_receivers: &[Receiver<WorkerMessage>],
+    output_writer: &mut ArrowOutputWriter<W>,
+) -> Result<(), JobRunError> {
+    let incomplete_output = || {
+        JobRunError::JobStartError(GeneralError::ErrorMessage(
+            "Module instance stopped before it sent all of its output".to_string(),
+        ))
+    };
+    let mut index = 0;
+    // a module instance without further record batches indicates that all record batches have been processed
+    'batches: loop {
+        let message_receiver = &message_receivers[index % message_receivers.len()];
+        loop {
+            match message_receiver.recv() {
+                Ok(WorkerMessage::Output(serialized_answer)) => {
+                    if let Err(error) = output_writer.write(serialized_answer) {
+                        return Err(JobRunError::JobSerializationError(error));
+                    }
+                }
+                Ok(WorkerMessage::BatchDone) => break,
+                Ok(WorkerMessage::BatchesDone) => break 'batches,
+                Err(_err) => return Err(incomplete_output()),
+            };
+        }
+        index += 1;
+    }
+    for message_receiver in message_receivers {
+        for message in message_receiver {
+            match message {
+                WorkerMessage::Output(serialized_answer) => {
+                    if let Err(error) = output_writer.write(serialized_answer) {
+                        return Err(JobRunError::JobSerializationError(error));
+                    }
+                }
+                WorkerMessage::BatchDone => return Err(incomplete_output()),
+                WorkerMessage::BatchesDone => (),
+            };
+        }
+    }
+    Ok(())
+}
+
+/// Processes the record batches distributed to one module instance without lifecycle hooks. Runs in its own thread
+/// # Arguments
+/// * `module_instance` - module instance executing the process
+/// * `serialized_metadata` - metadata (e.g. parameters) of the process in Arrow IPC format
+/// * `batch_receiver` - receives the record batches to process as Arrow IPC streams containing one record batch each
+/// * `message_sender` - sends the output of the module instance
+fn execute_worker(
+    module_instance: &mut dyn Library,
+    serialized_metadata: &[u8],
+    batch_receiver: Receiver<Vec<u8>>,
+    message_sender: SyncSender<WorkerMessage>,
+) -> Result<(), JobRunError> {
+    if module_instance.supports_streaming() {
+        // the module requests the next record batch once it returned all output of the previous one
+        let mut batch_pending = false;
+        let mut serialized_batches = std::iter::from_fn(|| {
+            if batch_pending && message_sender.send(WorkerMessage::BatchDone).is_err() {
+                return None;
+            }
+            match batch_receiver.recv() {
+                Ok(serialized_batch) => {
+                    batch_pending = true;
+                    Some(serialized_batch)
+                }
+                Err(_err) => {
+                    batch_pending = false;
+                    let _ = message_sender.send(WorkerMessage::BatchesDone);
+                    None
+                }
+            }
+        });
+        let stream_result = module_instance.exec_stream(
+            serialized_metadata.to_vec(),
+            &mut serialized_batches,
+            &mut |serialized_answer| {
+                // the output is only incomplete if the output has already failed
+                let _ = message_sender.send(WorkerMessage::Output(serialized_answer));
+            },
+        );
+        match stream_result {
+            Ok(()) => Ok(()),
+            Err(error) => Err(JobRunError::JobProcessError(error)),
+        }
+    } else {
+        for serialized_batch in batch_receiver {
+            let serialized_answer =
+                match module_instance.exec_func(serialized_metadata.to_vec(), serialized_batch) {
+                    Ok(serialized_answer) => serialized_answer,
+                    Err(error) => return Err(JobRunError::JobProcessError(error)),
+                };
+            let sent = message_sender
+                .send(WorkerMessage::Output(serialized_answer))
+                .and_then(|()| message_sender.send(WorkerMessage::BatchDone));
+            // the output failed and reports why
+            if sent.is_err() {
+                return Ok(());
+            }
+        }
+        // the output reports if it failed
+        let _ = message_sender.send(WorkerMessage::BatchesDone);
+        Ok(())
+    }
+}
+
+/// Reads the record batches of several Arrow IPC streams with the same schema one by one
+struct ArrowBatchReader<'a> {
+    /// streams that have not been read yet
+    serialized_streams: std::slice::Iter<'a, &'a [u8]>,
+    /// stream that is currently read
+    stream_reader: Option<StreamReader<&'a [u8]>>,
+    /// schema of the first stream
+    schema: Option<SchemaRef>,
+}
+
+impl<'a> ArrowBatchReader<'a> {
+    /// Creates a reader of the given streams. Empty streams are ignored
+    /// # Arguments
+    /// * `serialized_streams` - Arrow IPC streams to read in the given order
+    fn new(serialized_streams: &'a [&'a [u8]]) -> ArrowBatchReader<'a> {
+        ArrowBatchReader {
+            serialized_streams: serialized_streams.iter(),
+            stream_reader: None,
+            schema: None,
+        }
+    }
+
+    /// Opens the next non-empty stream and checks that it has the schema of the first stream
+    /// returns false if all streams have been read
+    fn next_stream(&mut self) -> Result<bool, GeneralError> {
+        let serialized_stream = match self
+            .serialized_streams
+            .find(|serialized_stream| !serialized_stream.is_empty())
+        {
+            Some(serialized_stream) => *serialized_stream,
+            None => return Ok(false),
+        };
+        let stream_reader = match StreamReader::try_new_unbuffered(serialized_stream, None) {
+            Ok(stream_reader) => stream_reader,
+            Err(err) => {
+                return Err(GeneralError::ErrorMessage(format!(
+                    "Cannot read process input: {}",
+                    err
+                )))
+            }
+        };
+        let schema = stream_reader.schema();
+        match &self.schema {
+            Some(first_schema) => {
+                if first_schema != &schema {
+                    return Err(GeneralError::ErrorMessage(format!(
+                        "Cannot merge process outputs with different schemas: {} and {}",
+                        first_schema, schema
+                    )));
+                }
+            }
+            None => self.schema = Some(schema),
+        };
+        self.stream_reader = Some(stream_reader);
+        Ok(true)
+    }
+}
+
+impl<'a> Iterator for ArrowBatchReader<'a> {
+    /// Arrow IPC stream containing exactly one record batch
+    type Item = Result<Vec<u8>, GeneralError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(stream_reader) = &mut self.stream_reader {
+                match stream_reader.next() {
+                    Some(Ok(batch)) => {
+                        return Some(batches_to_arrow(&stream_reader.schema(), &[batch]))
+                    }
+                    Some(Err(err)) => {
+                        self.stream_reader = None;
+                        return Some(Err(GeneralError::ErrorMessage(format!(
+                            "Cannot read record batch of process input: {}",
+                            err
+                        ))));
+                    }
+                    None => self.stream_reader = None,
+                };
+            }
+            match self.next_stream() {
+                Ok(true) => (),
+                Ok(false) => return None,
+                Err(error) => return Some(Err(error)),
+            };
+        }
+    }
+}
+
+/// Writes the outputs of module instances one by one as a single Arrow IPC stream
+/// The first output is kept until a second one arrives, so a single output is written as returned by the module, e.g. if it is not an Arrow IPC stream
+struct ArrowOutputWriter<W: Write> {
+    /// receives the stream. None once the stream writer has been created
+    output: Option<W>,
+    /// first non-empty output as long as it is the only one
+    first_answer: Option<Vec<u8>>,
+    /// writer of the stream and its schema. Created once a second output arrives
+    stream_writer: Option<(StreamWriter<W>, SchemaRef)>,
+}
+
+impl<W: Write> ArrowOutputWriter<W> {
+    /// Creates a writer of the output of a process
+    /// # Arguments
+    /// * `output` - receives the output
+    fn new(output: W) -> ArrowOutputWriter<W> {
+        ArrowOutputWriter {
+            output: Some(output),
+            first_answer: None,
+            stream_writer: None,
+        }
+    }
+
+    /// Writes an output of a module instance. Empty outputs are ignored
+    /// # Arguments
+    /// * `serialized_answer` - output in Arrow IPC format. It must have the same schema as the previous outputs
+    fn write(&mut self, serialized_answer: Vec<u8>) -> Result<(), GeneralError> {
+        if serialized_answer.is_empty() {
+            return Ok(());
+        }
+        if self.stream_writer.is_none() {
+            let first_answer = match self.first_answer.take() {
+                Some(first_answer) => first_answer,
+                None => {
+                    self.first_answer = Some(serialized_answer);
+                    return Ok(());
+                }
+            };
+            let (stream_reader, schema) = read_answer(&first_answer)?;
+            let output = match self.output.take() {
+                Some(output) => output,
+                None => {
+                    return Err(GeneralError::ErrorMessage(
+                        "Process output has already been finished".to_string(),
+                    ))
+                }
+            };
+            let stream_writer = match StreamWriter::try_new(output, &schema) {
+                Ok(stream_writer) => stream_writer,
+                Err(err) => {
+                    return Err(GeneralError::ErrorMessage(format!(
+                        "Cannot create Arrow stream writer: {}",
+                        err
+                    )))
+                }
+            };
+            self.stream_writer = Some((stream_writer, schema));
+            self.write_batches(stream_reader)?;
+        }
+        let (stream_reader, schema) = read_answer(&serialized_answer)?;
+        if let Some((_stream_writer, merged_schema)) = &self.stream_writer {
+            if merged_schema != &schema {
+                return Err(GeneralError::ErrorMessage(format!(
+                    "Cannot merge process outputs with different schemas: {} and {}",
+                    merged_schema, schema
+                )));
+            }
+        }
+        self.write_batches(stream_reader)
+    }
+
+    /// Writes the record batches of an output to the stream
+    /// # Arguments
+    /// * `stream_reader` - reader of the output
+    fn write_batches(&mut self, stream_reader: StreamReader<&[u8]>) -> Result<(), GeneralError> {
+        let stream_writer = match &mut self.stream_writer {
+            Some((stream_writer, _schema)) => stream_writer,
+            None => return Ok(()),
+        };
+        for batch in stream_reader {
+            let batch = match batch {
+                Ok(batch) => batch,
+                Err(err) => {
+                    return Err(GeneralError::ErrorMessage(format!(
+                        "Cannot read record batch of process output: {}",
+                        err
+                    )))
+                }
+            };
+            if let Err(err) = stream_writer.write(&batch) {
+                return Err(GeneralError::ErrorMessage(format!(
+                    "Cannot write record batch: {}",
+                    err
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Finishes the output once all outputs have been written
+    fn finish(self) -> Result<(), GeneralError> {
+        let result = match (self.stream_writer, self.first_answer, self.output) {
+            (Some((mut stream_writer, _schema)), _, _) => stream_writer
+                .finish()
+                .and_then(|()| stream_writer.into_inner().map(|_output| ())),
+            (None, Some(first_answer), Some(mut output)) => output
+                .write_all(&first_answer)
+                .and_then(|()| output.flush())
+                .map_err(|err| err.into()),
+            (None, _, _) => Ok(()),
+        };
+        match result {
+            Ok(()) => Ok(()),
+            Err(err) => Err(GeneralError::ErrorMessage(format!(
+                "Cannot finish Arrow stream: {}",
+                err
+            ))),
+        }
+    }
+}
+
+/// Reads an output of a module instance
+/// # Arguments
+/// * `serialized_answer` - output in Arrow IPC format
+/// returns a reader of its record batches and its schema
+fn read_answer(serialized_answer: &[u8]) -> Result<(StreamReader<&[u8]>, SchemaRef), GeneralError> {
+    match StreamReader::try_new_unbuffered(serialized_answer, None) {
+        Ok(stream_reader) => {
+            let schema = stream_reader.schema();
+            Ok((stream_reader, schema))
+        }
+        Err(err) => Err(GeneralError::ErrorMessage(format!(
+            "Cannot read process output: {}",
+            err
+        ))),
+    }
+}
+
+/// Merges the output of several processes into one input for a process
+/// The outputs are Arrow IPC streams that must have the same schema. Empty outputs are ignored
+/// # Arguments
+/// * `serialized_streams` - Arrow IPC streams to merge
+/// returns one Arrow IPC stream containing the record batches of all streams in the given order
+pub(crate) fn merge_arrow_streams(serialized_streams: &[&[u8]]) -> Result<Vec<u8>, GeneralError> {
+    let non_empty_streams: Vec<&[u8]> = serialized_streams
+        .iter()
+        .filter(|serialized_stream| !serialized_stream.is_empty())
+        .copied()
+        .collect();
+    match non_empty_streams.len() {
+        0 => return Ok(Vec::new()),
+        1 => return Ok(non_empty_streams[0].to_vec()),
+        _ => (),
+    };
+    let mut merged_schema: Option<SchemaRef> = None;
+    let mut merged_batches: Vec<RecordBatch> = Vec::new();
+    for serialized_stream in non_empty_streams {
+        let stream_reader = match StreamReader::try_new(serialized_stream, None) {
+            Ok(stream_reader) => stream_reader,
+            Err(err) => {
+                return Err(GeneralError::ErrorMessage(format!(
+                    "Cannot read process output: {}",
+                    err
+                )))
+            }
+        };
+        let schema = stream_reader.schema();
+        match &merged_schema {
+            Some(merged_schema) => {
+                if merged_schema != &schema {
+                    return Err(GeneralError::ErrorMessage(format!(
+                        "Cannot merge process outputs with different schemas: {} and {}",
+                        merged_schema, schema
+                    )));
+                }
+            }
+            None => merged_schema = Some(schema.clone()),
+        };
+        for batch in stream_reader {
+            match batch {
+                Ok(batch) => merged_batches.push(batch),
+                Err(err) => {
+                    return Err(GeneralError::ErrorMessage(format!(
+                        "Cannot read record batch of process output: {}",
+                        err
+                    )))
+                }
+            };
+        }
+    }
+    match merged_schema {
+        Some(schema) => batches_to_arrow(&schema, &merged_batches),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Serializes record batches into an Arrow IPC stream
+/// # Arguments
+/// * `schema` - schema of the record batches
+/// * `batches` - record batches to serialize
+/// returns the Arrow IPC stream
+pub(crate) fn batches_to_arrow(
+    schema: &SchemaRef,
+    batches: &[RecordBatch],
+) -> Result<Vec<u8>, GeneralError> {
+    let mut stream_writer = match StreamWriter::try_new(Vec::new(), schema) {
+        Ok(stream_writer) => stream_writer,
+        Err(err) => {
+            return Err(GeneralError::ErrorMessage(format!(
+                "Cannot create Arrow stream writer: {}",
+                err
+            )))
+        }
+    };
+    for batch in batches {
+        match stream_writer.write(batch) {
+            Ok(()) => (),
+            Err(err) => {
+                return Err(GeneralError::ErrorMessage(format!(
+                    "Cannot write record batch: {}",
+                    err
+                )))
+            }
+        };
+    }
+    match stream_writer.into_inner() {
+        Ok(serialized_stream) => Ok(serialized_stream),
+        Err(err) => Err(GeneralError::ErrorMessage(format!(
+            "Cannot finish Arrow stream: {}",
+            err
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::{Int64Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::ipc::reader::StreamReader;
+    use arrow::record_batch::RecordBatch;
+
+    use crate::jobs::interface::JobRunError;
+    use crate::modules::library::interface::{
+        ExecutionLimits, Library, LibraryDefinition, LibraryInstanceError, LibraryManager,
+        WasiDefinition,
+    };
+    use crate::modules::library::wasm::WASMLibraryManager;
+
+    const ECHO_WAT_PATH: &str = "tests/data/modules/library/wasm/echo.wat";
+    const STREAM_ECHO_WAT_PATH: &str = "tests/data/modules/library/wasm/stream_echo.wat";
+    const PROCESS_ERROR_WAT_PATH: &str = "tests/data/modules/library/wasm/process_error.wat";
+    const LIFECYCLE_WAT_PATH: &str = "tests/data/modules/library/wasm/lifecycle.wat";
+
+    fn library_definition(path: &str) -> LibraryDefinition {
+        LibraryDefinition {
+            path: path.to_string(),
+            max_memory_pages: None,
+            max_table_elements: None,
+            wasi: WasiDefinition::default(),
+        }
+    }
+
+    fn test_batches() -> (arrow::datatypes::SchemaRef, Vec<RecordBatch>) {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("text", DataType::Utf8, false),
+        ]));
+        let batches = (0..5)
+            .map(|batch_number| {
+                RecordBatch::try_new(
+                    schema.clone(),
+                    vec![
+                        Arc::new(Int64Array::from(vec![batch_number, batch_number + 100])),
+                        Arc::new(StringArray::from(vec![
+                            format!("first {}", batch_number),
+                            format!("second {}", batch_number),
+                        ])),
+                    ],
+                )
+                .unwrap()
+            })
+            .collect();
+        (schema, batches)
+    }
+
+    #[test]
+    // Test that record batches are processed in parallel and the output keeps the order of the input
+    fn test_execute_process_parallel() {
+        let (schema, batches) = test_batches();
+        let serialized_data = super::batches_to_arrow(&schema, &batches).unwrap();
+        let mut libmgr: WASMLibraryManager = WASMLibraryManager::new().unwrap();
+        let mut module_instance_count = 0;
+        let mut serialized_answer: Vec<u8> = Vec::new();
+        let fuel_consumed = super::execute_process(
+            || {
+                module_instance_count += 1;
+                Ok(libmgr
+                    .get_instance(
+                        &library_definition(ECHO_WAT_PATH),
+                        &ExecutionLimits::default(),
+                    )
+                    .unwrap() as Box<dyn Library>)
+            },
+            3,
+            &[],
+            &[serialized_data.as_slice()],
+            &mut serialized_answer,
+        )
+        .unwrap();
+        assert_eq!(module_instance_count, 3);
+        assert!(fuel_consumed > 0);
+        let stream_reader = StreamReader::try_new(serialized_answer.as_slice(), None).unwrap();
+        assert_eq!(stream_reader.schema(), schema);
+        let answer_batches: Vec<RecordBatch> = stream_reader.map(|batch| batch.unwrap()).collect();
+        assert_eq!(answer_batches, batches);
+    }
+
+    #[test]
+    // Test that record batches are pushed one by one to modules supporting streams, also if several module instances process them in parallel
+    fn test_execute_process_stream() {
+        let (schema, batches) = test_batches();
+        // the record batches are read from several inputs in their order
+        let first_serialized_data = super::batches_to_arrow(&schema, &batches[..2]).unwrap();
+        let second_serialized_data = super::batches_to_arrow(&schema, &batches[2..]).unwrap();
+        let mut libmgr: WASMLibraryManager = WASMLibraryManager::new().unwrap();
+        for (threads, expected_module_instance_count) in [(1, 1), (2, 2), (8, 5)] {
+            let mut module_instance_count = 0;
+            let mut serialized_answer: Vec<u8> = Vec::new();
+            super::execute_process(
+                || {
+                    module_instance_count += 1;
+                    Ok(libmgr
+                        .get_instance(
+                            &library_definition(STREAM_ECHO_WAT_PATH),
+                            &ExecutionLimits::default(),
+                        )
+                        .unwrap() as Box<dyn Library>)
+                },
+                threads,
+                &[],
+                &[
+                    first_serialized_data.as_slice(),
+                    &[],
+                    second_serialized_data.as_slice(),
+                ],
+                &mut serialized_answer,
+            )
+            .unwrap();
+            assert_eq!(module_instance_count, expected_module_instance_count);
+            let stream_reader = StreamReader::try_new(serialized_answer.as_slice(), None).unwrap();
+            let answer_batches: Vec<RecordBatch> =
+                stream_reader.map(|batch| batch.unwrap()).collect();
+            assert_eq!(answer_batches, batches);
+        }
+    }
+
+    #[test]
+    // Test that a module aggregating across record batches is initialized and finished once on a single instance and that its final output is the output of the process
+    fn test_execute_process_lifecycle() {
+        let (schema, batches) = test_batches();
+        let serialized_data = super::batches_to_arrow(&schema, &batches).unwrap();
+        let mut libmgr: WASMLibraryManager = WASMLibraryManager::new().unwrap();
+        let mut module_instance_count = 0;
+        let mut serialized_answer: Vec<u8> = Vec::new();
+        super::execute_process(
+            || {
+                module_instance_count += 1;
+                Ok(libmgr
+                    .get_instance(
+                        &library_definition(LIFECYCLE_WAT_PATH),
+                        &ExecutionLimits::default(),
+                    )
+                    .unwrap() as Box<dyn Library>)
+            },
+            3,
+            &[1u8, 2u8, 3u8],
+            &[serialized_data.as_slice()],
+            &mut serialized_answer,
+        )
+        .unwrap();
+        assert_eq!(module_instance_count, 1);
+        // number of processed record batches followed by the length of the metadata given to init
+        assert_eq!(serialized_answer, vec![5u8, 0, 0, 0, 3u8, 0, 0, 0]);
+    }
+
+    #[test]
+    // Test that a module failing to process a record batch fails the process, also if it runs in parallel
+    fn test_execute_process_error() {
+        let (schema, batches) = test_batches();
+        let serialized_data = super::batches_to_arrow(&schema, &batches).unwrap();
+        let mut libmgr: WASMLibraryManager = WASMLibraryManager::new().unwrap();
+        for threads in [1, 3] {
+            let mut serialized_answer: Vec<u8> = Vec::new();
+            let result = super::execute_process(
+                || {
+                    Ok(libmgr
+                        .get_instance(
+                            &library_definition(PROCESS_ERROR_WAT_PATH),
+                            &ExecutionLimits::default(),
+                        )
+                        .unwrap() as Box<dyn Library>)
+                },
+                threads,
+                &[],
+                &[serialized_data.as_slice()],
+                &mut serialized_answer,
+            );
+            match result {
+                Err(JobRunError::JobProcessError(LibraryInstanceError::ProcessFailed(_))) => (),
+                _ => panic!("Failed process not reported"),
+            }
+        }
+        // inputs with different schemas cannot be processed together
+        let other_schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)]));
+        let other_batch = RecordBatch::try_new(
+            other_schema.clone(),
+            vec![Arc::new(Int64Array::from(vec![1]))],
+        )
+        .unwrap();
+        let other_serialized_data = super::batches_to_arrow(&other_schema, &[other_batch]).unwrap();
+        let result = super::execute_process(
+            || {
+                Ok(libmgr
+                    .get_instance(
+                        &library_definition(ECHO_WAT_PATH),
+                        &ExecutionLimits::default(),
+                    )
+                    .unwrap() as Box<dyn Library>)
+            },
+            3,
+            &[],
+            &[serialized_data.as_slice(), other_serialized_data.as_slice()],
+            &mut Vec::new(),
+        );
+        match result {
+            Err(JobRunError::JobSerializationError(_)) => (),
+            _ => panic!("Inputs with different schemas processed"),
+        }
+    }
+
+    #[test]
+    // Test that streams with different schemas cannot be merged
+    fn test_merge_arrow_streams_different_schema() {
+        let (schema, batches) = test_batches();
+        let serialized_data = super::batches_to_arrow(&schema, &batches).unwrap();
+        let other_schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)]));
+        let other_batch = RecordBatch::try_new(
+            other_schema.clone(),
+            vec![Arc::new(Int64Array::from(vec![1]))],
+        )
+        .unwrap();
+        let other_serialized_data = super::batches_to_arrow(&other_schema, &[other_batch]).unwrap();
+        assert!(super::merge_arrow_streams(&[&serialized_data, &other_serialized_data]).is_err());
+    }
+}
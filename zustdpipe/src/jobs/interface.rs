@@ -1,17 +1,31 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
 
 use crate::error::error::GeneralError;
 use crate::modules::library::interface::LibraryInstanceError;
 use crate::pipeline::interface::PipelineDefinition;
+use crate::state::interface::{SharedStateStore, StateStore, StateStoreError};
 
 use crate::modules::interface::{ModuleDefinitionError, ModuleManagerList, ModulesDefinition};
 
-#[derive(Deserialize, Serialize)]
-#[serde(rename_all = "lowercase")]
+/// Defines when a job is started
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
 pub enum JobTriggerType {
+    /// the job is only started on request (e.g. zustdp-cli run)
     Manual,
+    /// the job is started by the scheduler according to a cron expression (minute hour day-of-month month day-of-week) in UTC
+    Cron { expression: String },
+    /// the job is started by the scheduler every given number of seconds
+    Interval { seconds: u64 },
+    /// the job is started by the scheduler whenever new files appear in the given directory. The paths of the new files are the input of the job
+    FileWatch { path: String },
 }
 
 /// Definition of a single job
@@ -19,12 +33,19 @@ pub enum JobTriggerType {
 pub struct JobDefinition {
     pub pipeline: String,
     pub threads: u32,
+    #[serde(with = "serde_yaml::with::singleton_map")]
     pub trigger: JobTriggerType,
 }
 
 #[derive(Debug)]
 pub enum JobValidationError {
     PipelineForJobNotFound(GeneralError),
+    DuplicateProcessName(GeneralError),
+    ProcessInputNotFound(GeneralError),
+    PipelineCycleDetected(GeneralError),
+    InvalidTrigger(GeneralError),
+    /// the output of a process does not match the input expected by a process it is connected to. Contains all incompatibilities
+    SchemaMismatch(Vec<GeneralError>),
 }
 
 #[derive(Debug)]
@@ -33,18 +54,115 @@ pub enum JobRunError {
     JobValidationError(JobValidationError),
     JobModuleDefinitionError(ModuleDefinitionError),
     JobModuleInstantiationError(LibraryInstanceError),
+    /// a module failed while processing the data of a process, e.g. pushing data to or pulling output from its stream
+    JobProcessError(LibraryInstanceError),
+    JobSerializationError(GeneralError),
+    JobNotFound(GeneralError),
+    JobCancelled(GeneralError),
+    /// the state of the stateful modules could not be checkpointed or persisted
+    JobStateError(StateStoreError),
+}
+
+/// State of a job or of a single step (process) of a job
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JobState {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
 }
 
-/// Properties of a running job
+/// Status of a single step (process) of a job
+#[derive(Clone, Debug)]
+pub struct JobStepStatus {
+    pub name: String,
+    pub state: JobState,
+    pub started: Option<OffsetDateTime>,
+    pub ended: Option<OffsetDateTime>,
+    /// fuel consumed by the modules executing the step
+    pub fuel_consumed: Option<u64>,
+}
+
+/// Properties of a job
+#[derive(Clone)]
 pub struct Job {
+    pub id: String,
+    /// name of the pipeline run by the job
     pub name: String,
     pub pipeline_definition: PipelineDefinition,
+    pub threads: u32,
+    pub state: JobState,
+    pub started: Option<OffsetDateTime>,
+    pub ended: Option<OffsetDateTime>,
+    /// status of all steps in the order of their execution
+    pub steps: Vec<JobStepStatus>,
+    /// error message if the job failed
+    pub error: Option<String>,
+    pub(crate) cancel_requested: Arc<AtomicBool>,
 }
 
-/// The job list contains all running jobs (key is the unique instance id and their Job information) and module managers
+/// Jobs of a job list (key is the unique instance id). They are shared, so a job can be queried or cancelled from another thread while it is running
+pub type SharedJobMap = Arc<Mutex<HashMap<String, Job>>>;
+
+/// Handle to cancel a job from another thread, e.g. while the job list is running it
+#[derive(Clone)]
+pub struct JobCancelHandle {
+    pub(crate) map: SharedJobMap,
+    pub(crate) job_id: String,
+}
+
+/// Context of a running job shared by all of its steps
+pub(crate) struct JobContext<'a> {
+    /// unique instance id of the job
+    pub job_id: &'a str,
+    /// maximum number of threads to execute a process
+    pub threads: usize,
+    /// set if the job should be cancelled. Interrupts the running process
+    pub cancel_requested: &'a Arc<AtomicBool>,
+    /// input of the job in Arrow IPC format
+    pub serialized_input: &'a Vec<u8>,
+    /// outputs of the already executed processes that are consumed by a process not executed yet (key is the name of the process)
+    pub outputs: &'a HashMap<String, Vec<u8>>,
+}
+
+/// Result of a job that has been run
+pub struct JobResult {
+    /// unique instance id of the job
+    pub id: String,
+    /// output of all processes whose output is not consumed by another process (key is the name of the process) in Arrow IPC format
+    pub outputs: HashMap<String, Vec<u8>>,
+    /// fuel consumed by each process (key is the name of the process)
+    pub fuel_consumed: HashMap<String, u64>,
+}
+
+/// Progress of a job. It is persisted after each successful step, so a failed job can be resumed without reprocessing the completed steps
+/// The input of the job and the outputs of the completed steps are checkpointed separately, so each of them is only written once
+#[derive(Deserialize, Serialize, Clone)]
+pub struct JobCheckpoint {
+    /// name of the pipeline run by the job
+    pub pipeline: String,
+    pub pipeline_definition: PipelineDefinition,
+    pub threads: u32,
+    /// steps that have been completed in the order of their execution. A resumed job continues with the next step
+    pub completed_steps: Vec<JobCheckpointStep>,
+}
+
+/// Step of a job that has been completed
+#[derive(Deserialize, Serialize, Clone)]
+pub struct JobCheckpointStep {
+    /// name of the process executed by the step
+    pub name: String,
+    /// fuel consumed by the process
+    pub fuel_consumed: u64,
+}
+
+/// The job list contains all jobs (key is the unique instance id and their Job information) and module managers
 pub struct JobList {
-    pub map: HashMap<String, Job>,
+    pub map: SharedJobMap,
     pub(crate) module_manager_list: ModuleManagerList,
+    /// state of the stateful modules of all jobs
+    pub(crate) state_store: SharedStateStore,
 }
 
 /// The Job manager manages the jobs
@@ -56,5 +174,97 @@ pub trait JobManager {
         &mut self,
         pipeline_definitions: &HashMap<String, PipelineDefinition>,
         job_definition: &JobDefinition,
+    ) -> Result<JobResult, JobRunError>;
+    fn run_job_with_input(
+        &mut self,
+        pipeline_definitions: &HashMap<String, PipelineDefinition>,
+        job_definition: &JobDefinition,
+        serialized_input: Vec<u8>,
+    ) -> Result<JobResult, JobRunError>;
+    fn create_job(
+        &mut self,
+        pipeline_definitions: &HashMap<String, PipelineDefinition>,
+        job_definition: &JobDefinition,
     ) -> Result<String, JobRunError>;
+    fn start_job(
+        &mut self,
+        job_id: &str,
+        serialized_input: Vec<u8>,
+    ) -> Result<JobResult, JobRunError>;
+    fn resume_job(&mut self, job_id: &str) -> Result<JobResult, JobRunError>;
+    fn get_job(&self, job_id: &str) -> Option<Job>;
+    fn list_jobs(&self) -> Vec<Job>;
+    fn cancel_job(&self, job_id: &str) -> Result<(), JobRunError>;
+    fn cancel_handle(&self, job_id: &str) -> Option<JobCancelHandle>;
+    fn set_state_store(&mut self, state_store: StateStore);
+}
+
+/// A parsed cron expression. Each field contains the values at which the job is started
+#[derive(Clone, Debug)]
+pub struct CronSchedule {
+    pub(crate) minutes: BTreeSet<u8>,
+    pub(crate) hours: BTreeSet<u8>,
+    pub(crate) days_of_month: BTreeSet<u8>,
+    pub(crate) months: BTreeSet<u8>,
+    pub(crate) days_of_week: BTreeSet<u8>,
+    pub(crate) days_of_month_restricted: bool,
+    pub(crate) days_of_week_restricted: bool,
+}
+
+/// A watched directory. Files are reported once their size and modification time did not change between two checks. Files of a failed job are reported again
+#[derive(Clone, Debug)]
+pub struct FileWatchSchedule {
+    pub path: PathBuf,
+    pub(crate) known_files: HashSet<PathBuf>,
+    pub(crate) pending_files: HashMap<PathBuf, (u64, Option<SystemTime>)>,
+    /// files passed to a job that has not finished yet. They become known once the job succeeded
+    pub(crate) processing_files: HashSet<PathBuf>,
+}
+
+/// Schedule of a job that is started by the scheduler
+#[derive(Clone, Debug)]
+pub enum JobSchedule {
+    Cron(CronSchedule),
+    Interval(time::Duration),
+    FileWatch(FileWatchSchedule),
+}
+
+/// A job that is started by the scheduler
+pub struct ScheduledJob {
+    pub name: String,
+    pub schedule: JobSchedule,
+    pub next_run: OffsetDateTime,
+}
+
+/// A job that is due to be started
+#[derive(Debug, PartialEq)]
+pub struct DueJob {
+    pub name: String,
+    /// paths of new files (only for jobs triggered by a file watch)
+    pub files: Vec<String>,
+}
+
+/// The schedule list contains all jobs of an app that are started by a trigger
+pub struct JobScheduleList {
+    pub scheduled_jobs: Vec<ScheduledJob>,
+}
+
+/// The Job scheduler starts jobs according to their trigger
+pub trait JobScheduler {
+    fn new(
+        job_definitions: &HashMap<String, JobDefinition>,
+        now: OffsetDateTime,
+    ) -> Result<Self, JobValidationError>
+    where
+        Self: Sized;
+    fn next_run(&self) -> Option<OffsetDateTime>;
+    fn due_jobs(&mut self, now: OffsetDateTime) -> Vec<DueJob>;
+    fn finish_due_job(&mut self, due_job: &DueJob, succeeded: bool);
+    fn run<M: JobManager>(
+        &mut self,
+        job_manager: &mut M,
+        job_definitions: &HashMap<String, JobDefinition>,
+        pipeline_definitions: &HashMap<String, PipelineDefinition>,
+        stop: &AtomicBool,
+    );
 }
@@ -1,46 +1,281 @@
-use std::{collections::HashMap, sync::Arc};
-use arrow::datatypes::{DataType, Field, Schema};
+use arrow::array::{ArrayRef, MapArray, StringArray, StructArray};
+use arrow::buffer::OffsetBuffer;
+use arrow::datatypes::{DataType, Field, Fields, Schema};
+use arrow::ipc::writer::StreamWriter;
+use arrow::record_batch::RecordBatch;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 use crate::{
     error::error::GeneralError,
-    modules::interface::{ModuleDefinitionError, ModuleManager, ModulesDefinition},
+    modules::interface::{ModuleDefinitionError, ModuleManager, ModuleManifest, ModulesDefinition},
+    modules::library::interface::ExecutionLimits,
+    modules::manifest::validate_schema_compatibility,
     pipeline::interface::{PipelineDefinition, ProcessDefinition},
+    state::interface::{StateHandle, StateManager, StateStore, StateStoreError},
 };
 
-use super::interface::{JobDefinition, JobList, JobManager, JobRunError, JobValidationError};
+use super::dag::{execution_order, last_consumers, ProcessNode};
+use super::executor::{execute_process, ProcessOutput};
+use super::interface::{
+    Job, JobCancelHandle, JobCheckpoint, JobCheckpointStep, JobContext, JobDefinition, JobList,
+    JobManager, JobResult, JobRunError, JobState, JobStepStatus, JobValidationError, SharedJobMap,
+};
+
+/// Maximum number of finished (succeeded, failed or cancelled) jobs kept in a job list
+const MAX_FINISHED_JOBS: usize = 1000;
 
 impl JobManager for JobList {
+    /// Runs a job without input data. Returns once the job has finished
+    ///
+    /// # Arguments
+    /// * `pipeline_definitions` - definitions of all pipelines (key is the name of the pipeline)
+    /// * `job_definition` - definition of the job to run
+    ///
     fn run_job(
         &mut self,
         pipeline_definitions: &HashMap<String, PipelineDefinition>,
         job_definition: &JobDefinition,
+    ) -> Result<JobResult, JobRunError> {
+        self.run_job_with_input(pipeline_definitions, job_definition, Vec::new())
+    }
+
+    /// Runs a job. Returns once the job has finished
+    ///
+    /// # Arguments
+    /// * `pipeline_definitions` - definitions of all pipelines (key is the name of the pipeline)
+    /// * `job_definition` - definition of the job to run
+    /// * `serialized_input` - input of all processes without inputs in Arrow IPC format
+    ///
+    fn run_job_with_input(
+        &mut self,
+        pipeline_definitions: &HashMap<String, PipelineDefinition>,
+        job_definition: &JobDefinition,
+        serialized_input: Vec<u8>,
+    ) -> Result<JobResult, JobRunError> {
+        let job_id = self.create_job(pipeline_definitions, job_definition)?;
+        self.start_job(&job_id, serialized_input)
+    }
+
+    /// Validates a job and adds it in state pending to the job list
+    ///
+    /// # Arguments
+    /// * `pipeline_definitions` - definitions of all pipelines (key is the name of the pipeline)
+    /// * `job_definition` - definition of the job to create
+    ///
+    /// returns the unique instance id of the job
+    fn create_job(
+        &mut self,
+        pipeline_definitions: &HashMap<String, PipelineDefinition>,
+        job_definition: &JobDefinition,
     ) -> Result<String, JobRunError> {
         // check if we can run the job
         let pipeline_definition = match validate_job(pipeline_definitions, job_definition) {
             Ok(pipeline_definition) => pipeline_definition,
             Err(error) => return Err(JobRunError::JobValidationError(error)),
         };
-        let job_id = Uuid::new_v4();
-        for process in &pipeline_definition.process {
-            for (process_name, process_definition) in process.iter() {
-                println!("Process name: {}", process_name);
-                let mut module_instance = match self
-                    .module_manager_list
-                    .get_module_instance(process_definition)
-                {
-                    Ok(module_instance) => module_instance,
-                    Err(error) => return Err(JobRunError::JobModuleDefinitionError(error)),
-                };
-               // process_definition.parameters
-                let serialized_answer=match module_instance.exec_func(vec!(1u8),vec!(1u8)) {
-                    Ok(serialized_data) => serialized_data,
-                    Err(error) => return Err(JobRunError::JobModuleInstantiationError(error))
-                };
+        let process_nodes = match execution_order(&pipeline_definition) {
+            Ok(process_nodes) => process_nodes,
+            Err(error) => return Err(JobRunError::JobValidationError(error)),
+        };
+        validate_pipeline_schemas(&process_nodes, &mut self.module_manager_list)?;
+        let job_id = Uuid::new_v4().to_string();
+        let steps: Vec<JobStepStatus> = process_nodes
+            .iter()
+            .map(|process_node| JobStepStatus {
+                name: process_node.name.clone(),
+                state: JobState::Pending,
+                started: None,
+                ended: None,
+                fuel_consumed: None,
+            })
+            .collect();
+        insert_job(
+            &mut lock_jobs(&self.map),
+            Job {
+                id: job_id.clone(),
+                name: job_definition.pipeline.clone(),
+                pipeline_definition: pipeline_definition,
+                threads: job_definition.threads,
+                state: JobState::Pending,
+                started: None,
+                ended: None,
+                steps: steps,
+                error: None,
+                cancel_requested: Arc::new(AtomicBool::new(false)),
+            },
+        );
+        Ok(job_id)
+    }
+
+    /// Starts a pending job. Returns once the job has finished
+    ///
+    /// # Arguments
+    /// * `job_id` - unique instance id of the job
+    /// * `serialized_input` - input of all processes without inputs in Arrow IPC format
+    ///
+    fn start_job(
+        &mut self,
+        job_id: &str,
+        serialized_input: Vec<u8>,
+    ) -> Result<JobResult, JobRunError> {
+        // the job is checked and marked as running at once, so it cannot be started or cancelled concurrently in between
+        let job_checkpoint = {
+            let mut jobs = lock_jobs(&self.map);
+            let job = match jobs.get_mut(job_id) {
+                Some(job) => job,
+                None => return Err(job_not_found(job_id)),
+            };
+            if job.state != JobState::Pending {
+                return Err(JobRunError::JobStartError(GeneralError::ErrorMessage(
+                    format!("Job {} cannot be started in state {:?}", job_id, job.state),
+                )));
+            }
+            job.state = JobState::Running;
+            job.started = Some(OffsetDateTime::now_utc());
+            JobCheckpoint {
+                pipeline: job.name.clone(),
+                pipeline_definition: job.pipeline_definition.clone(),
+                threads: job.threads,
+                completed_steps: Vec::new(),
+            }
+        };
+        self.run_started_job(job_id, job_checkpoint, serialized_input, HashMap::new())
+    }
+
+    /// Resumes a failed or cancelled job from its last checkpoint. The completed steps are not executed again. Returns once the job has finished
+    /// The job does not need to be in the job list, e.g. if it has been run by another process using the same state store
+    ///
+    /// # Arguments
+    /// * `job_id` - unique instance id of the job
+    ///
+    fn resume_job(&mut self, job_id: &str) -> Result<JobResult, JobRunError> {
+        if let Some(job) = lock_jobs(&self.map).get(job_id) {
+            if job.state != JobState::Failed && job.state != JobState::Cancelled {
+                return Err(JobRunError::JobStartError(GeneralError::ErrorMessage(
+                    format!("Job {} cannot be resumed in state {:?}", job_id, job.state),
+                )));
             }
         }
+        // the input and the outputs of the completed steps are restored, because later steps may depend on them
+        let mut job_checkpoint: Option<JobCheckpoint> = None;
+        let mut serialized_input: Vec<u8> = Vec::new();
+        let mut outputs: HashMap<String, Vec<u8>> = HashMap::new();
+        self.update_state_store(|state_store| {
+            let restored_checkpoint = state_store.restore_checkpoint(job_id)?;
+            serialized_input = state_store.restore_input(job_id)?;
+            for completed_step in restored_checkpoint.completed_steps.iter() {
+                outputs.insert(
+                    completed_step.name.clone(),
+                    state_store.restore_output(job_id, &completed_step.name)?,
+                );
+            }
+            job_checkpoint = Some(restored_checkpoint);
+            Ok(())
+        })?;
+        let job_checkpoint = match job_checkpoint {
+            Some(job_checkpoint) => job_checkpoint,
+            None => return Err(job_not_found(job_id)),
+        };
+        let process_nodes = match execution_order(&job_checkpoint.pipeline_definition) {
+            Ok(process_nodes) => process_nodes,
+            Err(error) => return Err(JobRunError::JobValidationError(error)),
+        };
+        let steps: Vec<JobStepStatus> = process_nodes
+            .iter()
+            .map(|process_node| {
+                let completed_step = job_checkpoint
+                    .completed_steps
+                    .iter()
+                    .find(|completed_step| completed_step.name == process_node.name);
+                JobStepStatus {
+                    name: process_node.name.clone(),
+                    state: match completed_step {
+                        Some(_completed_step) => JobState::Succeeded,
+                        None => JobState::Pending,
+                    },
+                    started: None,
+                    ended: None,
+                    fuel_consumed: completed_step
+                        .map(|completed_step| completed_step.fuel_consumed),
+                }
+            })
+            .collect();
+        insert_job(
+            &mut lock_jobs(&self.map),
+            Job {
+                id: job_id.to_string(),
+                name: job_checkpoint.pipeline.clone(),
+                pipeline_definition: job_checkpoint.pipeline_definition.clone(),
+                threads: job_checkpoint.threads,
+                state: JobState::Running,
+                started: Some(OffsetDateTime::now_utc()),
+                ended: None,
+                steps: steps,
+                error: None,
+                cancel_requested: Arc::new(AtomicBool::new(false)),
+            },
+        );
+        self.run_started_job(job_id, job_checkpoint, serialized_input, outputs)
+    }
 
-      Ok(job_id.to_string())
+    /// Returns a job of the job list
+    ///
+    /// # Arguments
+    /// * `job_id` - unique instance id of the job
+    ///
+    /// returns a snapshot of the job, ie later changes of its state are not reflected
+    fn get_job(&self, job_id: &str) -> Option<Job> {
+        lock_jobs(&self.map).get(job_id).cloned()
+    }
+
+    /// Returns snapshots of all jobs of the job list ordered by their start
+    fn list_jobs(&self) -> Vec<Job> {
+        let mut jobs: Vec<Job> = lock_jobs(&self.map).values().cloned().collect();
+        jobs.sort_by_key(|job| job.started);
+        jobs
+    }
+
+    /// Cancels a job. A pending job is cancelled immediately, a running job before its next step
+    /// A running job can only be cancelled from another thread using a cancel handle, because running it borrows the job list
+    ///
+    /// # Arguments
+    /// * `job_id` - unique instance id of the job
+    ///
+    fn cancel_job(&self, job_id: &str) -> Result<(), JobRunError> {
+        JobCancelHandle {
+            map: self.map.clone(),
+            job_id: job_id.to_string(),
+        }
+        .cancel()
+    }
+
+    /// Returns a handle to cancel a job from another thread while it is running
+    ///
+    /// # Arguments
+    /// * `job_id` - unique instance id of the job
+    ///
+    fn cancel_handle(&self, job_id: &str) -> Option<JobCancelHandle> {
+        if !lock_jobs(&self.map).contains_key(job_id) {
+            return None;
+        }
+        Some(JobCancelHandle {
+            map: self.map.clone(),
+            job_id: job_id.to_string(),
+        })
+    }
+
+    /// Sets the store keeping the state of stateful modules. By default the state is only kept in memory and jobs cannot be resumed
+    ///
+    /// # Arguments
+    /// * `state_store` - state store, e.g. opened from the state definition of the app
+    ///
+    fn set_state_store(&mut self, state_store: StateStore) {
+        self.state_store = Arc::new(Mutex::new(state_store));
     }
 
     // include job metadata
@@ -51,10 +286,401 @@ impl JobManager for JobList {
             Err(module_definition_error) => return Err(module_definition_error),
         };
         Ok(JobList {
-            map: HashMap::new(),
+            map: Arc::new(Mutex::new(HashMap::new())),
             module_manager_list: module_manager_list,
+            state_store: Arc::new(Mutex::new(StateManager::in_memory())),
+        })
+    }
+}
+
+impl JobList {
+    /// Runs a job that has been started or resumed and records its final state
+    ///
+    /// # Arguments
+    /// * `job_id` - unique instance id of the job
+    /// * `job_checkpoint` - progress of the job to continue from
+    /// * `serialized_input` - input of all processes without inputs in Arrow IPC format
+    /// * `outputs` - outputs of the completed steps (key is the name of the process)
+    ///
+    fn run_started_job(
+        &mut self,
+        job_id: &str,
+        job_checkpoint: JobCheckpoint,
+        serialized_input: Vec<u8>,
+        outputs: HashMap<String, Vec<u8>>,
+    ) -> Result<JobResult, JobRunError> {
+        let cancel_requested = match lock_jobs(&self.map).get(job_id) {
+            Some(job) => job.cancel_requested.clone(),
+            None => return Err(job_not_found(job_id)),
+        };
+        let result = self.execute_job(
+            job_id,
+            job_checkpoint,
+            serialized_input,
+            outputs,
+            &cancel_requested,
+        );
+        if let Some(job) = lock_jobs(&self.map).get_mut(job_id) {
+            job.ended = Some(OffsetDateTime::now_utc());
+            job.state = match &result {
+                Ok(_job_result) => JobState::Succeeded,
+                Err(JobRunError::JobCancelled(_error)) => JobState::Cancelled,
+                Err(error) => {
+                    job.error = Some(format!("{:#?}", error));
+                    JobState::Failed
+                }
+            };
+        }
+        result
+    }
+
+    /// Executes all processes of a job not completed yet and keeps track of the status of each step
+    ///
+    /// # Arguments
+    /// * `job_id` - unique instance id of the job
+    /// * `job_checkpoint` - progress of the job to continue from. It contains the pipeline and the completed steps
+    /// * `serialized_input` - input of all processes without inputs in Arrow IPC format
+    /// * `outputs` - outputs of the completed steps (key is the name of the process)
+    /// * `cancel_requested` - set if the job should be cancelled
+    ///
+    fn execute_job(
+        &mut self,
+        job_id: &str,
+        mut job_checkpoint: JobCheckpoint,
+        serialized_input: Vec<u8>,
+        mut outputs: HashMap<String, Vec<u8>>,
+        cancel_requested: &Arc<AtomicBool>,
+    ) -> Result<JobResult, JobRunError> {
+        let process_nodes = match execution_order(&job_checkpoint.pipeline_definition) {
+            Ok(process_nodes) => process_nodes,
+            Err(error) => return Err(JobRunError::JobValidationError(error)),
+        };
+        // independent record batches are processed by several module instances in parallel
+        let threads = job_checkpoint.threads.max(1) as usize;
+        // the input and the progress are checkpointed before the first step and the state is committed after each successful step, so a failed step does not leave partial changes and can be resumed
+        if job_checkpoint.completed_steps.is_empty() {
+            self.update_state_store(|state_store| {
+                state_store.checkpoint_input(job_id, &serialized_input)?;
+                state_store.checkpoint(job_id, &job_checkpoint)
+            })?;
+        }
+        // the output of a process is dropped once the last process consuming it has been executed
+        let last_consumers = last_consumers(&process_nodes);
+        for (step, process_node) in process_nodes.iter().enumerate() {
+            // the output of a completed step has been restored from its checkpoint
+            if job_checkpoint
+                .completed_steps
+                .iter()
+                .any(|completed_step| completed_step.name == process_node.name)
+            {
+                remove_consumed_outputs(&mut outputs, &last_consumers, step);
+                continue;
+            }
+            if cancel_requested.load(Ordering::Relaxed) {
+                self.update_state_store(|state_store| {
+                    state_store.rollback(job_id);
+                    Ok(())
+                })?;
+                self.set_step_states(job_id, step, JobState::Cancelled);
+                return Err(JobRunError::JobCancelled(GeneralError::ErrorMessage(
+                    format!("Job {} has been cancelled", job_id),
+                )));
+            }
+            log::info!("Job {}: executing process {}", job_id, process_node.name);
+            self.set_step_state(job_id, step, JobState::Running);
+            // the output of a process is the input of all processes referring to it. Processes without inputs receive the input of the job
+            let job_context = JobContext {
+                job_id: job_id,
+                threads: threads,
+                cancel_requested: cancel_requested,
+                serialized_input: &serialized_input,
+                outputs: &outputs,
+            };
+            let process_output = self.execute_step(
+                &job_context,
+                process_node,
+                format!("{}/{}", job_checkpoint.pipeline, process_node.name),
+            );
+            match process_output {
+                Ok(process_output) => {
+                    self.set_step_state(job_id, step, JobState::Succeeded);
+                    self.set_step_fuel_consumed(job_id, step, process_output.fuel_consumed);
+                    job_checkpoint.completed_steps.push(JobCheckpointStep {
+                        name: process_node.name.clone(),
+                        fuel_consumed: process_output.fuel_consumed,
+                    });
+                    // the output is written first. The state is committed together with the checkpoint, so the step is either completed including its state or executed again when the job is resumed
+                    self.update_state_store(|state_store| {
+                        state_store.checkpoint_output(
+                            job_id,
+                            &process_node.name,
+                            &process_output.serialized_data,
+                        )?;
+                        state_store.commit_checkpoint(job_id, &job_checkpoint)
+                    })?;
+                    outputs.insert(process_node.name.clone(), process_output.serialized_data);
+                    remove_consumed_outputs(&mut outputs, &last_consumers, step);
+                }
+                // the running process has been interrupted by cancelling the job
+                Err(_error) if cancel_requested.load(Ordering::Relaxed) => {
+                    self.update_state_store(|state_store| {
+                        state_store.rollback(job_id);
+                        Ok(())
+                    })?;
+                    self.set_step_state(job_id, step, JobState::Cancelled);
+                    self.set_step_states(job_id, step + 1, JobState::Cancelled);
+                    return Err(JobRunError::JobCancelled(GeneralError::ErrorMessage(
+                        format!("Job {} has been cancelled", job_id),
+                    )));
+                }
+                Err(error) => {
+                    self.update_state_store(|state_store| {
+                        state_store.rollback(job_id);
+                        Ok(())
+                    })?;
+                    self.set_step_state(job_id, step, JobState::Failed);
+                    self.set_step_states(job_id, step + 1, JobState::Cancelled);
+                    return Err(error);
+                }
+            };
+        }
+        // the checkpoint is not needed anymore once all steps have been completed
+        self.update_state_store(|state_store| state_store.remove_checkpoint(job_id))?;
+        // only the outputs not consumed by another process are left
+        Ok(JobResult {
+            id: job_id.to_string(),
+            outputs: outputs,
+            fuel_consumed: job_checkpoint
+                .completed_steps
+                .into_iter()
+                .map(|completed_step| (completed_step.name, completed_step.fuel_consumed))
+                .collect(),
         })
     }
+
+    /// Executes a single process of a job
+    ///
+    /// # Arguments
+    /// * `job_context` - context of the running job, ie its id, threads, cancel flag, input and outputs of the already executed processes
+    /// * `process_node` - process to execute
+    /// * `state_namespace` - namespace of the state of the process
+    ///
+    fn execute_step(
+        &mut self,
+        job_context: &JobContext,
+        process_node: &ProcessNode,
+        state_namespace: String,
+    ) -> Result<ProcessOutput, JobRunError> {
+        let serialized_metadata = match metadata_to_arrow(&process_node.definition) {
+            Ok(serialized_metadata) => serialized_metadata,
+            Err(error) => return Err(JobRunError::JobSerializationError(error)),
+        };
+        // the inputs are read record batch by record batch while the process is executed
+        let process_inputs: Vec<&[u8]> = if process_node.inputs.is_empty() {
+            vec![job_context.serialized_input.as_slice()]
+        } else {
+            process_node
+                .inputs
+                .iter()
+                .filter_map(|input| job_context.outputs.get(input))
+                .map(|output| output.as_slice())
+                .collect()
+        };
+        // the timeout applies to the whole process, ie all module instances processing it
+        let execution_limits = ExecutionLimits {
+            deadline: process_node
+                .definition
+                .timeout_seconds
+                .map(|timeout_seconds| Instant::now() + Duration::from_secs(timeout_seconds)),
+            cancel_requested: Some(job_context.cancel_requested.clone()),
+            fuel: self
+                .module_manager_list
+                .fuel_limit(&process_node.definition),
+        };
+        let state = StateHandle {
+            store: self.state_store.clone(),
+            job_id: job_context.job_id.to_string(),
+            namespace: state_namespace,
+        };
+        let module_manager_list = &mut self.module_manager_list;
+        let mut serialized_data: Vec<u8> = Vec::new();
+        let fuel_consumed = execute_process(
+            || match module_manager_list
+                .get_module_instance(&process_node.definition, &execution_limits)
+            {
+                Ok(mut module_instance) => {
+                    module_instance.set_state(state.clone());
+                    module_instance.set_job_id(job_context.job_id.to_string());
+                    Ok(module_instance)
+                }
+                Err(error) => Err(JobRunError::JobModuleDefinitionError(error)),
+            },
+            job_context.threads,
+            &serialized_metadata,
+            &process_inputs,
+            &mut serialized_data,
+        )?;
+        Ok(ProcessOutput {
+            serialized_data: serialized_data,
+            fuel_consumed: fuel_consumed,
+        })
+    }
+
+    /// Applies a change to the state store
+    ///
+    /// # Arguments
+    /// * `f` - change to apply, e.g. taking a checkpoint
+    ///
+    fn update_state_store(
+        &self,
+        f: impl FnOnce(&mut StateStore) -> Result<(), StateStoreError>,
+    ) -> Result<(), JobRunError> {
+        let mut state_store = match self.state_store.lock() {
+            Ok(state_store) => state_store,
+            Err(err) => {
+                return Err(JobRunError::JobStateError(
+                    StateStoreError::StateStoreIOError(GeneralError::ErrorMessage(format!(
+                        "State store is not available: {}",
+                        err
+                    ))),
+                ))
+            }
+        };
+        match f(&mut state_store) {
+            Ok(()) => Ok(()),
+            Err(error) => Err(JobRunError::JobStateError(error)),
+        }
+    }
+
+    /// Sets the state of a step of a job and records when it started or ended
+    fn set_step_state(&mut self, job_id: &str, step: usize, state: JobState) {
+        if let Some(step_status) = lock_jobs(&self.map)
+            .get_mut(job_id)
+            .and_then(|job| job.steps.get_mut(step))
+        {
+            step_status.state = state;
+            match state {
+                JobState::Running => step_status.started = Some(OffsetDateTime::now_utc()),
+                JobState::Pending => (),
+                _ => step_status.ended = Some(OffsetDateTime::now_utc()),
+            }
+        }
+    }
+
+    /// Sets the fuel consumed by a step of a job
+    fn set_step_fuel_consumed(&mut self, job_id: &str, step: usize, fuel_consumed: u64) {
+        if let Some(step_status) = lock_jobs(&self.map)
+            .get_mut(job_id)
+            .and_then(|job| job.steps.get_mut(step))
+        {
+            step_status.fuel_consumed = Some(fuel_consumed);
+        }
+    }
+
+    /// Sets the state of all steps of a job starting from the given step
+    fn set_step_states(&mut self, job_id: &str, first_step: usize, state: JobState) {
+        if let Some(job) = lock_jobs(&self.map).get_mut(job_id) {
+            for step_status in job.steps.iter_mut().skip(first_step) {
+                step_status.state = state;
+            }
+        }
+    }
+}
+
+impl JobCancelHandle {
+    /// Cancels the job. A pending job is cancelled immediately. A running job is cancelled before its next step and its running process is interrupted
+    pub fn cancel(&self) -> Result<(), JobRunError> {
+        let mut jobs = lock_jobs(&self.map);
+        let job = match jobs.get_mut(&self.job_id) {
+            Some(job) => job,
+            None => return Err(job_not_found(&self.job_id)),
+        };
+        match job.state {
+            JobState::Pending => {
+                job.state = JobState::Cancelled;
+                job.ended = Some(OffsetDateTime::now_utc());
+                for step in job.steps.iter_mut() {
+                    step.state = JobState::Cancelled;
+                }
+                job.cancel_requested.store(true, Ordering::Relaxed);
+                Ok(())
+            }
+            JobState::Running => {
+                job.cancel_requested.store(true, Ordering::Relaxed);
+                Ok(())
+            }
+            _ => Err(JobRunError::JobStartError(GeneralError::ErrorMessage(
+                format!(
+                    "Job {} cannot be cancelled in state {:?}",
+                    self.job_id, job.state
+                ),
+            ))),
+        }
+    }
+}
+
+/// Locks the jobs of a job list. The jobs are only changed while being locked and each change leaves them consistent, so a lock poisoned by a panicking thread can still be used
+fn lock_jobs(map: &SharedJobMap) -> MutexGuard<'_, HashMap<String, Job>> {
+    match map.lock() {
+        Ok(jobs) => jobs,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// Adds a job to the jobs of a job list. A job with the same id is replaced. The oldest finished jobs are removed, so a long running scheduler does not keep all of its jobs
+/// # Arguments
+/// * `jobs` - jobs of the job list
+/// * `job` - job to add
+fn insert_job(jobs: &mut HashMap<String, Job>, job: Job) {
+    jobs.insert(job.id.clone(), job);
+    remove_finished_jobs(jobs, MAX_FINISHED_JOBS);
+}
+
+/// Removes the finished jobs that ended first until at most the given number of finished jobs is left. Pending and running jobs are kept
+/// # Arguments
+/// * `jobs` - jobs of the job list
+/// * `max_finished_jobs` - maximum number of finished jobs to keep
+fn remove_finished_jobs(jobs: &mut HashMap<String, Job>, max_finished_jobs: usize) {
+    let mut finished_jobs: Vec<(Option<OffsetDateTime>, String)> = jobs
+        .values()
+        .filter(|job| job.state != JobState::Pending && job.state != JobState::Running)
+        .map(|job| (job.ended, job.id.clone()))
+        .collect();
+    if finished_jobs.len() <= max_finished_jobs {
+        return;
+    }
+    finished_jobs.sort();
+    for (_ended, job_id) in finished_jobs
+        .iter()
+        .take(finished_jobs.len() - max_finished_jobs)
+    {
+        jobs.remove(job_id);
+    }
+}
+
+/// Removes the outputs of all processes that are not consumed by a process executed after the given step
+///
+/// # Arguments
+/// * `outputs` - outputs of the executed processes (key is the name of the process)
+/// * `last_consumers` - position of the last process consuming the output of each process
+/// * `step` - position of the executed process
+fn remove_consumed_outputs(
+    outputs: &mut HashMap<String, Vec<u8>>,
+    last_consumers: &HashMap<String, usize>,
+    step: usize,
+) {
+    outputs.retain(|process_name, _| match last_consumers.get(process_name) {
+        Some(last_consumer) => *last_consumer > step,
+        None => true,
+    });
+}
+
+/// Error in case a job is not in the job list
+fn job_not_found(job_id: &str) -> JobRunError {
+    JobRunError::JobNotFound(GeneralError::ErrorMessage(format!(
+        "Job {} not found",
+        job_id
+    )))
 }
 
 pub fn validate_job(
@@ -75,53 +701,501 @@ pub fn validate_job(
     Ok(pipeline_definition.clone())
 }
 
+/// Validates that the output schema of each process is compatible with the input schema of the processes it is connected to
+/// The schemas are declared in the manifests of the modules. Processes whose modules do not have a manifest are not checked
+/// # Arguments
+/// * `process_nodes` - processes of a pipeline
+/// * `module_manager` - module manager to read the manifests of the modules
+/// returns all incompatibilities at once
+pub fn validate_pipeline_schemas<M: ModuleManager>(
+    process_nodes: &[ProcessNode],
+    module_manager: &mut M,
+) -> Result<(), JobRunError> {
+    let mut module_manifests: HashMap<&String, Option<ModuleManifest>> = HashMap::new();
+    for process_node in process_nodes {
+        match module_manager.get_module_manifest(&process_node.definition) {
+            Ok(module_manifest) => module_manifests.insert(&process_node.name, module_manifest),
+            Err(error) => return Err(JobRunError::JobModuleDefinitionError(error)),
+        };
+    }
+    let mut violations: Vec<GeneralError> = Vec::new();
+    for process_node in process_nodes {
+        let input_manifest = match &module_manifests[&process_node.name] {
+            Some(input_manifest) => input_manifest,
+            None => continue,
+        };
+        for input in &process_node.inputs {
+            if let Some(Some(output_manifest)) = module_manifests.get(input) {
+                violations.extend(validate_schema_compatibility(
+                    input,
+                    output_manifest,
+                    &process_node.name,
+                    input_manifest,
+                ));
+            }
+        }
+    }
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(JobRunError::JobValidationError(
+            JobValidationError::SchemaMismatch(violations),
+        ))
+    }
+}
+
 /// Converts metadata for a process into arrow format
+/// The metadata is a record batch with exactly one row. The column "parameters" contains all parameters of the process as a Map<Utf8,Utf8>
 /// # Arguments
 /// * `process_definition` - process definition
-fn metadata_to_arrow(process_definition: &ProcessDefinition) -> Vec<u8> {
-   // define schema
-   let schema = Schema::new(vec![
-    Field::new(
+/// returns the metadata serialized as Arrow IPC stream
+fn metadata_to_arrow(process_definition: &ProcessDefinition) -> Result<Vec<u8>, GeneralError> {
+    // define schema
+    let keys_field = Arc::new(Field::new("keys", DataType::Utf8, false));
+    let values_field = Arc::new(Field::new("values", DataType::Utf8, false));
+    let entries_fields = Fields::from(vec![keys_field, values_field]);
+    let entries_field = Arc::new(Field::new(
+        "entries",
+        DataType::Struct(entries_fields.clone()),
+        false,
+    ));
+    let schema = Arc::new(Schema::new(vec![Field::new(
         "parameters",
-        DataType::Map(Arc::new(
-            Field::new("entries",
-            DataType::Struct(arrow::datatypes::Fields::from(vec![Field::new(
-            "keys",
-            DataType::Utf8,
-            false,
-        ),Field::new(
-            "values",
-            DataType::Utf8,
-            false,
-        )])),false)),false),
-    false)
-]);
-//let mut decoder = ReaderBuilder::new(Arc::new(schema)).build_decoder().unwrap();
-
-// add values
-/*for param in process_definition.parameters {
-    param.
+        DataType::Map(entries_field.clone(), false),
+        false,
+    )]));
+    // add values
+    let mut keys: Vec<&str> = Vec::new();
+    let mut values: Vec<&str> = Vec::new();
+    for parameter in &process_definition.parameters {
+        for (key, value) in parameter.iter() {
+            keys.push(key);
+            values.push(value);
+        }
+    }
+    let parameter_count = keys.len();
+    let entries = match StructArray::try_new(
+        entries_fields,
+        vec![
+            Arc::new(StringArray::from(keys)) as ArrayRef,
+            Arc::new(StringArray::from(values)) as ArrayRef,
+        ],
+        None,
+    ) {
+        Ok(entries) => entries,
+        Err(err) => {
+            return Err(GeneralError::ErrorMessage(format!(
+                "Cannot create parameter entries: {}",
+                err
+            )))
+        }
+    };
+    let parameters = match MapArray::try_new(
+        entries_field,
+        OffsetBuffer::from_lengths([parameter_count]),
+        entries,
+        None,
+        false,
+    ) {
+        Ok(parameters) => parameters,
+        Err(err) => {
+            return Err(GeneralError::ErrorMessage(format!(
+                "Cannot create parameter map: {}",
+                err
+            )))
+        }
+    };
+    // build a record batch
+    let batch = match RecordBatch::try_new(schema.clone(), vec![Arc::new(parameters)]) {
+        Ok(batch) => batch,
+        Err(err) => {
+            return Err(GeneralError::ErrorMessage(format!(
+                "Cannot create parameter record batch: {}",
+                err
+            )))
+        }
+    };
+    // serialize it
+    let mut stream_writer = match StreamWriter::try_new(Vec::new(), &schema) {
+        Ok(stream_writer) => stream_writer,
+        Err(err) => {
+            return Err(GeneralError::ErrorMessage(format!(
+                "Cannot create Arrow stream writer for parameters: {}",
+                err
+            )))
+        }
+    };
+    match stream_writer.write(&batch) {
+        Ok(()) => (),
+        Err(err) => {
+            return Err(GeneralError::ErrorMessage(format!(
+                "Cannot serialize parameters: {}",
+                err
+            )))
+        }
+    };
+    match stream_writer.into_inner() {
+        Ok(serialized_batch) => Ok(serialized_batch),
+        Err(err) => Err(GeneralError::ErrorMessage(format!(
+            "Cannot finish serialization of parameters: {}",
+            err
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::jobs::interface::{
+        JobDefinition, JobList, JobManager, JobRunError, JobState, JobValidationError,
+    };
+    use crate::modules::interface::{ModuleType, ModulesDefinition, WasmModulesDefinition};
+    use crate::pipeline::interface::PipelineDefinition;
+    use crate::pipeline::interface::{ProcessDefinition, ProcessModuleRequirements};
+    use std::collections::HashMap;
+
+    #[test]
+    // Test that the parameters of a process are serialized as a map in Arrow IPC format
+    fn test_metadata_to_arrow() {
+        use arrow::array::{Array, MapArray, StringArray};
+        use arrow::ipc::reader::StreamReader;
+        let process_definition = ProcessDefinition {
+            module: ProcessModuleRequirements {
+                name: "test.wasm".to_string(),
+                r#type: ModuleType::Wasm,
+            },
+            inputs: None,
+            timeout_seconds: None,
+            fuel: None,
+            parameters: vec![
+                HashMap::from([("text".to_string(), "Hello, World!".to_string())]),
+                HashMap::from([("language".to_string(), "en".to_string())]),
+            ],
+        };
+        let serialized_metadata = super::metadata_to_arrow(&process_definition).unwrap();
+        let mut reader = StreamReader::try_new(serialized_metadata.as_slice(), None).unwrap();
+        let batch = reader.next().unwrap().unwrap();
+        assert_eq!(batch.num_rows(), 1);
+        let parameters = batch
+            .column_by_name("parameters")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<MapArray>()
+            .unwrap();
+        let entries = parameters.value(0);
+        let keys = entries
+            .column(0)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        let values = entries
+            .column(1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys.value(0), "text");
+        assert_eq!(values.value(0), "Hello, World!");
+        assert_eq!(keys.value(1), "language");
+        assert_eq!(values.value(1), "en");
+    }
+
+    #[test]
+    // Test that the output of a process is the input of the next process and the output of the last process is returned
+    fn test_run_job_chains_processes() {
+        const WASM_MODULE_PATH: &str = "tests/data/modules/library/wasm";
+        const PIPELINE_STRING: &str = "process:\n - parameters_as_data:\n    module:\n     name: metadata.wat\n     type: wasm\n    parameters:\n     - text: Hello, World!\n - echo:\n    module:\n     name: echo.wat\n     type: wasm\n    parameters: []\n";
+        let pipeline_definition: PipelineDefinition =
+            serde_yaml::from_str(PIPELINE_STRING).unwrap();
+        let pipeline_definitions =
+            HashMap::from([("test_pipeline".to_string(), pipeline_definition.clone())]);
+        let job_definition: JobDefinition =
+            serde_yaml::from_str("pipeline: test_pipeline\nthreads: 1\ntrigger: manual\n").unwrap();
+        let modules_definition = ModulesDefinition {
+            wasm: Some(WasmModulesDefinition {
+                module_path_base: vec![WASM_MODULE_PATH.to_string()],
+                modules: HashMap::new(),
+            }),
+        };
+        let mut jobmgr: JobList = JobManager::new(&modules_definition).unwrap();
+        let job_result = jobmgr
+            .run_job(&pipeline_definitions, &job_definition)
+            .unwrap();
+        // the first process returns its parameters, the second one echoes them
+        let (_, first_process_definition) = pipeline_definition.process[0].iter().next().unwrap();
+        let expected = super::metadata_to_arrow(first_process_definition).unwrap();
+        assert_eq!(job_result.outputs.len(), 1);
+        assert_eq!(job_result.outputs["echo"], expected);
+        assert!(job_result.fuel_consumed["parameters_as_data"] > 0);
+        assert!(job_result.fuel_consumed["echo"] > 0);
+        // the job and its steps are tracked in the job list
+        let job = jobmgr.get_job(&job_result.id).unwrap();
+        assert_eq!(job.state, JobState::Succeeded);
+        assert!(job.started.is_some() && job.ended.is_some());
+        let step_states: Vec<(&str, JobState)> = job
+            .steps
+            .iter()
+            .map(|step| (step.name.as_str(), step.state))
+            .collect();
+        assert_eq!(
+            step_states,
+            vec![
+                ("parameters_as_data", JobState::Succeeded),
+                ("echo", JobState::Succeeded)
+            ]
+        );
+        assert_eq!(jobmgr.list_jobs().len(), 1);
+    }
+
+    #[test]
+    // Test that a pending job can be cancelled and cannot be started anymore
+    fn test_cancel_pending_job() {
+        const PIPELINE_STRING: &str = "process:\n - echo:\n    module:\n     name: echo.wat\n     type: wasm\n    parameters: []\n";
+        let pipeline_definitions = HashMap::from([(
+            "test_pipeline".to_string(),
+            serde_yaml::from_str::<PipelineDefinition>(PIPELINE_STRING).unwrap(),
+        )]);
+        let job_definition: JobDefinition =
+            serde_yaml::from_str("pipeline: test_pipeline\nthreads: 1\ntrigger: manual\n").unwrap();
+        let modules_definition = ModulesDefinition {
+            wasm: Some(WasmModulesDefinition {
+                module_path_base: vec!["tests/data/modules/library/wasm".to_string()],
+                modules: HashMap::new(),
+            }),
+        };
+        let mut jobmgr: JobList = JobManager::new(&modules_definition).unwrap();
+        let job_id = jobmgr
+            .create_job(&pipeline_definitions, &job_definition)
+            .unwrap();
+        assert_eq!(jobmgr.get_job(&job_id).unwrap().state, JobState::Pending);
+        jobmgr.cancel_job(&job_id).unwrap();
+        assert_eq!(jobmgr.get_job(&job_id).unwrap().state, JobState::Cancelled);
+        assert!(jobmgr.start_job(&job_id, Vec::new()).is_err());
+        assert!(jobmgr.cancel_job("unknown").is_err());
+        // only the finished jobs that ended last are kept
+        let pending_job_id = jobmgr
+            .create_job(&pipeline_definitions, &job_definition)
+            .unwrap();
+        let mut cancelled_job_ids: Vec<String> = Vec::new();
+        for _ in 0..2 {
+            let cancelled_job_id = jobmgr
+                .create_job(&pipeline_definitions, &job_definition)
+                .unwrap();
+            jobmgr.cancel_job(&cancelled_job_id).unwrap();
+            cancelled_job_ids.push(cancelled_job_id);
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+        super::remove_finished_jobs(&mut super::lock_jobs(&jobmgr.map), 1);
+        let mut job_ids: Vec<String> = jobmgr.list_jobs().into_iter().map(|job| job.id).collect();
+        job_ids.sort();
+        let mut expected_job_ids = vec![pending_job_id, cancelled_job_ids[1].clone()];
+        expected_job_ids.sort();
+        assert_eq!(job_ids, expected_job_ids);
+    }
+
+    #[test]
+    // Test that a running job is cancelled by interrupting its running process
+    fn test_cancel_running_job() {
+        const PIPELINE_STRING: &str = "process:\n - endless:\n    module:\n     name: loop.wat\n     type: wasm\n    parameters: []\n";
+        let pipeline_definitions = HashMap::from([(
+            "test_pipeline".to_string(),
+            serde_yaml::from_str::<PipelineDefinition>(PIPELINE_STRING).unwrap(),
+        )]);
+        let job_definition: JobDefinition =
+            serde_yaml::from_str("pipeline: test_pipeline\nthreads: 1\ntrigger: manual\n").unwrap();
+        let modules_definition = ModulesDefinition {
+            wasm: Some(WasmModulesDefinition {
+                module_path_base: vec!["tests/data/modules/library/wasm".to_string()],
+                modules: HashMap::new(),
+            }),
+        };
+        let mut jobmgr: JobList = JobManager::new(&modules_definition).unwrap();
+        let job_id = jobmgr
+            .create_job(&pipeline_definitions, &job_definition)
+            .unwrap();
+        let cancel_handle = jobmgr.cancel_handle(&job_id).unwrap();
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(200));
+            cancel_handle.cancel().unwrap();
+        });
+        match jobmgr.start_job(&job_id, Vec::new()) {
+            Err(JobRunError::JobCancelled(_)) => (),
+            _ => panic!("Running job not cancelled"),
+        }
+        canceller.join().unwrap();
+        let job = jobmgr.get_job(&job_id).unwrap();
+        assert_eq!(job.state, JobState::Cancelled);
+        assert_eq!(job.steps[0].state, JobState::Cancelled);
+    }
+
+    #[test]
+    // Test that a failed job is resumed from its last checkpoint without executing the completed steps again
+    fn test_resume_failed_job() {
+        use crate::state::interface::{StateDefinition, StateManager, StateStore};
+        const PIPELINE_STRING: &str = "process:\n - parameters_as_data:\n    module:\n     name: metadata.wat\n     type: wasm\n    parameters:\n     - text: Hello, World!\n - echo:\n    module:\n     name: resumable.wat\n     type: wasm\n    parameters: []\n";
+        let pipeline_definition: PipelineDefinition =
+            serde_yaml::from_str(PIPELINE_STRING).unwrap();
+        let pipeline_definitions =
+            HashMap::from([("test_pipeline".to_string(), pipeline_definition.clone())]);
+        let job_definition: JobDefinition =
+            serde_yaml::from_str("pipeline: test_pipeline\nthreads: 1\ntrigger: manual\n").unwrap();
+        // the second process fails in the first run and succeeds once the module has been fixed
+        let path = std::env::temp_dir().join(format!("zustdp-resume-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&path).unwrap();
+        std::fs::copy(
+            "tests/data/modules/library/wasm/trap.wat",
+            path.join("resumable.wat"),
+        )
+        .unwrap();
+        let modules_definition = ModulesDefinition {
+            wasm: Some(WasmModulesDefinition {
+                module_path_base: vec![
+                    "tests/data/modules/library/wasm".to_string(),
+                    path.display().to_string(),
+                ],
+                modules: HashMap::new(),
+            }),
+        };
+        let state_definition = StateDefinition {
+            path: path.join("state").display().to_string(),
+        };
+        let mut jobmgr: JobList = JobManager::new(&modules_definition).unwrap();
+        let state_store: StateStore = StateManager::open(&state_definition).unwrap();
+        jobmgr.set_state_store(state_store);
+        let job_id = jobmgr
+            .create_job(&pipeline_definitions, &job_definition)
+            .unwrap();
+        assert!(jobmgr.start_job(&job_id, Vec::new()).is_err());
+        let job = jobmgr.get_job(&job_id).unwrap();
+        assert_eq!(job.state, JobState::Failed);
+        assert_eq!(job.steps[0].state, JobState::Succeeded);
+        assert_eq!(job.steps[1].state, JobState::Failed);
+        // a job without checkpoint cannot be resumed
+        assert!(jobmgr.resume_job("unknown").is_err());
+        // the job is resumed by another job list using the same state store, e.g. another zustdp-cli process
+        std::fs::copy(
+            "tests/data/modules/library/wasm/echo.wat",
+            path.join("resumable.wat"),
+        )
+        .unwrap();
+        let mut jobmgr: JobList = JobManager::new(&modules_definition).unwrap();
+        let state_store: StateStore = StateManager::open(&state_definition).unwrap();
+        jobmgr.set_state_store(state_store);
+        let job_result = jobmgr.resume_job(&job_id).unwrap();
+        let (_, first_process_definition) = pipeline_definition.process[0].iter().next().unwrap();
+        let expected = super::metadata_to_arrow(first_process_definition).unwrap();
+        assert_eq!(job_result.id, job_id);
+        assert_eq!(job_result.outputs["echo"], expected);
+        let job = jobmgr.get_job(&job_id).unwrap();
+        assert_eq!(job.state, JobState::Succeeded);
+        // the completed step has not been executed again
+        assert!(job.steps[0].started.is_none());
+        assert!(job.steps[1].started.is_some());
+        // a succeeded job cannot be resumed and its checkpoint has been removed
+        assert!(jobmgr.resume_job(&job_id).is_err());
+        let mut state_store: StateStore = StateManager::open(&state_definition).unwrap();
+        assert!(state_store.restore_checkpoint(&job_id).is_err());
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    // Test that a failing job does not roll back the state committed by another job running at the same time
+    fn test_concurrent_jobs_state() {
+        use crate::state::interface::{StateDefinition, StateManager, StateStore};
+        const PIPELINES_STRING: &str = "counting_pipeline:\n process:\n  - count:\n     module:\n      name: state_counter.wat\n      type: wasm\n     parameters: []\nfailing_pipeline:\n process:\n  - count:\n     module:\n      name: state_counter.wat\n      type: wasm\n     parameters: []\n  - endless:\n     module:\n      name: loop.wat\n      type: wasm\n     inputs: []\n     timeout_seconds: 1\n     parameters: []\n";
+        let pipeline_definitions: HashMap<String, PipelineDefinition> =
+            serde_yaml::from_str(PIPELINES_STRING).unwrap();
+        let modules_definition = ModulesDefinition {
+            wasm: Some(WasmModulesDefinition {
+                module_path_base: vec!["tests/data/modules/library/wasm".to_string()],
+                modules: HashMap::new(),
+            }),
+        };
+        let path = std::env::temp_dir().join(format!("zustdp-concurrent-{}", uuid::Uuid::new_v4()));
+        let state_definition = StateDefinition {
+            path: path.display().to_string(),
+        };
+        // both job lists share the same state store
+        let mut counting_jobmgr: JobList = JobManager::new(&modules_definition).unwrap();
+        let state_store: StateStore = StateManager::open(&state_definition).unwrap();
+        counting_jobmgr.set_state_store(state_store);
+        let mut failing_jobmgr: JobList = JobManager::new(&modules_definition).unwrap();
+        failing_jobmgr.state_store = counting_jobmgr.state_store.clone();
+        let failing_job_definition: JobDefinition =
+            serde_yaml::from_str("pipeline: failing_pipeline\nthreads: 1\ntrigger: manual\n")
+                .unwrap();
+        let failing_job_id = failing_jobmgr
+            .create_job(&pipeline_definitions, &failing_job_definition)
+            .unwrap();
+        let failing_jobs = failing_jobmgr.map.clone();
+        let failing_job_thread_id = failing_job_id.clone();
+        let failing_job = std::thread::spawn(move || {
+            let result = failing_jobmgr.start_job(&failing_job_thread_id, Vec::new());
+            (
+                result.is_err(),
+                failing_jobmgr.get_job(&failing_job_thread_id).unwrap(),
+            )
+        });
+        // the counting job runs while the failing job executes its endless step until it times out
+        loop {
+            let endless_step_running =
+                failing_jobs.lock().unwrap()[&failing_job_id].steps[1].state == JobState::Running;
+            if endless_step_running {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        let counting_job_definition: JobDefinition =
+            serde_yaml::from_str("pipeline: counting_pipeline\nthreads: 1\ntrigger: manual\n")
+                .unwrap();
+        let job_result = counting_jobmgr
+            .run_job(&pipeline_definitions, &counting_job_definition)
+            .unwrap();
+        assert_eq!(job_result.outputs["count"], vec![1u8]);
+        let (failed, failing_job) = failing_job.join().unwrap();
+        assert!(failed);
+        assert_eq!(failing_job.state, JobState::Failed);
+        assert_eq!(failing_job.steps[0].state, JobState::Succeeded);
+        assert_eq!(failing_job.steps[1].state, JobState::Failed);
+        // the state committed by both jobs is kept, also after opening the store again
+        let reopened_state_store: StateStore = StateManager::open(&state_definition).unwrap();
+        for state_store in [
+            &*counting_jobmgr.state_store.lock().unwrap(),
+            &reopened_state_store,
+        ] {
+            assert_eq!(
+                state_store.get("", "counting_pipeline/count", b"count"),
+                Some(vec![1u8])
+            );
+            assert_eq!(
+                state_store.get("", "failing_pipeline/count", b"count"),
+                Some(vec![1u8])
+            );
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    // Test that a job is rejected if the output of a process does not match the input expected by the next process
+    fn test_create_job_schema_mismatch() {
+        const PIPELINE_STRING: &str = "process:\n - echo:\n    module:\n     name: echo.wat\n     type: wasm\n    parameters: []\n - parameters_as_data:\n    module:\n     name: metadata.wat\n     type: wasm\n    parameters: []\n";
+        let pipeline_definitions = HashMap::from([(
+            "test_pipeline".to_string(),
+            serde_yaml::from_str::<PipelineDefinition>(PIPELINE_STRING).unwrap(),
+        )]);
+        let job_definition: JobDefinition =
+            serde_yaml::from_str("pipeline: test_pipeline\nthreads: 1\ntrigger: manual\n").unwrap();
+        let modules_definition = ModulesDefinition {
+            wasm: Some(WasmModulesDefinition {
+                module_path_base: vec!["tests/data/modules/library/wasm".to_string()],
+                modules: HashMap::new(),
+            }),
+        };
+        let mut jobmgr: JobList = JobManager::new(&modules_definition).unwrap();
+        match jobmgr.create_job(&pipeline_definitions, &job_definition) {
+            Err(JobRunError::JobValidationError(JobValidationError::SchemaMismatch(
+                violations,
+            ))) => assert_eq!(violations.len(), 1),
+            _ => panic!("Schema mismatch not detected"),
+        }
+    }
 }
-let command = StringArray::from(vec!["test"]);
-
-let parameters = StructArray::from(vec![(
-    Arc::new(Field::new("filename", DataType::Utf8, false)),
-    Arc::new(StringArray::from(vec!["test.txt"])) as ArrayRef,
-)]);
-// build a record batch
-let batch = RecordBatch::try_new(
-    Arc::new(schema.clone()),
-    vec![Arc::new(command), Arc::new(config)],
-)
-.unwrap();
-// serialize it
-let buffer: Vec<u8> = Vec::new();
-
-let mut stream_writer = StreamWriter::try_new(buffer, &schema).unwrap();
-stream_writer.write(&batch).unwrap();
-
-let serialized_batch = stream_writer.into_inner().unwrap();
-return serialized_batch;
-*/
-Vec::new()
-}
\ No newline at end of file
@@ -1,3 +1,5 @@
+pub mod dag;
+pub mod executor;
 pub mod interface;
 pub mod manager;
 /**
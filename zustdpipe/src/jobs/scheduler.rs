@@ -1 +1,618 @@
+//! The scheduler starts jobs that are triggered by a cron expression, in a fixed interval or by new files in a directory.
+//! Cron expressions have five fields (minute hour day-of-month month day-of-week) and are evaluated in UTC.
+//! Each field can be a `*`, a single value, a range (e.g. `1-5`), a step (e.g. `*/15` or `0-30/10`) or a list of them separated by `,`.
 
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use arrow::array::StringArray;
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use time::{Duration, OffsetDateTime, Time};
+
+use crate::error::error::GeneralError;
+use crate::pipeline::interface::PipelineDefinition;
+
+use super::executor::batches_to_arrow;
+use super::interface::{
+    CronSchedule, DueJob, FileWatchSchedule, JobDefinition, JobManager, JobRunError, JobSchedule,
+    JobScheduleList, JobScheduler, JobTriggerType, JobValidationError, ScheduledJob,
+};
+
+/// Maximum time the scheduler sleeps before checking if it should stop
+const MAX_SLEEP: std::time::Duration = std::time::Duration::from_secs(1);
+/// Maximum number of days to search for the next run of a cron expression. Some expressions (e.g. 30th of February) never match
+const MAX_CRON_SEARCH_DAYS: u32 = 5 * 366;
+/// Time between two checks of a watched directory
+const FILE_WATCH_POLL_INTERVAL: Duration = Duration::seconds(1);
+
+impl JobScheduler for JobScheduleList {
+    /// Returns a new schedule containing all jobs that are not triggered manually
+    ///
+    /// # Arguments
+    /// * `job_definitions` - definitions of all jobs (key is the name of the job)
+    /// * `now` - current time from which the first run of each job is calculated
+    ///
+    fn new(
+        job_definitions: &HashMap<String, JobDefinition>,
+        now: OffsetDateTime,
+    ) -> Result<JobScheduleList, JobValidationError> {
+        let mut scheduled_jobs: Vec<ScheduledJob> = Vec::new();
+        for (job_name, job_definition) in job_definitions {
+            let schedule = match &job_definition.trigger {
+                JobTriggerType::Manual => continue,
+                JobTriggerType::Cron { expression } => match CronSchedule::parse(expression) {
+                    Ok(cron_schedule) => JobSchedule::Cron(cron_schedule),
+                    Err(error) => return Err(error),
+                },
+                JobTriggerType::Interval { seconds } => {
+                    if *seconds == 0 {
+                        return Err(JobValidationError::InvalidTrigger(
+                            GeneralError::ErrorMessage(format!(
+                                "Interval of job {} must be at least one second",
+                                job_name
+                            )),
+                        ));
+                    }
+                    JobSchedule::Interval(Duration::seconds(*seconds as i64))
+                }
+                JobTriggerType::FileWatch { path } => match FileWatchSchedule::new(path) {
+                    Ok(file_watch_schedule) => JobSchedule::FileWatch(file_watch_schedule),
+                    Err(error) => return Err(error),
+                },
+            };
+            let next_run = match schedule.next_run_after(now) {
+                Some(next_run) => next_run,
+                None => {
+                    return Err(JobValidationError::InvalidTrigger(
+                        GeneralError::ErrorMessage(format!("Job {} is never triggered", job_name)),
+                    ))
+                }
+            };
+            scheduled_jobs.push(ScheduledJob {
+                name: job_name.clone(),
+                schedule: schedule,
+                next_run: next_run,
+            });
+        }
+        Ok(JobScheduleList {
+            scheduled_jobs: scheduled_jobs,
+        })
+    }
+
+    /// Returns the time when the next job is started
+    fn next_run(&self) -> Option<OffsetDateTime> {
+        self.scheduled_jobs
+            .iter()
+            .map(|scheduled_job| scheduled_job.next_run)
+            .min()
+    }
+
+    /// Returns all jobs that are due and calculates their next run
+    /// Jobs triggered by a file watch are only due if new files appeared in the watched directory
+    ///
+    /// # Arguments
+    /// * `now` - current time
+    ///
+    fn due_jobs(&mut self, now: OffsetDateTime) -> Vec<DueJob> {
+        let mut due_jobs: Vec<DueJob> = Vec::new();
+        self.scheduled_jobs.retain_mut(|scheduled_job| {
+            if scheduled_job.next_run > now {
+                return true;
+            }
+            match &mut scheduled_job.schedule {
+                JobSchedule::FileWatch(file_watch_schedule) => {
+                    let files = file_watch_schedule.new_files();
+                    if !files.is_empty() {
+                        due_jobs.push(DueJob {
+                            name: scheduled_job.name.clone(),
+                            files: files,
+                        });
+                    }
+                }
+                _ => due_jobs.push(DueJob {
+                    name: scheduled_job.name.clone(),
+                    files: Vec::new(),
+                }),
+            };
+            match scheduled_job.schedule.next_run_after(now) {
+                Some(next_run) => {
+                    scheduled_job.next_run = next_run;
+                    true
+                }
+                None => false,
+            }
+        });
+        due_jobs
+    }
+
+    /// Records that a due job has finished. The files passed to a job triggered by a file watch are only marked as processed if the job succeeded, otherwise they are passed to the job again
+    ///
+    /// # Arguments
+    /// * `due_job` - job that has finished
+    /// * `succeeded` - if the job succeeded
+    ///
+    fn finish_due_job(&mut self, due_job: &DueJob, succeeded: bool) {
+        for scheduled_job in self.scheduled_jobs.iter_mut() {
+            if scheduled_job.name != due_job.name {
+                continue;
+            }
+            if let JobSchedule::FileWatch(file_watch_schedule) = &mut scheduled_job.schedule {
+                file_watch_schedule.files_processed(&due_job.files, succeeded);
+            }
+        }
+    }
+
+    /// Runs all due jobs until the scheduler is stopped. Errors of a job do not stop the scheduler
+    ///
+    /// # Arguments
+    /// * `job_manager` - job manager running the jobs
+    /// * `job_definitions` - definitions of all jobs (key is the name of the job)
+    /// * `pipeline_definitions` - definitions of all pipelines (key is the name of the pipeline)
+    /// * `stop` - the scheduler returns once this is set to true
+    ///
+    fn run<M: JobManager>(
+        &mut self,
+        job_manager: &mut M,
+        job_definitions: &HashMap<String, JobDefinition>,
+        pipeline_definitions: &HashMap<String, PipelineDefinition>,
+        stop: &AtomicBool,
+    ) {
+        while !stop.load(Ordering::Relaxed) {
+            let now = OffsetDateTime::now_utc();
+            for due_job in self.due_jobs(now) {
+                let job_name = &due_job.name;
+                let job_definition = match job_definitions.get(job_name) {
+                    Some(job_definition) => job_definition,
+                    None => continue,
+                };
+                log::info!("Starting scheduled job {}", job_name);
+                let job_result = if due_job.files.is_empty() {
+                    job_manager.run_job(pipeline_definitions, job_definition)
+                } else {
+                    match paths_to_arrow(&due_job.files) {
+                        Ok(serialized_input) => job_manager.run_job_with_input(
+                            pipeline_definitions,
+                            job_definition,
+                            serialized_input,
+                        ),
+                        Err(error) => Err(JobRunError::JobSerializationError(error)),
+                    }
+                };
+                match job_result {
+                    Ok(job_result) => {
+                        log::info!("Job {} finished. Job id: {}", job_name, job_result.id);
+                        self.finish_due_job(&due_job, true);
+                    }
+                    Err(error) => {
+                        log::error!("Job {} failed: {:#?}", job_name, error);
+                        self.finish_due_job(&due_job, false);
+                    }
+                }
+            }
+            let sleep_time = match self.next_run() {
+                Some(next_run) => {
+                    let remaining = next_run - OffsetDateTime::now_utc();
+                    if remaining.is_negative() {
+                        std::time::Duration::ZERO
+                    } else {
+                        MAX_SLEEP.min(remaining.unsigned_abs())
+                    }
+                }
+                None => MAX_SLEEP,
+            };
+            thread::sleep(sleep_time);
+        }
+    }
+}
+
+impl JobSchedule {
+    /// Returns the first time after the given time when the job should be started
+    ///
+    /// # Arguments
+    /// * `time` - time after which the job should be started
+    ///
+    pub fn next_run_after(&self, time: OffsetDateTime) -> Option<OffsetDateTime> {
+        match self {
+            JobSchedule::Cron(cron_schedule) => cron_schedule.next_run_after(time),
+            JobSchedule::Interval(interval) => Some(time + *interval),
+            JobSchedule::FileWatch(_file_watch_schedule) => Some(time + FILE_WATCH_POLL_INTERVAL),
+        }
+    }
+}
+
+impl FileWatchSchedule {
+    /// Creates a new watch of a directory. Files that already exist in the directory are not reported as new files
+    ///
+    /// # Arguments
+    /// * `path` - directory to watch
+    ///
+    pub fn new(path: &str) -> Result<FileWatchSchedule, JobValidationError> {
+        let path = PathBuf::from(path);
+        if !path.is_dir() {
+            return Err(JobValidationError::InvalidTrigger(
+                GeneralError::ErrorMessage(format!(
+                    "Watched directory \"{}\" does not exist",
+                    path.display()
+                )),
+            ));
+        }
+        let known_files: HashSet<PathBuf> = list_files(&path)
+            .into_iter()
+            .map(|(file, _size, _modified)| file)
+            .collect();
+        Ok(FileWatchSchedule {
+            path: path,
+            known_files: known_files,
+            pending_files: HashMap::new(),
+            processing_files: HashSet::new(),
+        })
+    }
+
+    /// Returns all files that appeared in the watched directory since the last check and are completely written,
+    /// ie their size and modification time did not change since the last check
+    /// The files are not reported again until the job processing them has finished (see files_processed)
+    pub fn new_files(&mut self) -> Vec<String> {
+        let mut new_files: Vec<String> = Vec::new();
+        let mut pending_files: HashMap<PathBuf, (u64, Option<std::time::SystemTime>)> =
+            HashMap::new();
+        for (file, size, modified) in list_files(&self.path) {
+            if self.known_files.contains(&file) || self.processing_files.contains(&file) {
+                continue;
+            }
+            match self.pending_files.get(&file) {
+                Some(pending_file) if *pending_file == (size, modified) => {
+                    new_files.push(file.display().to_string());
+                    self.processing_files.insert(file);
+                }
+                _ => {
+                    pending_files.insert(file, (size, modified));
+                }
+            };
+        }
+        self.pending_files = pending_files;
+        new_files.sort();
+        new_files
+    }
+
+    /// Records that the job processing files has finished
+    /// Files of a succeeded job are not reported anymore. Files of a failed job are reported again once they are unchanged for another check
+    ///
+    /// # Arguments
+    /// * `files` - files passed to the job
+    /// * `succeeded` - if the job succeeded
+    ///
+    pub fn files_processed(&mut self, files: &[String], succeeded: bool) {
+        for file in files {
+            let file = PathBuf::from(file);
+            self.processing_files.remove(&file);
+            if succeeded {
+                self.known_files.insert(file);
+            }
+        }
+    }
+}
+
+/// Lists all files in a directory with their size and modification time. Errors are ignored, the directory is checked again later
+fn list_files(path: &Path) -> Vec<(PathBuf, u64, Option<std::time::SystemTime>)> {
+    let entries = match std::fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_err) => return Vec::new(),
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| match entry.metadata() {
+            Ok(metadata) if metadata.is_file() => {
+                Some((entry.path(), metadata.len(), metadata.modified().ok()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Converts file paths into Arrow IPC format to provide them as input to a job
+/// # Arguments
+/// * `paths` - paths of files
+/// returns an Arrow IPC stream with one record batch containing the column "path"
+pub fn paths_to_arrow(paths: &[String]) -> Result<Vec<u8>, GeneralError> {
+    let schema = Arc::new(Schema::new(vec![Field::new("path", DataType::Utf8, false)]));
+    let batch = match RecordBatch::try_new(
+        schema.clone(),
+        vec![Arc::new(StringArray::from(paths.to_vec()))],
+    ) {
+        Ok(batch) => batch,
+        Err(err) => {
+            return Err(GeneralError::ErrorMessage(format!(
+                "Cannot create record batch for file paths: {}",
+                err
+            )))
+        }
+    };
+    batches_to_arrow(&schema, &[batch])
+}
+
+impl CronSchedule {
+    /// Parses a cron expression with five fields (minute hour day-of-month month day-of-week)
+    ///
+    /// # Arguments
+    /// * `expression` - cron expression
+    ///
+    pub fn parse(expression: &str) -> Result<CronSchedule, JobValidationError> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(JobValidationError::InvalidTrigger(
+                GeneralError::ErrorMessage(format!(
+                    "Cron expression \"{}\" must have five fields (minute hour day-of-month month day-of-week)",
+                    expression
+                )),
+            ));
+        }
+        let mut days_of_week = parse_cron_field(fields[4], 0, 7)?;
+        // 7 and 0 are both Sunday
+        if days_of_week.remove(&7) {
+            days_of_week.insert(0);
+        }
+        Ok(CronSchedule {
+            minutes: parse_cron_field(fields[0], 0, 59)?,
+            hours: parse_cron_field(fields[1], 0, 23)?,
+            days_of_month: parse_cron_field(fields[2], 1, 31)?,
+            months: parse_cron_field(fields[3], 1, 12)?,
+            days_of_week: days_of_week,
+            days_of_month_restricted: !fields[2].starts_with('*'),
+            days_of_week_restricted: !fields[4].starts_with('*'),
+        })
+    }
+
+    /// Returns the first time (with a precision of minutes) after the given time that matches the cron expression
+    ///
+    /// # Arguments
+    /// * `time` - time after which the job should be started
+    ///
+    pub fn next_run_after(&self, time: OffsetDateTime) -> Option<OffsetDateTime> {
+        let time = time.to_offset(time::UtcOffset::UTC);
+        let mut candidate = time.replace_time(Time::from_hms(time.hour(), time.minute(), 0).ok()?)
+            + Duration::minutes(1);
+        let last_candidate = candidate + Duration::days(MAX_CRON_SEARCH_DAYS as i64);
+        while candidate < last_candidate {
+            if !self.matches_day(candidate) {
+                candidate = candidate.replace_time(Time::MIDNIGHT) + Duration::days(1);
+            } else if !self.hours.contains(&candidate.hour()) {
+                candidate = candidate.replace_time(Time::from_hms(candidate.hour(), 0, 0).ok()?)
+                    + Duration::hours(1);
+            } else if !self.minutes.contains(&candidate.minute()) {
+                candidate += Duration::minutes(1);
+            } else {
+                return Some(candidate);
+            }
+        }
+        None
+    }
+
+    /// Checks if the day of the given time matches the cron expression
+    /// If day-of-month and day-of-week are both restricted then it is sufficient if one of them matches
+    fn matches_day(&self, time: OffsetDateTime) -> bool {
+        if !self.months.contains(&(time.month() as u8)) {
+            return false;
+        }
+        let day_of_month_matches = self.days_of_month.contains(&time.day());
+        let day_of_week_matches = self
+            .days_of_week
+            .contains(&time.weekday().number_days_from_sunday());
+        match (self.days_of_month_restricted, self.days_of_week_restricted) {
+            (true, true) => day_of_month_matches || day_of_week_matches,
+            (true, false) => day_of_month_matches,
+            (false, true) => day_of_week_matches,
+            (false, false) => true,
+        }
+    }
+}
+
+/// Parses a single field of a cron expression
+/// # Arguments
+/// * `field` - field of the cron expression
+/// * `min` - minimum allowed value
+/// * `max` - maximum allowed value
+/// returns all values matching the field
+fn parse_cron_field(field: &str, min: u8, max: u8) -> Result<BTreeSet<u8>, JobValidationError> {
+    let invalid_field = || {
+        JobValidationError::InvalidTrigger(GeneralError::ErrorMessage(format!(
+            "Invalid cron field \"{}\". Allowed values are {}-{}",
+            field, min, max
+        )))
+    };
+    let mut values: BTreeSet<u8> = BTreeSet::new();
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => match step.parse::<u8>() {
+                Ok(step) if step > 0 => (range, step),
+                _ => return Err(invalid_field()),
+            },
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else {
+            match range.split_once('-') {
+                Some((start, end)) => match (start.parse::<u8>(), end.parse::<u8>()) {
+                    (Ok(start), Ok(end)) => (start, end),
+                    _ => return Err(invalid_field()),
+                },
+                None => match range.parse::<u8>() {
+                    // a single value with a step (e.g. 5/10) means from the value until the maximum
+                    Ok(start) if part.contains('/') => (start, max),
+                    Ok(start) => (start, start),
+                    Err(_err) => return Err(invalid_field()),
+                },
+            }
+        };
+        if start < min || end > max || start > end {
+            return Err(invalid_field());
+        }
+        values.extend((start..=end).step_by(step as usize));
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use time::macros::datetime;
+
+    use crate::jobs::interface::{
+        CronSchedule, DueJob, JobDefinition, JobScheduleList, JobScheduler, JobValidationError,
+    };
+
+    #[test]
+    // Test finding the next run of cron expressions
+    fn test_cron_next_run_after() {
+        let now = datetime!(2024-01-31 10:15:30 UTC);
+        let every_fifteen_minutes = CronSchedule::parse("*/15 * * * *").unwrap();
+        assert_eq!(
+            every_fifteen_minutes.next_run_after(now),
+            Some(datetime!(2024-01-31 10:30:00 UTC))
+        );
+        let nightly = CronSchedule::parse("0 2 * * *").unwrap();
+        assert_eq!(
+            nightly.next_run_after(now),
+            Some(datetime!(2024-02-01 02:00:00 UTC))
+        );
+        // 2024-02-05 is a Monday
+        let monday_morning = CronSchedule::parse("30 8 * * 1").unwrap();
+        assert_eq!(
+            monday_morning.next_run_after(now),
+            Some(datetime!(2024-02-05 08:30:00 UTC))
+        );
+        let leap_day = CronSchedule::parse("0 0 29 2 *").unwrap();
+        assert_eq!(
+            leap_day.next_run_after(now),
+            Some(datetime!(2024-02-29 00:00:00 UTC))
+        );
+        let never = CronSchedule::parse("0 0 30 2 *").unwrap();
+        assert_eq!(never.next_run_after(now), None);
+    }
+
+    #[test]
+    // Test that invalid cron expressions are rejected
+    fn test_cron_invalid() {
+        for expression in [
+            "* * * *",
+            "60 * * * *",
+            "* 5-2 * * *",
+            "*/0 * * * *",
+            "a * * * *",
+        ] {
+            match CronSchedule::parse(expression) {
+                Err(JobValidationError::InvalidTrigger(_)) => (),
+                _ => panic!("Invalid cron expression {} accepted", expression),
+            }
+        }
+    }
+
+    #[test]
+    // Test that only triggered jobs are scheduled and due jobs are rescheduled
+    fn test_due_jobs() {
+        const JOBS_STRING: &str = "manual_job:\n pipeline: p\n threads: 1\n trigger: manual\ninterval_job:\n pipeline: p\n threads: 1\n trigger:\n  interval:\n   seconds: 60\ncron_job:\n pipeline: p\n threads: 1\n trigger:\n  cron:\n   expression: \"0 2 * * *\"\n";
+        let job_definitions: HashMap<String, JobDefinition> =
+            serde_yaml::from_str(JOBS_STRING).unwrap();
+        let now = datetime!(2024-01-31 10:15:30 UTC);
+        let mut schedule_list: JobScheduleList = JobScheduler::new(&job_definitions, now).unwrap();
+        assert_eq!(schedule_list.scheduled_jobs.len(), 2);
+        assert_eq!(
+            schedule_list.next_run(),
+            Some(datetime!(2024-01-31 10:16:30 UTC))
+        );
+        assert!(schedule_list.due_jobs(now).is_empty());
+        let later = datetime!(2024-01-31 10:16:30 UTC);
+        assert_eq!(
+            schedule_list.due_jobs(later),
+            vec![DueJob {
+                name: "interval_job".to_string(),
+                files: Vec::new()
+            }]
+        );
+        assert_eq!(
+            schedule_list.next_run(),
+            Some(datetime!(2024-01-31 10:17:30 UTC))
+        );
+    }
+
+    #[test]
+    // Test that a job triggered by a file watch is only due once new files are completely written
+    fn test_due_jobs_file_watch() {
+        let watched_directory =
+            std::env::temp_dir().join(format!("zustdp-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&watched_directory).unwrap();
+        std::fs::write(watched_directory.join("existing.jsonlines"), "{}").unwrap();
+        let jobs_string = format!(
+            "watch_job:\n pipeline: p\n threads: 1\n trigger:\n  file_watch:\n   path: \"{}\"\n",
+            watched_directory.display()
+        );
+        let job_definitions: HashMap<String, JobDefinition> =
+            serde_yaml::from_str(&jobs_string).unwrap();
+        let mut now = datetime!(2024-01-31 10:15:30 UTC);
+        let mut schedule_list: JobScheduleList = JobScheduler::new(&job_definitions, now).unwrap();
+        now += time::Duration::seconds(1);
+        assert!(schedule_list.due_jobs(now).is_empty());
+        let new_file = watched_directory.join("new.jsonlines");
+        std::fs::write(&new_file, "{}").unwrap();
+        // the first check only notices the new file
+        now += time::Duration::seconds(1);
+        assert!(schedule_list.due_jobs(now).is_empty());
+        now += time::Duration::seconds(1);
+        assert_eq!(
+            schedule_list.due_jobs(now),
+            vec![DueJob {
+                name: "watch_job".to_string(),
+                files: vec![new_file.display().to_string()]
+            }]
+        );
+        // files are only reported once
+        now += time::Duration::seconds(1);
+        assert!(schedule_list.due_jobs(now).is_empty());
+        now += time::Duration::seconds(1);
+        assert!(schedule_list.due_jobs(now).is_empty());
+        std::fs::remove_dir_all(&watched_directory).unwrap();
+    }
+
+    #[test]
+    // Test that the files of a failed job triggered by a file watch are passed to the job again
+    fn test_due_jobs_file_watch_failed() {
+        let watched_directory =
+            std::env::temp_dir().join(format!("zustdp-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&watched_directory).unwrap();
+        let jobs_string = format!(
+            "watch_job:\n pipeline: p\n threads: 1\n trigger:\n  file_watch:\n   path: \"{}\"\n",
+            watched_directory.display()
+        );
+        let job_definitions: HashMap<String, JobDefinition> =
+            serde_yaml::from_str(&jobs_string).unwrap();
+        let mut now = datetime!(2024-01-31 10:15:30 UTC);
+        let mut schedule_list: JobScheduleList = JobScheduler::new(&job_definitions, now).unwrap();
+        let new_file = watched_directory.join("new.jsonlines");
+        std::fs::write(&new_file, "{}").unwrap();
+        let mut due_jobs: Vec<DueJob> = Vec::new();
+        while due_jobs.is_empty() {
+            now += time::Duration::seconds(1);
+            due_jobs = schedule_list.due_jobs(now);
+        }
+        assert_eq!(due_jobs[0].files, vec![new_file.display().to_string()]);
+        // the files are passed to the job again after it failed
+        schedule_list.finish_due_job(&due_jobs[0], false);
+        now += time::Duration::seconds(1);
+        assert!(schedule_list.due_jobs(now).is_empty());
+        now += time::Duration::seconds(1);
+        let retried_due_jobs = schedule_list.due_jobs(now);
+        assert_eq!(retried_due_jobs, due_jobs);
+        // the files are not passed to the job anymore once it succeeded
+        schedule_list.finish_due_job(&retried_due_jobs[0], true);
+        for _check in 0..3 {
+            now += time::Duration::seconds(1);
+            assert!(schedule_list.due_jobs(now).is_empty());
+        }
+        std::fs::remove_dir_all(&watched_directory).unwrap();
+    }
+}
@@ -3,8 +3,10 @@
 pub mod apps;
 pub mod error;
 pub mod jobs;
+pub mod logger;
 pub mod modules;
 pub mod pipeline;
+pub mod state;
 
 pub fn version() -> String {
     return env!("CARGO_PKG_VERSION").to_string();
@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+
+use log::LevelFilter;
+use serde::{Deserialize, Serialize};
+
+use crate::error::error::GeneralError;
+
+/// Definition of the logging of an app
+#[derive(Deserialize, Serialize, Clone)]
+pub struct LoggingDefinition {
+    /// minimum level of the messages that are logged: off, error, warn, info, debug or trace
+    pub level: LevelFilter,
+    /// where the messages are written to
+    #[serde(with = "serde_yaml::with::singleton_map_recursive")]
+    pub appenders: Vec<AppenderDefinition>,
+    /// minimum level of the messages of a target overriding the level (key is the target, e.g. zustdpipe::jobs or zustdp_module::<module file name>)
+    #[serde(default)]
+    pub loggers: HashMap<String, LevelFilter>,
+}
+
+/// Definition of an appender writing log messages
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum AppenderDefinition {
+    /// writes messages to stdout in the given pattern. If no pattern is defined then a default pattern is used
+    Console {
+        #[serde(default)]
+        pattern: Option<String>,
+    },
+    /// writes messages in the given pattern to a file. The file is rolled once it exceeds the given size and only the given number of rolled files is kept
+    RollingFile {
+        path: String,
+        max_size_bytes: u64,
+        max_files: u32,
+        #[serde(default)]
+        pattern: Option<String>,
+    },
+    /// writes each message as a JSON object on a line to a file or to stdout if no path is defined
+    Json {
+        #[serde(default)]
+        path: Option<String>,
+    },
+}
+
+#[derive(Debug)]
+pub enum LoggerError {
+    /// the logging definition cannot be converted into a configuration of the logger
+    LoggerConfigurationError(GeneralError),
+    /// the logger has already been initialized
+    LoggerInitError(GeneralError),
+}
+
+/// Manages the logger of ZuStDPipe
+pub trait LoggerManager {
+    fn init(logging_definition: &LoggingDefinition) -> Result<Self, LoggerError>
+    where
+        Self: Sized;
+}
@@ -0,0 +1,176 @@
+use ::log4rs::append::console::ConsoleAppender;
+use ::log4rs::append::file::FileAppender;
+use ::log4rs::append::rolling_file::policy::compound::roll::fixed_window::FixedWindowRoller;
+use ::log4rs::append::rolling_file::policy::compound::trigger::size::SizeTrigger;
+use ::log4rs::append::rolling_file::policy::compound::CompoundPolicy;
+use ::log4rs::append::rolling_file::RollingFileAppender;
+use ::log4rs::append::Append;
+use ::log4rs::config::{Appender, Config, Logger, Root};
+use ::log4rs::encode::json::JsonEncoder;
+use ::log4rs::encode::pattern::PatternEncoder;
+
+use crate::error::error::GeneralError;
+
+use super::interface::{AppenderDefinition, LoggerError, LoggerManager, LoggingDefinition};
+
+/// Pattern of messages if an appender does not define one: time, level, target and message
+pub const DEFAULT_PATTERN: &str = "{d(%Y-%m-%dT%H:%M:%S%.3f%:z)} {l} {t} - {m}{n}";
+
+/// Logger based on log4rs. It is installed as the global logger of the process
+pub struct Log4rsLogger;
+
+impl LoggerManager for Log4rsLogger {
+    /// Initializes the logger. It can only be initialized once per process
+    ///
+    /// # Arguments
+    /// * `logging_definition` - definition of the logging of the app
+    ///
+    fn init(logging_definition: &LoggingDefinition) -> Result<Log4rsLogger, LoggerError> {
+        let config = build_config(logging_definition)?;
+        match ::log4rs::init_config(config) {
+            Ok(_handle) => Ok(Log4rsLogger),
+            Err(err) => Err(LoggerError::LoggerInitError(GeneralError::ErrorMessage(
+                format!("Cannot initialize logger: {}", err),
+            ))),
+        }
+    }
+}
+
+/// Converts a logging definition into a configuration of log4rs
+/// # Arguments
+/// * `logging_definition` - definition of the logging of the app
+fn build_config(logging_definition: &LoggingDefinition) -> Result<Config, LoggerError> {
+    let mut config_builder = Config::builder();
+    let mut appender_names: Vec<String> = Vec::new();
+    for (index, appender_definition) in logging_definition.appenders.iter().enumerate() {
+        let appender_name = format!("appender_{}", index);
+        let appender = build_appender(appender_definition)?;
+        config_builder =
+            config_builder.appender(Appender::builder().build(&appender_name, appender));
+        appender_names.push(appender_name);
+    }
+    for (target, level) in &logging_definition.loggers {
+        config_builder = config_builder.logger(Logger::builder().build(target, *level));
+    }
+    let root = Root::builder()
+        .appenders(appender_names)
+        .build(logging_definition.level);
+    match config_builder.build(root) {
+        Ok(config) => Ok(config),
+        Err(err) => Err(configuration_error(format!(
+            "Invalid logging definition: {}",
+            err
+        ))),
+    }
+}
+
+/// Creates an appender of log4rs
+/// # Arguments
+/// * `appender_definition` - definition of the appender
+fn build_appender(
+    appender_definition: &AppenderDefinition,
+) -> Result<Box<dyn Append>, LoggerError> {
+    match appender_definition {
+        AppenderDefinition::Console { pattern } => Ok(Box::new(
+            ConsoleAppender::builder()
+                .encoder(Box::new(pattern_encoder(pattern)))
+                .build(),
+        )),
+        AppenderDefinition::RollingFile {
+            path,
+            max_size_bytes,
+            max_files,
+            pattern,
+        } => {
+            // rolled files are numbered, e.g. zustdp.log.0 is the most recent one
+            let roller =
+                match FixedWindowRoller::builder().build(&format!("{}.{{}}", path), *max_files) {
+                    Ok(roller) => roller,
+                    Err(err) => {
+                        return Err(configuration_error(format!(
+                            "Invalid rolling file appender {}: {}",
+                            path, err
+                        )))
+                    }
+                };
+            let policy = CompoundPolicy::new(
+                Box::new(SizeTrigger::new(*max_size_bytes)),
+                Box::new(roller),
+            );
+            match RollingFileAppender::builder()
+                .encoder(Box::new(pattern_encoder(pattern)))
+                .build(path, Box::new(policy))
+            {
+                Ok(appender) => Ok(Box::new(appender)),
+                Err(err) => Err(configuration_error(format!(
+                    "Cannot open log file {}: {}",
+                    path, err
+                ))),
+            }
+        }
+        AppenderDefinition::Json { path: Some(path) } => {
+            match FileAppender::builder()
+                .encoder(Box::new(JsonEncoder::new()))
+                .build(path)
+            {
+                Ok(appender) => Ok(Box::new(appender)),
+                Err(err) => Err(configuration_error(format!(
+                    "Cannot open log file {}: {}",
+                    path, err
+                ))),
+            }
+        }
+        AppenderDefinition::Json { path: None } => Ok(Box::new(
+            ConsoleAppender::builder()
+                .encoder(Box::new(JsonEncoder::new()))
+                .build(),
+        )),
+    }
+}
+
+/// Creates an encoder writing messages in the given pattern or the default pattern
+/// # Arguments
+/// * `pattern` - pattern of the messages
+fn pattern_encoder(pattern: &Option<String>) -> PatternEncoder {
+    match pattern {
+        Some(pattern) => PatternEncoder::new(pattern),
+        None => PatternEncoder::new(DEFAULT_PATTERN),
+    }
+}
+
+fn configuration_error(message: String) -> LoggerError {
+    LoggerError::LoggerConfigurationError(GeneralError::ErrorMessage(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logger::interface::LoggingDefinition;
+
+    #[test]
+    // Test that console, rolling file and JSON appenders are configured from the logging section of an app
+    fn test_build_config() {
+        let path = std::env::temp_dir().join(format!("zustdp-logging-{}", uuid::Uuid::new_v4()));
+        let logging_string = format!(
+            "level: info\nappenders:\n - console:\n    pattern: \"{{l}} {{m}}{{n}}\"\n - rolling_file:\n    path: {}\n    max_size_bytes: 1048576\n    max_files: 3\n - json:\n    path: {}\nloggers:\n  zustdp_module::echo.wasm: debug\n",
+            path.join("zustdp.log").display(),
+            path.join("zustdp.json").display()
+        );
+        let logging_definition: LoggingDefinition = serde_yaml::from_str(&logging_string).unwrap();
+        let config = super::build_config(&logging_definition).unwrap();
+        assert_eq!(config.appenders().len(), 3);
+        assert_eq!(config.root().appenders().len(), 3);
+        assert_eq!(config.root().level(), log::LevelFilter::Info);
+        assert_eq!(config.loggers()[0].name(), "zustdp_module::echo.wasm");
+        assert_eq!(config.loggers()[0].level(), log::LevelFilter::Debug);
+        // the files are created when the appenders are configured
+        assert!(path.join("zustdp.log").exists());
+        assert!(path.join("zustdp.json").exists());
+        std::fs::remove_dir_all(path).unwrap();
+        // appenders writing to a directory that cannot be created are rejected
+        let invalid_logging_definition: LoggingDefinition = serde_yaml::from_str(
+            "level: info\nappenders:\n - json:\n    path: /dev/null/zustdp.json\n",
+        )
+        .unwrap();
+        assert!(super::build_config(&invalid_logging_definition).is_err());
+    }
+}
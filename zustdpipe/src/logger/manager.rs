@@ -0,0 +1,31 @@
+use std::collections::HashMap;
+
+use log::LevelFilter;
+
+use super::interface::{AppenderDefinition, LoggerError, LoggerManager, LoggingDefinition};
+use super::log4rs::Log4rsLogger;
+
+impl Default for LoggingDefinition {
+    /// Logs messages of level info and above to stdout
+    fn default() -> LoggingDefinition {
+        LoggingDefinition {
+            level: LevelFilter::Info,
+            appenders: vec![AppenderDefinition::Console { pattern: None }],
+            loggers: HashMap::new(),
+        }
+    }
+}
+
+/// Initializes the logger of ZuStDPipe
+///
+/// # Arguments
+/// * `logging_definition` - logging section of the app. If it is not defined then messages of level info and above are logged to stdout
+///
+pub fn init_logger(
+    logging_definition: Option<&LoggingDefinition>,
+) -> Result<Log4rsLogger, LoggerError> {
+    match logging_definition {
+        Some(logging_definition) => LoggerManager::init(logging_definition),
+        None => LoggerManager::init(&LoggingDefinition::default()),
+    }
+}
@@ -0,0 +1,5 @@
+//! The logger routes all messages of ZuStDPipe and of the modules it runs to the appenders configured in the logging section of an app
+
+pub mod interface;
+pub mod log4rs;
+pub mod manager;
@@ -1,8 +1,11 @@
+use arrow::datatypes::DataType;
+
 use crate::error::error::GeneralError;
 use crate::pipeline::interface::ProcessDefinition;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-use super::library::interface::{Library, LibraryDefinitionError};
+use super::library::interface::{ExecutionLimits, Library, LibraryDefinitionError, WasiDefinition};
 use super::library::wasm::WASMLibraryManager;
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -17,6 +20,47 @@ pub enum ModuleDefinitionError {
     ModuleNotFound(GeneralError),
     ModuleTypeNotFound(GeneralError),
     ModuleCannotBeInstantiated(LibraryDefinitionError),
+    ModuleManifestInvalid(GeneralError),
+    /// the parameters of a process do not match the parameters declared in the manifest of its module. Contains all violations
+    ProcessParametersInvalid(Vec<GeneralError>),
+}
+
+/// Manifest shipped with a module (as custom section "zustdp_manifest" of a WASM module or as sidecar file <module>.manifest.yml) describing the module
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ModuleManifest {
+    pub name: String,
+    pub version: String,
+    /// fields the module expects in its input
+    #[serde(default)]
+    pub input_schema: Vec<ModuleManifestField>,
+    /// fields the module returns in its output
+    #[serde(default)]
+    pub output_schema: Vec<ModuleManifestField>,
+    /// parameters accepted by the module
+    #[serde(default)]
+    pub parameters: Vec<ModuleManifestParameter>,
+}
+
+/// Field of an Arrow schema declared in a module manifest
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ModuleManifestField {
+    pub name: String,
+    /// Arrow data type, e.g. Utf8 or Int64. Unknown data types are rejected when the manifest is read
+    #[serde(with = "super::manifest::arrow_data_type")]
+    pub data_type: DataType,
+    #[serde(default)]
+    pub nullable: bool,
+}
+
+/// Parameter declared in a module manifest
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ModuleManifestParameter {
+    pub name: String,
+    /// if the parameter must be defined by each process using the module
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub description: Option<String>,
 }
 
 /// Deifinition of a single pipeline
@@ -28,11 +72,34 @@ pub struct ModulesDefinition {
 #[derive(Deserialize, Serialize)]
 pub struct WasmModulesDefinition {
     pub module_path_base: Vec<String>,
+    /// settings of individual modules (key is the name of the module)
+    #[serde(default)]
+    pub modules: HashMap<String, WasmModuleDefinition>,
+}
+
+/// Settings of a single WASM module. They apply to all processes using the module
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct WasmModuleDefinition {
+    /// maximum fuel (roughly the number of executed instructions) a single call of the module may consume. Can be overridden by a process
+    #[serde(default)]
+    pub fuel: Option<u64>,
+    /// maximum number of pages (64 KiB each) of linear memory an instance of the module may use
+    #[serde(default)]
+    pub max_memory_pages: Option<u64>,
+    /// maximum number of elements of a table an instance of the module may use
+    #[serde(default)]
+    pub max_table_elements: Option<u32>,
+    /// WASI context of the instances of the module, e.g. the directories of the host it can access
+    #[serde(default)]
+    pub wasi: WasiDefinition,
 }
 
 pub struct ModuleManagerList {
     pub wasm_library_manager: Option<WASMLibraryManager>,
     pub(crate) module_paths: Vec<String>,
+    pub(crate) wasm_module_definitions: HashMap<String, WasmModuleDefinition>,
+    /// manifests of all modules read so far (key is the path to the module). None if a module does not have a manifest
+    pub(crate) manifests: HashMap<String, Option<ModuleManifest>>,
 }
 
 /// Creates a new module manager
@@ -43,5 +110,14 @@ pub trait ModuleManager {
     fn get_module_instance(
         &mut self,
         process_definition: &ProcessDefinition,
+        execution_limits: &ExecutionLimits,
     ) -> Result<Box<dyn Library>, ModuleDefinitionError>;
+    fn validate_module(
+        &mut self,
+        process_definition: &ProcessDefinition,
+    ) -> Result<(), ModuleDefinitionError>;
+    fn get_module_manifest(
+        &mut self,
+        process_definition: &ProcessDefinition,
+    ) -> Result<Option<ModuleManifest>, ModuleDefinitionError>;
 }
@@ -1,35 +1,165 @@
 use crate::error::error::GeneralError;
+use crate::state::interface::StateHandle;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Instant;
 
 /// Definition of a library
 #[derive(Deserialize, Serialize)]
 pub struct LibraryDefinition {
     pub path: String,
+    /// maximum number of pages (64 KiB each) of memory an instance may use
+    #[serde(default)]
+    pub max_memory_pages: Option<u64>,
+    /// maximum number of elements of a table an instance may use
+    #[serde(default)]
+    pub max_table_elements: Option<u32>,
+    /// WASI context of an instance
+    #[serde(default)]
+    pub wasi: WasiDefinition,
+}
+
+/// WASI context of the instances of a module. By default a module has no access to the file system, environment variables, arguments or stdin, but writes to stdout and stderr
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct WasiDefinition {
+    /// directories of the host the module can access
+    #[serde(default)]
+    pub preopened_dirs: Vec<WasiPreopenedDirDefinition>,
+    /// environment variables of the module (key is the name of the variable)
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// arguments of the module
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// if the module writes to stdout and stderr of ZuStDPipe
+    #[serde(default = "default_inherit_stdio")]
+    pub inherit_stdio: bool,
+    /// if the module reads from stdin of ZuStDPipe
+    #[serde(default)]
+    pub inherit_stdin: bool,
+}
+
+impl Default for WasiDefinition {
+    fn default() -> WasiDefinition {
+        WasiDefinition {
+            preopened_dirs: Vec::new(),
+            env: HashMap::new(),
+            args: Vec::new(),
+            inherit_stdio: default_inherit_stdio(),
+            inherit_stdin: false,
+        }
+    }
+}
+
+fn default_inherit_stdio() -> bool {
+    true
+}
+
+/// Directory of the host a module can access
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct WasiPreopenedDirDefinition {
+    /// path of the directory on the host, e.g. data/import
+    pub host_path: String,
+    /// path under which the module sees the directory, e.g. /import
+    pub guest_path: String,
+    /// if the module may only read or also modify the directory and its files. Read-only if not defined
+    #[serde(default)]
+    pub access: WasiDirAccess,
+}
+
+/// Access of a module to a directory of the host
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WasiDirAccess {
+    #[default]
+    ReadOnly,
+    ReadWrite,
 }
 
 /// Error in case of an issue with a Library Definition
 #[derive(Debug, Clone)]
 pub enum LibraryDefinitionError {
     ModuleSpecificError(GeneralError),
+    /// the start function of the module trapped
+    Trap(GeneralError),
+    /// the start function of the module has been interrupted because it has been cancelled or it exceeded its timeout
+    ExecutionInterrupted(GeneralError),
+    /// the module does not implement the interface expected by the host. Contains all violations
+    AbiViolation(Vec<GeneralError>),
+    /// the module implements a version of the interface not supported by the host
+    UnsupportedAbiVersion(GeneralError),
 }
 
 /// Error in case of an issue with a Library Instance
 #[derive(Debug, Clone)]
 pub enum LibraryInstanceError {
     InstantiationError(GeneralError),
+    /// a function or memory required by the host is not exported by the module
+    MissingExport(GeneralError),
+    /// an exported function does not have the signature required by the host
+    SignatureMismatch(GeneralError),
+    /// the module trapped (e.g. unreachable, division by zero). Contains the WASM backtrace if available
+    Trap(GeneralError),
+    /// the module returned a pointer or length outside of its memory
+    OutOfBounds(GeneralError),
+    /// execution has been interrupted because it has been cancelled or it exceeded its timeout
+    ExecutionInterrupted(GeneralError),
+    /// execution has been stopped because it consumed all of its fuel
+    FuelExhausted(GeneralError),
+    /// execution has been stopped because it tried to use more memory or table elements than allowed
+    ResourceLimitExceeded(GeneralError),
+    /// the module reported that processing failed. Contains the error reported by the module
+    ProcessFailed(GeneralError),
+}
+
+/// Limits for executing functions of a library instance
+#[derive(Clone, Default)]
+pub struct ExecutionLimits {
+    /// a running function is interrupted once this point in time is reached
+    pub deadline: Option<Instant>,
+    /// a running function is interrupted once this is set
+    pub cancel_requested: Option<Arc<AtomicBool>>,
+    /// maximum fuel a single function call may consume. If not set then the fuel is not limited
+    pub fuel: Option<u64>,
 }
 
-/// Represents a dynamically loaded library
-pub trait Library {
+/// Represents a dynamically loaded library. An instance can be moved to another thread to process data in parallel
+pub trait Library: Send {
     fn exec_func(
         &mut self,
         serialized_metadata: Vec<u8>,
         serialized_data: Vec<u8>,
     ) -> Result<Vec<u8>, LibraryInstanceError>;
+    fn init(&mut self, serialized_metadata: Vec<u8>) -> Result<(), LibraryInstanceError>;
+    fn finish(&mut self) -> Result<Option<Vec<u8>>, LibraryInstanceError>;
+    fn has_lifecycle_hooks(&self) -> bool;
+    fn supports_streaming(&self) -> bool;
+    fn exec_stream(
+        &mut self,
+        serialized_metadata: Vec<u8>,
+        serialized_batches: &mut dyn Iterator<Item = Vec<u8>>,
+        output: &mut dyn FnMut(Vec<u8>),
+    ) -> Result<(), LibraryInstanceError>;
+    fn set_execution_limits(&mut self, execution_limits: ExecutionLimits);
+    fn set_state(&mut self, state: StateHandle);
+    fn set_job_id(&mut self, job_id: String);
+    fn fuel_consumed(&self) -> u64;
 }
 
 /// Manage dynamically loaded libraries
 pub trait LibraryManager<T: Library> {
-    fn new() -> Self;
-    fn get_instance(&mut self, path: &String) -> Result<Box<T>, LibraryDefinitionError>;
+    fn new() -> Result<Self, LibraryDefinitionError>
+    where
+        Self: Sized;
+    fn validate(
+        &mut self,
+        library_definition: &LibraryDefinition,
+    ) -> Result<(), LibraryDefinitionError>;
+    fn get_instance(
+        &mut self,
+        library_definition: &LibraryDefinition,
+        execution_limits: &ExecutionLimits,
+    ) -> Result<Box<T>, LibraryDefinitionError>;
 }
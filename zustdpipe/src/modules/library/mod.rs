@@ -5,3 +5,5 @@ pub mod interface;
 
 // Specific module loader: WASM
 pub mod wasm;
+// Interface (ABI) between the host and WASM modules
+pub mod wasm_abi;
@@ -1,19 +1,148 @@
 use super::interface;
+use super::wasm_abi::{
+    has_lifecycle_hooks, is_memory64, supports_streaming, validate_abi_version,
+    validate_module_abi, validate_module_imports, ABI_VERSION_EXPORT, ALLOCATE_EXPORT,
+    DEALLOCATE_EXPORT, FINISH_EXPORT, HOST_MODULE, INIT_EXPORT, LAST_ERROR_EXPORT, LOG_IMPORT,
+    MEMORY_EXPORT, RAW_PROCESS_ENTRY_EXPORT, STATE_DELETE_IMPORT, STATE_GET_IMPORT,
+    STATE_PUT_IMPORT, STATUS_ERROR, STATUS_OK, STREAM_END_EXPORT, STREAM_PULL_EXPORT,
+    STREAM_PUSH_EXPORT, STREAM_START_EXPORT, STREAM_STATUS_OUTPUT_PENDING,
+};
 use crate::error::error::GeneralError;
+use crate::state::interface::{StateHandle, StateManager};
 
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
-use wasmtime::{AsContextMut, Engine, Instance, Linker, Module, Store};
-use wasmtime_wasi::{sync::WasiCtxBuilder, WasiCtx};
+use wasmtime::{
+    Caller, Config, Engine, Instance, Linker, Memory, Module, ResourceLimiter, Store,
+    StoreContextMut, Trap, TypedFunc, UpdateDeadline, WasmBacktrace, WasmParams, WasmResults,
+};
+use wasmtime_wasi::preview2::preview1::{WasiPreview1Adapter, WasiPreview1View};
+use wasmtime_wasi::preview2::{
+    DirPerms, FilePerms, ResourceTable, WasiCtx, WasiCtxBuilder, WasiView,
+};
+use wasmtime_wasi::sync::{ambient_authority, Dir};
+
+/// Interval in which running modules check if they need to be interrupted
+const EPOCH_TICK: Duration = Duration::from_millis(10);
+/// Size of a page of WASM linear memory in bytes
+const WASM_PAGE_SIZE: usize = 65536;
+/// Fuel a module may consume to be instantiated and return the version of its ABI when it is validated
+const ABI_VERSION_FUEL: u64 = 10_000_000;
 
 struct WASMState {
     wasi: WasiCtx,
+    /// resources (e.g. open files) of the WASI context
+    wasi_table: ResourceTable,
+    /// state of the WASI preview 1 functions imported by modules
+    wasi_adapter: WasiPreview1Adapter,
+    execution_limits: interface::ExecutionLimits,
+    /// reason why the running function has been interrupted
+    interrupted: Option<String>,
+    resource_limiter: WASMResourceLimiter,
+    /// state of the step the instance is executing. Modules access it using the functions provided by the host
+    state: Option<StateHandle>,
+    /// name of the module and id of the job the instance is executing. Messages logged by the module contain them as context
+    module_name: String,
+    job_id: Option<String>,
+}
+
+impl WasiView for WASMState {
+    fn table(&self) -> &ResourceTable {
+        &self.wasi_table
+    }
+
+    fn table_mut(&mut self) -> &mut ResourceTable {
+        &mut self.wasi_table
+    }
+
+    fn ctx(&self) -> &WasiCtx {
+        &self.wasi
+    }
+
+    fn ctx_mut(&mut self) -> &mut WasiCtx {
+        &mut self.wasi
+    }
+}
+
+impl WasiPreview1View for WASMState {
+    fn adapter(&self) -> &WasiPreview1Adapter {
+        &self.wasi_adapter
+    }
+
+    fn adapter_mut(&mut self) -> &mut WasiPreview1Adapter {
+        &mut self.wasi_adapter
+    }
+}
+
+/// Limits the memory and tables a module instance can use
+struct WASMResourceLimiter {
+    max_memory_pages: Option<u64>,
+    max_table_elements: Option<u32>,
+    /// description of the limit the module instance tried to exceed
+    exceeded: Option<String>,
+}
+
+impl ResourceLimiter for WASMResourceLimiter {
+    fn memory_growing(
+        &mut self,
+        _current: usize,
+        desired: usize,
+        _maximum: Option<usize>,
+    ) -> anyhow::Result<bool> {
+        let desired_pages = (desired / WASM_PAGE_SIZE) as u64;
+        match self.max_memory_pages {
+            Some(max_memory_pages) if desired_pages > max_memory_pages => {
+                let reason = format!(
+                    "Module exceeded its memory limit: {} pages requested, but only {} pages allowed",
+                    desired_pages, max_memory_pages
+                );
+                self.exceeded = Some(reason.clone());
+                Err(anyhow::anyhow!(reason))
+            }
+            _ => Ok(true),
+        }
+    }
+
+    fn table_growing(
+        &mut self,
+        _current: u32,
+        desired: u32,
+        _maximum: Option<u32>,
+    ) -> anyhow::Result<bool> {
+        match self.max_table_elements {
+            Some(max_table_elements) if desired > max_table_elements => {
+                let reason = format!(
+                    "Module exceeded its table limit: {} elements requested, but only {} elements allowed",
+                    desired, max_table_elements
+                );
+                self.exceeded = Some(reason.clone());
+                Err(anyhow::anyhow!(reason))
+            }
+            _ => Ok(true),
+        }
+    }
 }
 
 pub struct WASMLibrary {
     path: String,
     instance: Instance,
     store: Store<WASMState>,
+    /// fuel consumed by all calls before the store has been refueled the last time
+    fuel_consumed_before_refuel: u64,
+    /// fuel the store has been refueled with the last time
+    fuel_refueled: u64,
+    /// version of the ABI implemented by the module
+    abi_version: u32,
+    /// if the module uses 64 bit memory (memory64). Pointers and sizes are 64 bit then
+    memory64: bool,
+    /// if the module exports the stream functions
+    streaming: bool,
+    /// if the module exports an init or finish function
+    lifecycle_hooks: bool,
 }
 
 impl interface::Library for WASMLibrary {
@@ -22,266 +151,1274 @@ impl interface::Library for WASMLibrary {
         serialized_metadata: Vec<u8>,
         serialized_data: Vec<u8>,
     ) -> Result<Vec<u8>, interface::LibraryInstanceError> {
+        let memory = self.prepare_call()?;
         // make serialized data available to function
-        // call function
-        let func_def = &self
-            .instance
-            .get_func(&mut self.store, "zustdp_module_wasm_raw_process_entry")
-            .expect(
-                format!(
-                    "`{}` was not an exported function",
-                    "zustdp_module_wasm_raw_process_entry"
-                )
-                .as_str(),
-            );
-        let func_validated = func_def
-            .typed::<(u32, u32, u32, u32), u32>(&self.store)
-            .unwrap();
         // prepare handing Arrow data
-        let serialized_metadata_size = serialized_metadata.len();
-        let serialized_data_size = serialized_data.len();
-
-        // instantiate memory
-        let memory = match self.instance.get_memory(&mut self.store, "memory") {
-            Some(memory) => memory,
-            None => {
-                return Err(interface::LibraryInstanceError::InstantiationError(
-                    GeneralError::ErrorMessage(format!("Cannot instantiate module memory")),
-                ))
-            }
-        };
+        let serialized_metadata_size = serialized_metadata.len() as u64;
+        let serialized_data_size = serialized_data.len() as u64;
         // allocate some memory within the WASM module for metadata
-        let offset_meta_data: u32 = wrapper_wasm_allocate(
-            self.instance,
-            &mut self.store,
-            serialized_metadata_size as u32,
-        )
-        .unwrap() as u32;
-        memory
-            .write(
-                &mut self.store,
-                offset_meta_data.try_into().unwrap(),
-                serialized_metadata.as_slice(),
-            )
-            .unwrap();
+        let offset_meta_data: u64 = self.wrapper_wasm_allocate(serialized_metadata_size)?;
+        self.write_memory(memory, offset_meta_data, serialized_metadata.as_slice())?;
         // allocate some memory within the WASM module for data
-        let offset_data: u32 =
-            wrapper_wasm_allocate(self.instance, &mut self.store, serialized_data_size as u32)
-                .unwrap() as u32;
-        memory
-            .write(
-                &mut self.store,
-                offset_data.try_into().unwrap(),
-                serialized_data.as_slice(),
-            )
-            .unwrap();
+        let offset_data: u64 = self.wrapper_wasm_allocate(serialized_data_size)?;
+        self.write_memory(memory, offset_data, serialized_data.as_slice())?;
         // call function answer
-        let result_offset = match func_validated.call(
-            &mut self.store,
-            (
-                offset_meta_data,
-                serialized_metadata_size as u32,
-                offset_data,
-                serialized_data_size as u32,
-            ),
-        ) {
-            Ok(result) => result,
-            Err(_err) => {
-                return Err(interface::LibraryInstanceError::InstantiationError(
-                    GeneralError::ErrorMessage(format!("Cannot instantiate function")),
-                ))
-            }
-        };
+        let result_offset = self.wrapper_wasm_raw_process_entry(
+            offset_meta_data,
+            serialized_metadata_size,
+            offset_data,
+            serialized_data_size,
+        )?;
         // deallocate shared WASM Module memory
-        let dealloc_metadata_code: i32 = wrapper_wasm_deallocate(
-            self.instance,
-            &mut self.store,
-            offset_meta_data as *const u8,
-        )
-        .unwrap();
+        let dealloc_metadata_code: i32 = self.wrapper_wasm_deallocate(offset_meta_data)?;
         if dealloc_metadata_code != 0 {
-            println!("Error: Could not deallocate shared WASM module memory for meta data");
+            log::warn!("Could not deallocate shared WASM module memory for meta data");
         }
-        let dealloc_data_code: i32 =
-            wrapper_wasm_deallocate(self.instance, &mut self.store, offset_data as *const u8)
-                .unwrap();
+        let dealloc_data_code: i32 = self.wrapper_wasm_deallocate(offset_data)?;
         if dealloc_data_code != 0 {
-            println!("Error: Could not deallocate shared WASM module memory for data");
+            log::warn!("Could not deallocate shared WASM module memory for data");
         }
         if result_offset == 0 {
-            return Err(interface::LibraryInstanceError::InstantiationError(
-                GeneralError::ErrorMessage(format!("Invalid return code.")),
-            ));
-        } else {
-            let mut result_offset_position = result_offset;
-            // read answer from memory: these are two values: offset of the processed data and size of the processed data in Arrow IPC format
-            // read metadata (offset and size of the Arrow IPC data)
-            // note: WebAssembly is by default 32 bit
-            let mut ptr_buffer = [0u8; (u32::BITS / 8) as usize];
-            let mut len_buffer = [0u8; (u32::BITS / 8) as usize];
-            match memory.read(
-                &self.store,
-                result_offset_position.try_into().unwrap(),
-                &mut ptr_buffer,
-            ) {
-                Ok(()) => (),
-                Err(_err) => {
-                    return Err(interface::LibraryInstanceError::InstantiationError(
-                        GeneralError::ErrorMessage(format!(
-                            "Cannot read metadata pointer from module memory."
-                        )),
-                    ))
-                }
+            // the module may report why it failed
+            return match self.wrapper_wasm_last_error(memory)? {
+                Some(message) => Err(interface::LibraryInstanceError::ProcessFailed(
+                    GeneralError::ErrorMessage(message),
+                )),
+                None => Err(interface::LibraryInstanceError::InstantiationError(
+                    GeneralError::ErrorMessage("Invalid return code.".to_string()),
+                )),
             };
-            result_offset_position += (u32::BITS / 8) as u32;
-            match memory.read(
-                &self.store,
-                result_offset_position.try_into().unwrap(),
-                &mut len_buffer,
-            ) {
-                Ok(()) => (),
-                Err(_err) => {
-                    return Err(interface::LibraryInstanceError::InstantiationError(
-                        GeneralError::ErrorMessage(format!(
-                            "Cannot read metadata pointer from module memory."
-                        )),
-                    ))
-                }
+        }
+        // read answer from memory: the result descriptor contains the offset of the processed data and size of the processed data in Arrow IPC format
+        let result_arrow_ipc = self.read_result(memory, result_offset)?;
+        Ok(result_arrow_ipc)
+    }
+
+    /// Initializes the module before it processes the first data, so it can set up its state. Does nothing if the module does not export an init function
+    ///
+    /// # Arguments
+    /// * `serialized_metadata` - metadata (e.g. parameters) in Arrow IPC format
+    ///
+    fn init(
+        &mut self,
+        serialized_metadata: Vec<u8>,
+    ) -> Result<(), interface::LibraryInstanceError> {
+        if self
+            .instance
+            .get_func(&mut self.store, INIT_EXPORT)
+            .is_none()
+        {
+            return Ok(());
+        }
+        let memory = self.prepare_call()?;
+        let serialized_metadata_size = serialized_metadata.len() as u64;
+        let offset_meta_data: u64 = self.wrapper_wasm_allocate(serialized_metadata_size)?;
+        self.write_memory(memory, offset_meta_data, serialized_metadata.as_slice())?;
+        let init_code = self.call_pointer_status(
+            INIT_EXPORT,
+            offset_meta_data,
+            serialized_metadata_size,
+            "Cannot initialize module",
+        )?;
+        let dealloc_metadata_code: i32 = self.wrapper_wasm_deallocate(offset_meta_data)?;
+        if dealloc_metadata_code != 0 {
+            log::warn!("Could not deallocate shared WASM module memory for meta data");
+        }
+        self.check_status(memory, init_code)
+    }
+
+    /// Finishes the module after it processed all data, so it can flush its state. Does nothing if the module does not export a finish function
+    /// returns the final output of the module in Arrow IPC format or None if it does not have any
+    fn finish(&mut self) -> Result<Option<Vec<u8>>, interface::LibraryInstanceError> {
+        if self
+            .instance
+            .get_func(&mut self.store, FINISH_EXPORT)
+            .is_none()
+        {
+            return Ok(None);
+        }
+        let memory = self.prepare_call()?;
+        let result_offset = self.call_pointer_result(FINISH_EXPORT, "Cannot finish module")?;
+        if result_offset == 0 {
+            // the module may report why it failed
+            return match self.wrapper_wasm_last_error(memory)? {
+                Some(message) => Err(interface::LibraryInstanceError::ProcessFailed(
+                    GeneralError::ErrorMessage(message),
+                )),
+                None => Ok(None),
             };
-            let result_ptr = u32::from_le_bytes(ptr_buffer);
-            let result_len = u32::from_le_bytes(len_buffer);
-            // read the Arrow IPC data
-            let mut result_arrow_ipc: Vec<u8> = vec![0; result_len as usize];
-            let mut result_arrow_ipc_buffer = result_arrow_ipc.as_mut_slice();
-            match memory.read(
-                &self.store,
-                result_ptr.try_into().unwrap(),
-                &mut result_arrow_ipc_buffer,
-            ) {
-                Ok(()) => (),
-                Err(_err) => {
-                    return Err(interface::LibraryInstanceError::InstantiationError(
-                        GeneralError::ErrorMessage(format!(
-                            "Cannot read resuts from module memory."
-                        )),
-                    ))
+        }
+        Ok(Some(self.read_result(memory, result_offset)?))
+    }
+
+    /// Returns true if the module exports an init or finish function. Such a module must process all data of a process in one instance
+    fn has_lifecycle_hooks(&self) -> bool {
+        self.lifecycle_hooks
+    }
+
+    /// Returns true if the module can process data as a stream
+    fn supports_streaming(&self) -> bool {
+        self.streaming
+    }
+
+    /// Processes data as a stream. Each record batch is pushed to the module and its output is pulled before the next one is pushed, so the memory of the module stays bounded
+    ///
+    /// # Arguments
+    /// * `serialized_metadata` - metadata (e.g. parameters) in Arrow IPC format
+    /// * `serialized_batches` - Arrow IPC streams containing one record batch each
+    /// * `output` - receives each output of the module in Arrow IPC format
+    ///
+    fn exec_stream(
+        &mut self,
+        serialized_metadata: Vec<u8>,
+        serialized_batches: &mut dyn Iterator<Item = Vec<u8>>,
+        output: &mut dyn FnMut(Vec<u8>),
+    ) -> Result<(), interface::LibraryInstanceError> {
+        if !self.streaming {
+            return Err(interface::LibraryInstanceError::MissingExport(
+                GeneralError::ErrorMessage(format!(
+                    "Module does not export the stream function {}",
+                    STREAM_START_EXPORT
+                )),
+            ));
+        }
+        let memory = self.prepare_call()?;
+        // start the stream with the metadata
+        let serialized_metadata_size = serialized_metadata.len() as u64;
+        let offset_meta_data: u64 = self.wrapper_wasm_allocate(serialized_metadata_size)?;
+        self.write_memory(memory, offset_meta_data, serialized_metadata.as_slice())?;
+        let start_code = self.call_pointer_status(
+            STREAM_START_EXPORT,
+            offset_meta_data,
+            serialized_metadata_size,
+            "Cannot start stream",
+        )?;
+        let dealloc_metadata_code: i32 = self.wrapper_wasm_deallocate(offset_meta_data)?;
+        if dealloc_metadata_code != 0 {
+            log::warn!("Could not deallocate shared WASM module memory for meta data");
+        }
+        self.check_status(memory, start_code)?;
+        // push the record batches one by one
+        for serialized_batch in serialized_batches {
+            let serialized_batch_size = serialized_batch.len() as u64;
+            let offset_batch: u64 = self.wrapper_wasm_allocate(serialized_batch_size)?;
+            self.write_memory(memory, offset_batch, serialized_batch.as_slice())?;
+            let mut push_code = self.call_pointer_status(
+                STREAM_PUSH_EXPORT,
+                offset_batch,
+                serialized_batch_size,
+                "Cannot push data to stream",
+            )?;
+            // back-pressure: the module only accepts data once its pending output has been pulled
+            while push_code == STREAM_STATUS_OUTPUT_PENDING {
+                if self.pull_stream_output(memory, output)? == 0 {
+                    return Err(interface::LibraryInstanceError::ProcessFailed(
+                        GeneralError::ErrorMessage(
+                            "Module reports pending output, but does not return any".to_string(),
+                        ),
+                    ));
                 }
-            };
-            let dealloc_return_meta_code: i32 =
-                wrapper_wasm_deallocate(self.instance, &mut self.store, result_offset as *const u8)
-                    .unwrap();
-            if dealloc_return_meta_code != 0 {
-                println!(
-                    "Error: Could not deallocate shared WASM module memory for return metadata"
-                );
+                push_code = self.call_pointer_status(
+                    STREAM_PUSH_EXPORT,
+                    offset_batch,
+                    serialized_batch_size,
+                    "Cannot push data to stream",
+                )?;
+            }
+            let dealloc_batch_code: i32 = self.wrapper_wasm_deallocate(offset_batch)?;
+            if dealloc_batch_code != 0 {
+                log::warn!("Could not deallocate shared WASM module memory for data");
+            }
+            self.check_status(memory, push_code)?;
+            self.pull_stream_output(memory, output)?;
+        }
+        // end the stream and pull the remaining output
+        let end_code = match self
+            .typed_func::<(), i32>(STREAM_END_EXPORT)?
+            .call(&mut self.store, ())
+        {
+            Ok(code) => code,
+            Err(err) => return Err(self.call_error(err, "Cannot end stream")),
+        };
+        self.check_status(memory, end_code)?;
+        self.pull_stream_output(memory, output)?;
+        Ok(())
+    }
+
+    /// Sets the limits for all following function calls. A running function is interrupted if it exceeds them
+    ///
+    /// # Arguments
+    /// * `execution_limits` - deadline and cancellation flag
+    ///
+    fn set_execution_limits(&mut self, execution_limits: interface::ExecutionLimits) {
+        self.store.data_mut().execution_limits = execution_limits;
+    }
+
+    /// Sets the state the module accesses using the functions provided by the host
+    ///
+    /// # Arguments
+    /// * `state` - state store and namespace of the step
+    ///
+    fn set_state(&mut self, state: StateHandle) {
+        self.store.data_mut().state = Some(state);
+    }
+
+    /// Sets the id of the job the instance is executing. Messages logged by the module contain it as context
+    ///
+    /// # Arguments
+    /// * `job_id` - unique instance id of the job
+    ///
+    fn set_job_id(&mut self, job_id: String) {
+        self.store.data_mut().job_id = Some(job_id);
+    }
+
+    /// Returns the fuel consumed by all calls of this instance
+    fn fuel_consumed(&self) -> u64 {
+        let fuel_remaining = match self.store.get_fuel() {
+            Ok(fuel_remaining) => fuel_remaining,
+            Err(_err) => self.fuel_refueled,
+        };
+        self.fuel_consumed_before_refuel + (self.fuel_refueled - fuel_remaining)
+    }
+}
+
+impl WASMLibrary {
+    /// Returns an exported function of the module with the given signature
+    /// # Arguments
+    /// * `name` - name of the exported function
+    /// returns an error if the function is not exported or has a different signature
+    fn typed_func<Params: WasmParams, Results: WasmResults>(
+        &mut self,
+        name: &str,
+    ) -> Result<TypedFunc<Params, Results>, interface::LibraryInstanceError> {
+        let func_def = match self.instance.get_func(&mut self.store, name) {
+            Some(func_def) => func_def,
+            None => {
+                return Err(interface::LibraryInstanceError::MissingExport(
+                    GeneralError::ErrorMessage(format!("`{}` was not an exported function", name)),
+                ))
             }
-            let dealloc_return_data_code: i32 =
-                wrapper_wasm_deallocate(self.instance, &mut self.store, result_ptr as *const u8)
-                    .unwrap();
-            if dealloc_return_data_code != 0 {
-                println!("Error: Could not deallocate shared WASM module memory for return data");
+        };
+        match func_def.typed::<Params, Results>(&self.store) {
+            Ok(func_validated) => Ok(func_validated),
+            Err(err) => Err(interface::LibraryInstanceError::SignatureMismatch(
+                GeneralError::ErrorMessage(format!(
+                    "`{}` does not have the expected signature: {}",
+                    name, err
+                )),
+            )),
+        }
+    }
+
+    /// Prepares a call of the module: checks the execution limits and refuels the module
+    /// returns the memory of the module
+    fn prepare_call(&mut self) -> Result<Memory, interface::LibraryInstanceError> {
+        // do not start if the execution should already be interrupted
+        self.store.data_mut().interrupted = None;
+        if let Some(reason) = check_execution_limits(&self.store.data().execution_limits) {
+            return Err(interface::LibraryInstanceError::ExecutionInterrupted(
+                GeneralError::ErrorMessage(reason),
+            ));
+        }
+        // account for the fuel consumed by the previous call and refuel for this call
+        self.fuel_consumed_before_refuel = interface::Library::fuel_consumed(self);
+        let fuel = self.store.data().execution_limits.fuel.unwrap_or(u64::MAX);
+        match self.store.set_fuel(fuel) {
+            Ok(()) => self.fuel_refueled = fuel,
+            Err(err) => {
+                return Err(interface::LibraryInstanceError::InstantiationError(
+                    GeneralError::ErrorMessage(format!("Cannot set fuel of module: {}", err)),
+                ))
             }
+        }
+        // instantiate memory
+        match self.instance.get_memory(&mut self.store, MEMORY_EXPORT) {
+            Some(memory) => Ok(memory),
+            None => Err(interface::LibraryInstanceError::MissingExport(
+                GeneralError::ErrorMessage(
+                    "Module does not export its memory as \"memory\"".to_string(),
+                ),
+            )),
+        }
+    }
+
+    /// Wrapper around the allocate function of the WASM module to allocate shared WASM memory. Allocate some memory for the application to write data for the module
+    /// Note: It is up to the application (and not the WASM module) to provide enough pages, so the module does not run out of memory
+    /// # Arguments
+    /// * `size` - size of memory to allocate
+    /// returns a pointer to the allocated memory area
+    fn wrapper_wasm_allocate(&mut self, size: u64) -> Result<u64, interface::LibraryInstanceError> {
+        // get the function and validate that it corresponds to the parameters and return types we need
+        // pointers and sizes are 64 bit for modules with 64 bit memory
+        let result = if self.memory64 {
+            let func_validated = self.typed_func::<u64, u64>(ALLOCATE_EXPORT)?;
+            func_validated.call(&mut self.store, size)
+        } else {
+            let size = pointer32(size)?;
+            let func_validated = self.typed_func::<u32, u32>(ALLOCATE_EXPORT)?;
+            func_validated
+                .call(&mut self.store, size)
+                .map(|ptr| ptr as u64)
+        };
+        match result {
+            Ok(ptr) => Ok(ptr),
+            Err(err) => Err(self.call_error(err, "Cannot allocate shared WASM module memory")),
+        }
+    }
+
+    ///  Wrapper around the deallocate function of the WASM module to deallocate shared WASM memory. Deallocates existing memory for the purpose of the application
+    /// # Arguments
+    /// * `ptr` - pointer to the memory to deallocate
+    /// returns a code if it was successful or not
+    fn wrapper_wasm_deallocate(
+        &mut self,
+        ptr: u64,
+    ) -> Result<i32, interface::LibraryInstanceError> {
+        // get the function and validate that it corresponds to the parameters and return types we need
+        let result = if self.memory64 {
+            let func_validated = self.typed_func::<u64, i32>(DEALLOCATE_EXPORT)?;
+            func_validated.call(&mut self.store, ptr)
+        } else {
+            let ptr = pointer32(ptr)?;
+            let func_validated = self.typed_func::<u32, i32>(DEALLOCATE_EXPORT)?;
+            func_validated.call(&mut self.store, ptr)
+        };
+        match result {
+            Ok(code) => Ok(code),
+            Err(err) => Err(self.call_error(err, "Cannot deallocate shared WASM module memory")),
+        }
+    }
+
+    /// Wrapper around the raw process entry function of the WASM module
+    /// # Arguments
+    /// * `meta_ptr` - pointer to the metadata in Arrow IPC format
+    /// * `meta_len` - size of the metadata
+    /// * `data_ptr` - pointer to the data in Arrow IPC format
+    /// * `data_len` - size of the data
+    /// returns a pointer to the result descriptor or 0 in case of an error
+    fn wrapper_wasm_raw_process_entry(
+        &mut self,
+        meta_ptr: u64,
+        meta_len: u64,
+        data_ptr: u64,
+        data_len: u64,
+    ) -> Result<u64, interface::LibraryInstanceError> {
+        let result = if self.memory64 {
+            let func_validated =
+                self.typed_func::<(u64, u64, u64, u64), u64>(RAW_PROCESS_ENTRY_EXPORT)?;
+            func_validated.call(&mut self.store, (meta_ptr, meta_len, data_ptr, data_len))
+        } else {
+            let params = (
+                pointer32(meta_ptr)?,
+                pointer32(meta_len)?,
+                pointer32(data_ptr)?,
+                pointer32(data_len)?,
+            );
+            let func_validated =
+                self.typed_func::<(u32, u32, u32, u32), u32>(RAW_PROCESS_ENTRY_EXPORT)?;
+            func_validated
+                .call(&mut self.store, params)
+                .map(|result_offset| result_offset as u64)
+        };
+        match result {
+            Ok(result_offset) => Ok(result_offset),
+            Err(err) => Err(self.call_error(err, "Cannot execute function")),
+        }
+    }
 
-            return Ok(result_arrow_ipc);
+    /// Wrapper around the optional last error function of the WASM module. It returns the error of the last failed call of the raw process entry
+    /// # Arguments
+    /// * `memory` - memory of the module
+    /// returns the error message or None if the module does not report errors
+    fn wrapper_wasm_last_error(
+        &mut self,
+        memory: Memory,
+    ) -> Result<Option<String>, interface::LibraryInstanceError> {
+        if self
+            .instance
+            .get_func(&mut self.store, LAST_ERROR_EXPORT)
+            .is_none()
+        {
+            return Ok(None);
+        }
+        let error_offset =
+            self.call_pointer_result(LAST_ERROR_EXPORT, "Cannot read error of module")?;
+        if error_offset == 0 {
+            return Ok(None);
         }
+        let message = self.read_result(memory, error_offset)?;
+        Ok(Some(String::from_utf8_lossy(&message).to_string()))
+    }
 
-        Err(interface::LibraryInstanceError::InstantiationError(
-            GeneralError::ErrorMessage(format!("Cannot execute function from module")),
+    /// Calls a function of the WASM module without parameters returning a pointer
+    /// # Arguments
+    /// * `name` - name of the function
+    /// * `context` - description of the call for error messages
+    /// returns the pointer
+    fn call_pointer_result(
+        &mut self,
+        name: &str,
+        context: &str,
+    ) -> Result<u64, interface::LibraryInstanceError> {
+        let result = if self.memory64 {
+            let func_validated = self.typed_func::<(), u64>(name)?;
+            func_validated.call(&mut self.store, ())
+        } else {
+            let func_validated = self.typed_func::<(), u32>(name)?;
+            func_validated
+                .call(&mut self.store, ())
+                .map(|ptr| ptr as u64)
+        };
+        match result {
+            Ok(ptr) => Ok(ptr),
+            Err(err) => Err(self.call_error(err, context)),
+        }
+    }
+
+    /// Calls a function of the WASM module with a pointer and a size as parameters returning a status code
+    /// # Arguments
+    /// * `name` - name of the function
+    /// * `ptr` - pointer
+    /// * `len` - size
+    /// * `context` - description of the call for error messages
+    /// returns the status code
+    fn call_pointer_status(
+        &mut self,
+        name: &str,
+        ptr: u64,
+        len: u64,
+        context: &str,
+    ) -> Result<i32, interface::LibraryInstanceError> {
+        let result = if self.memory64 {
+            let func_validated = self.typed_func::<(u64, u64), i32>(name)?;
+            func_validated.call(&mut self.store, (ptr, len))
+        } else {
+            let params = (pointer32(ptr)?, pointer32(len)?);
+            let func_validated = self.typed_func::<(u32, u32), i32>(name)?;
+            func_validated.call(&mut self.store, params)
+        };
+        match result {
+            Ok(code) => Ok(code),
+            Err(err) => Err(self.call_error(err, context)),
+        }
+    }
+
+    /// Checks the status code returned by a function of the module
+    /// # Arguments
+    /// * `memory` - memory of the module
+    /// * `code` - status code
+    /// returns an error containing the error reported by the module if the status code is not 0
+    fn check_status(
+        &mut self,
+        memory: Memory,
+        code: i32,
+    ) -> Result<(), interface::LibraryInstanceError> {
+        if code == STATUS_OK {
+            return Ok(());
+        }
+        let message = match self.wrapper_wasm_last_error(memory)? {
+            Some(message) => message,
+            None => format!("Function returned code {}", code),
+        };
+        Err(interface::LibraryInstanceError::ProcessFailed(
+            GeneralError::ErrorMessage(message),
         ))
     }
+
+    /// Pulls all pending output of a stream from the module
+    /// # Arguments
+    /// * `memory` - memory of the module
+    /// * `output` - receives each output in Arrow IPC format
+    /// returns the number of outputs pulled
+    fn pull_stream_output(
+        &mut self,
+        memory: Memory,
+        output: &mut dyn FnMut(Vec<u8>),
+    ) -> Result<usize, interface::LibraryInstanceError> {
+        let mut pulled = 0;
+        loop {
+            let result_offset =
+                self.call_pointer_result(STREAM_PULL_EXPORT, "Cannot pull output of stream")?;
+            if result_offset == 0 {
+                return Ok(pulled);
+            }
+            output(self.read_result(memory, result_offset)?);
+            pulled += 1;
+        }
+    }
+
+    /// Reads a result returned by the module and deallocates it in the module
+    /// # Arguments
+    /// * `memory` - memory of the module
+    /// * `descriptor_offset` - position of the result descriptor in the memory
+    /// returns the content of the result
+    fn read_result(
+        &mut self,
+        memory: Memory,
+        descriptor_offset: u64,
+    ) -> Result<Vec<u8>, interface::LibraryInstanceError> {
+        let (result_ptr, result_len) = self.read_result_descriptor(memory, descriptor_offset)?;
+        // Check the length first, so a wrong length does not allocate a huge buffer
+        let memory_size = memory.data_size(&self.store) as u64;
+        if result_ptr > memory_size || result_len > memory_size - result_ptr {
+            return Err(interface::LibraryInstanceError::OutOfBounds(
+                GeneralError::ErrorMessage(format!(
+                    "Result of {} bytes at offset {} is outside of module memory of {} bytes",
+                    result_len, result_ptr, memory_size
+                )),
+            ));
+        }
+        let mut result: Vec<u8> = vec![0; result_len as usize];
+        self.read_memory(memory, result_ptr, result.as_mut_slice())?;
+        let dealloc_return_meta_code: i32 = self.wrapper_wasm_deallocate(descriptor_offset)?;
+        if dealloc_return_meta_code != 0 {
+            log::warn!("Could not deallocate shared WASM module memory for return metadata");
+        }
+        let dealloc_return_data_code: i32 = self.wrapper_wasm_deallocate(result_ptr)?;
+        if dealloc_return_data_code != 0 {
+            log::warn!("Could not deallocate shared WASM module memory for return data");
+        }
+        Ok(result)
+    }
+
+    /// Reads the result descriptor returned by the module. Its layout depends on the version of the ABI implemented by the module
+    /// # Arguments
+    /// * `memory` - memory of the module
+    /// * `offset` - position of the result descriptor in the memory
+    /// returns the pointer to and the length of the result
+    fn read_result_descriptor(
+        &self,
+        memory: Memory,
+        offset: u64,
+    ) -> Result<(u64, u64), interface::LibraryInstanceError> {
+        match self.abi_version {
+            // two little endian u32
+            1 => {
+                let mut ptr_buffer = [0u8; (u32::BITS / 8) as usize];
+                let mut len_buffer = [0u8; (u32::BITS / 8) as usize];
+                self.read_memory(memory, offset, &mut ptr_buffer)?;
+                self.read_memory(
+                    memory,
+                    offset.wrapping_add((u32::BITS / 8) as u64),
+                    &mut len_buffer,
+                )?;
+                Ok((
+                    u32::from_le_bytes(ptr_buffer) as u64,
+                    u32::from_le_bytes(len_buffer) as u64,
+                ))
+            }
+            // two little endian u64 independent of the width of pointers of the module
+            _ => {
+                let mut ptr_buffer = [0u8; (u64::BITS / 8) as usize];
+                let mut len_buffer = [0u8; (u64::BITS / 8) as usize];
+                self.read_memory(memory, offset, &mut ptr_buffer)?;
+                self.read_memory(
+                    memory,
+                    offset.wrapping_add((u64::BITS / 8) as u64),
+                    &mut len_buffer,
+                )?;
+                Ok((
+                    u64::from_le_bytes(ptr_buffer),
+                    u64::from_le_bytes(len_buffer),
+                ))
+            }
+        }
+    }
+
+    /// Writes data into the memory of the module
+    /// # Arguments
+    /// * `memory` - memory of the module
+    /// * `offset` - position in the memory to write to
+    /// * `data` - data to write
+    fn write_memory(
+        &mut self,
+        memory: Memory,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<(), interface::LibraryInstanceError> {
+        match memory.write(&mut self.store, offset as usize, data) {
+            Ok(()) => Ok(()),
+            Err(_err) => Err(interface::LibraryInstanceError::OutOfBounds(
+                GeneralError::ErrorMessage(format!(
+                    "Cannot write {} bytes at offset {} to module memory of {} bytes",
+                    data.len(),
+                    offset,
+                    memory.data_size(&self.store)
+                )),
+            )),
+        }
+    }
+
+    /// Reads data from the memory of the module
+    /// # Arguments
+    /// * `memory` - memory of the module
+    /// * `offset` - position in the memory to read from
+    /// * `buffer` - buffer to fill with the data
+    fn read_memory(
+        &self,
+        memory: Memory,
+        offset: u64,
+        buffer: &mut [u8],
+    ) -> Result<(), interface::LibraryInstanceError> {
+        match memory.read(&self.store, offset as usize, buffer) {
+            Ok(()) => Ok(()),
+            Err(_err) => Err(interface::LibraryInstanceError::OutOfBounds(
+                GeneralError::ErrorMessage(format!(
+                    "Cannot read {} bytes at offset {} from module memory of {} bytes",
+                    buffer.len(),
+                    offset,
+                    memory.data_size(&self.store)
+                )),
+            )),
+        }
+    }
+
+    /// Converts the error of calling a function of the module into an error of the library instance
+    /// # Arguments
+    /// * `err` - error returned by the function call
+    /// * `message` - describes the function call that failed
+    /// returns a distinct error if the function exceeded its timeout, fuel or memory or if it trapped
+    fn call_error(&mut self, err: anyhow::Error, message: &str) -> interface::LibraryInstanceError {
+        if let Some(reason) = self.store.data_mut().interrupted.take() {
+            return interface::LibraryInstanceError::ExecutionInterrupted(
+                GeneralError::ErrorMessage(reason),
+            );
+        }
+        if let Some(reason) = self.store.data_mut().resource_limiter.exceeded.take() {
+            return interface::LibraryInstanceError::ResourceLimitExceeded(
+                GeneralError::ErrorMessage(reason),
+            );
+        }
+        match err.downcast_ref::<Trap>() {
+            Some(Trap::OutOfFuel) => {
+                interface::LibraryInstanceError::FuelExhausted(GeneralError::ErrorMessage(format!(
+                    "Execution of module consumed all of its fuel ({})",
+                    self.fuel_refueled
+                )))
+            }
+            Some(trap) => {
+                let backtrace = match err.downcast_ref::<WasmBacktrace>() {
+                    Some(backtrace) => format!("\n{}", backtrace),
+                    None => String::new(),
+                };
+                interface::LibraryInstanceError::Trap(GeneralError::ErrorMessage(format!(
+                    "{}. Module trapped: {}{}",
+                    message, trap, backtrace
+                )))
+            }
+            None => interface::LibraryInstanceError::InstantiationError(
+                GeneralError::ErrorMessage(format!("{}: {:?}", message, err)),
+            ),
+        }
+    }
 }
 
-/// Wrapper around the allocate function of the WASM module to allocate shared WASM memory. Allocate some memory for the application to write data for the module
-/// Note: It is up to the application (and not the WASM module) to provide enough pages, so the module does not run out of memory
+/// Converts a pointer or size to a pointer or size of a module with 32 bit memory
 /// # Arguments
-/// * `size` - size of memory to allocate
-/// returns a pointer to the allocated memory area
-fn wrapper_wasm_allocate(
-    instance: Instance,
-    mut store: impl AsContextMut<Data = WASMState>,
-    size: u32,
-) -> anyhow::Result<*const u8> {
-    // Load function an instantiate it
-
-    // get the function
-    let func_def = instance
-        .get_func(&mut store, "zustdp_module_wasm_allocate")
-        .expect("`wasm_allocate` was not an exported function");
-    // validate that it corresponds to the parameters and return types we need
-    let func_validated = func_def.typed::<u32, u32>(&store)?;
-    // call function
-    let result = func_validated.call(&mut store, size)?;
-    Ok(result as *const u8)
-}
-
-///  Wrapper around the deallocate function of the WASM module to deallocate shared WASM memory. Deallocates existing memory for the purpose of the application
+/// * `value` - pointer or size
+/// returns an error if the value does not fit into 32 bit
+fn pointer32(value: u64) -> Result<u32, interface::LibraryInstanceError> {
+    match u32::try_from(value) {
+        Ok(value) => Ok(value),
+        Err(_err) => Err(interface::LibraryInstanceError::OutOfBounds(
+            GeneralError::ErrorMessage(format!(
+                "{} exceeds the 32 bit memory of the module",
+                value
+            )),
+        )),
+    }
+}
+
+/// Checks if a function needs to be interrupted
 /// # Arguments
-/// * `ptr` - mutuable pointer to the memory to deallocate
-/// returns a code if it was successful or not
-fn wrapper_wasm_deallocate(
-    instance: Instance,
-    mut store: impl AsContextMut<Data = WASMState>,
-    ptr: *const u8,
+/// * `execution_limits` - limits of the function
+/// returns the reason for the interruption or None if the function can continue
+fn check_execution_limits(execution_limits: &interface::ExecutionLimits) -> Option<String> {
+    if let Some(cancel_requested) = &execution_limits.cancel_requested {
+        if cancel_requested.load(Ordering::Relaxed) {
+            return Some("Execution of module has been cancelled".to_string());
+        }
+    }
+    if let Some(deadline) = execution_limits.deadline {
+        if Instant::now() >= deadline {
+            return Some("Execution of module exceeded its timeout".to_string());
+        }
+    }
+    None
+}
+
+/// Called by wasmtime on each epoch tick while a function of the module is running
+/// Interrupts the function (by a trap) if it has been cancelled or exceeded its deadline
+fn epoch_deadline_reached(
+    mut context: StoreContextMut<WASMState>,
+) -> anyhow::Result<UpdateDeadline> {
+    match check_execution_limits(&context.data().execution_limits) {
+        Some(reason) => {
+            context.data_mut().interrupted = Some(reason.clone());
+            Err(anyhow::anyhow!(reason))
+        }
+        None => Ok(UpdateDeadline::Continue(1)),
+    }
+}
+
+/// Creates the WASI context of a module instance. The module can only access the preopened directories, environment variables and arguments of its definition
+/// # Arguments
+/// * `wasi_definition` - WASI context of the module
+fn build_wasi_ctx(
+    wasi_definition: &interface::WasiDefinition,
+) -> Result<WasiCtx, interface::LibraryDefinitionError> {
+    let mut wasi_ctx_builder = WasiCtxBuilder::new();
+    if wasi_definition.inherit_stdio {
+        wasi_ctx_builder.inherit_stdout();
+        wasi_ctx_builder.inherit_stderr();
+    }
+    if wasi_definition.inherit_stdin {
+        wasi_ctx_builder.inherit_stdin();
+    }
+    let mut env: Vec<(&String, &String)> = wasi_definition.env.iter().collect();
+    env.sort();
+    wasi_ctx_builder.envs(&env);
+    wasi_ctx_builder.args(&wasi_definition.args);
+    for preopened_dir in &wasi_definition.preopened_dirs {
+        let dir = match Dir::open_ambient_dir(&preopened_dir.host_path, ambient_authority()) {
+            Ok(dir) => dir,
+            Err(err) => {
+                return Err(interface::LibraryDefinitionError::ModuleSpecificError(
+                    GeneralError::ErrorMessage(format!(
+                        "WASM Library Manager. Cannot open preopened directory {}: {}",
+                        preopened_dir.host_path, err
+                    )),
+                ))
+            }
+        };
+        let (dir_perms, file_perms) = match preopened_dir.access {
+            interface::WasiDirAccess::ReadOnly => (DirPerms::READ, FilePerms::READ),
+            interface::WasiDirAccess::ReadWrite => (DirPerms::all(), FilePerms::all()),
+        };
+        wasi_ctx_builder.preopened_dir(dir, dir_perms, file_perms, &preopened_dir.guest_path);
+    }
+    Ok(wasi_ctx_builder.build())
+}
+
+/// Links the functions the host provides to modules (see `wasm_abi`)
+/// # Arguments
+/// * `linker` - linker of the module
+/// * `memory64` - if the module uses 64 bit memory. Pointers and sizes are 64 bit then
+fn add_host_functions(linker: &mut Linker<WASMState>, memory64: bool) -> anyhow::Result<()> {
+    if memory64 {
+        linker.func_wrap(
+            HOST_MODULE,
+            STATE_GET_IMPORT,
+            |caller: Caller<'_, WASMState>, key_ptr: u64, key_len: u64| -> anyhow::Result<u64> {
+                host_state_get(caller, key_ptr, key_len)
+            },
+        )?;
+        linker.func_wrap(
+            HOST_MODULE,
+            STATE_PUT_IMPORT,
+            |caller: Caller<'_, WASMState>,
+             key_ptr: u64,
+             key_len: u64,
+             value_ptr: u64,
+             value_len: u64|
+             -> anyhow::Result<i32> {
+                host_state_put(caller, key_ptr, key_len, value_ptr, value_len)
+            },
+        )?;
+        linker.func_wrap(
+            HOST_MODULE,
+            STATE_DELETE_IMPORT,
+            |caller: Caller<'_, WASMState>, key_ptr: u64, key_len: u64| -> anyhow::Result<i32> {
+                host_state_delete(caller, key_ptr, key_len)
+            },
+        )?;
+        linker.func_wrap(
+            HOST_MODULE,
+            LOG_IMPORT,
+            |caller: Caller<'_, WASMState>,
+             level: i32,
+             message_ptr: u64,
+             message_len: u64|
+             -> anyhow::Result<i32> {
+                host_log(caller, level, message_ptr, message_len)
+            },
+        )?;
+    } else {
+        linker.func_wrap(
+            HOST_MODULE,
+            STATE_GET_IMPORT,
+            |caller: Caller<'_, WASMState>, key_ptr: u32, key_len: u32| -> anyhow::Result<u32> {
+                // memory allocated in a module with 32 bit memory has a 32 bit pointer
+                host_state_get(caller, key_ptr as u64, key_len as u64).map(|ptr| ptr as u32)
+            },
+        )?;
+        linker.func_wrap(
+            HOST_MODULE,
+            STATE_PUT_IMPORT,
+            |caller: Caller<'_, WASMState>,
+             key_ptr: u32,
+             key_len: u32,
+             value_ptr: u32,
+             value_len: u32|
+             -> anyhow::Result<i32> {
+                host_state_put(
+                    caller,
+                    key_ptr as u64,
+                    key_len as u64,
+                    value_ptr as u64,
+                    value_len as u64,
+                )
+            },
+        )?;
+        linker.func_wrap(
+            HOST_MODULE,
+            STATE_DELETE_IMPORT,
+            |caller: Caller<'_, WASMState>, key_ptr: u32, key_len: u32| -> anyhow::Result<i32> {
+                host_state_delete(caller, key_ptr as u64, key_len as u64)
+            },
+        )?;
+        linker.func_wrap(
+            HOST_MODULE,
+            LOG_IMPORT,
+            |caller: Caller<'_, WASMState>,
+             level: i32,
+             message_ptr: u32,
+             message_len: u32|
+             -> anyhow::Result<i32> {
+                host_log(caller, level, message_ptr as u64, message_len as u64)
+            },
+        )?;
+    }
+    Ok(())
+}
+
+/// Logs a message of the module with the name of the module and the id of the job as context. Called by the module
+/// # Arguments
+/// * `caller` - module instance calling the function
+/// * `level` - level of the message: 1 error, 2 warn, 3 info, 4 debug, 5 trace
+/// * `message_ptr` - pointer to the message (UTF-8)
+/// * `message_len` - size of the message
+/// returns a status code
+fn host_log(
+    mut caller: Caller<'_, WASMState>,
+    level: i32,
+    message_ptr: u64,
+    message_len: u64,
 ) -> anyhow::Result<i32> {
-    // get the function
-    let func_def = instance
-        .get_func(&mut store, "zustdp_module_wasm_deallocate")
-        .expect("`wasm_deallocate` was not an exported function");
-    // validate that it corresponds to the parameters and return types we need
-    let func_validated = func_def.typed::<u32, i32>(&store)?;
-    // call function
-    let result = func_validated.call(&mut store, ptr as u32)?;
-    Ok(result)
+    let level = match level {
+        1 => log::Level::Error,
+        2 => log::Level::Warn,
+        3 => log::Level::Info,
+        4 => log::Level::Debug,
+        5 => log::Level::Trace,
+        _ => return Ok(STATUS_ERROR),
+    };
+    let memory = caller_memory(&mut caller)?;
+    let message = caller_read_memory(&caller, memory, message_ptr, message_len)?;
+    let state = caller.data();
+    let target = format!("zustdp_module::{}", state.module_name);
+    let job_id = match &state.job_id {
+        Some(job_id) => job_id.as_str(),
+        None => "none",
+    };
+    log::log!(
+        target: &target,
+        level,
+        "[job {}] {}",
+        job_id,
+        String::from_utf8_lossy(&message)
+    );
+    Ok(STATUS_OK)
+}
+
+/// Returns the name of a module used as context of its log messages
+/// # Arguments
+/// * `path` - path to the module
+fn module_name(path: &str) -> String {
+    match std::path::Path::new(path).file_name() {
+        Some(file_name) => file_name.to_string_lossy().to_string(),
+        None => path.to_string(),
+    }
+}
+
+/// Returns the value of a key in the state of the step. Called by the module
+/// # Arguments
+/// * `caller` - module instance calling the function
+/// * `key_ptr` - pointer to the key
+/// * `key_len` - size of the key
+/// returns a pointer to a result descriptor of the value allocated in the module or 0 if the key does not exist
+fn host_state_get(
+    mut caller: Caller<'_, WASMState>,
+    key_ptr: u64,
+    key_len: u64,
+) -> anyhow::Result<u64> {
+    let memory = caller_memory(&mut caller)?;
+    let key = caller_read_memory(&caller, memory, key_ptr, key_len)?;
+    let state = caller_state(&caller)?;
+    let value = match state.store.lock() {
+        Ok(store) => store.get(&state.job_id, &state.namespace, &key),
+        Err(err) => return Err(anyhow::anyhow!("State store is not available: {}", err)),
+    };
+    let value = match value {
+        Some(value) => value,
+        None => return Ok(0),
+    };
+    let value_offset = caller_allocate(&mut caller, memory, value.len() as u64)?;
+    caller_write_memory(&mut caller, memory, value_offset, &value)?;
+    let mut descriptor: Vec<u8> = Vec::new();
+    descriptor.extend_from_slice(&value_offset.to_le_bytes());
+    descriptor.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    let descriptor_offset = caller_allocate(&mut caller, memory, descriptor.len() as u64)?;
+    caller_write_memory(&mut caller, memory, descriptor_offset, &descriptor)?;
+    Ok(descriptor_offset)
+}
+
+/// Sets the value of a key in the state of the step. Called by the module
+/// # Arguments
+/// * `caller` - module instance calling the function
+/// * `key_ptr` - pointer to the key
+/// * `key_len` - size of the key
+/// * `value_ptr` - pointer to the value
+/// * `value_len` - size of the value
+/// returns a status code
+fn host_state_put(
+    mut caller: Caller<'_, WASMState>,
+    key_ptr: u64,
+    key_len: u64,
+    value_ptr: u64,
+    value_len: u64,
+) -> anyhow::Result<i32> {
+    let memory = caller_memory(&mut caller)?;
+    let key = caller_read_memory(&caller, memory, key_ptr, key_len)?;
+    let value = caller_read_memory(&caller, memory, value_ptr, value_len)?;
+    let state = caller_state(&caller)?;
+    match state.store.lock() {
+        Ok(mut store) => store.put(&state.job_id, &state.namespace, &key, &value),
+        Err(err) => return Err(anyhow::anyhow!("State store is not available: {}", err)),
+    };
+    Ok(STATUS_OK)
+}
+
+/// Deletes a key from the state of the step. Called by the module
+/// # Arguments
+/// * `caller` - module instance calling the function
+/// * `key_ptr` - pointer to the key
+/// * `key_len` - size of the key
+/// returns a status code
+fn host_state_delete(
+    mut caller: Caller<'_, WASMState>,
+    key_ptr: u64,
+    key_len: u64,
+) -> anyhow::Result<i32> {
+    let memory = caller_memory(&mut caller)?;
+    let key = caller_read_memory(&caller, memory, key_ptr, key_len)?;
+    let state = caller_state(&caller)?;
+    match state.store.lock() {
+        Ok(mut store) => store.delete(&state.job_id, &state.namespace, &key),
+        Err(err) => return Err(anyhow::anyhow!("State store is not available: {}", err)),
+    };
+    Ok(STATUS_OK)
+}
+
+/// Returns the state of the step executed by the calling module instance
+/// # Arguments
+/// * `caller` - module instance calling a function provided by the host
+fn caller_state(caller: &Caller<'_, WASMState>) -> anyhow::Result<StateHandle> {
+    match &caller.data().state {
+        Some(state) => Ok(state.clone()),
+        None => Err(anyhow::anyhow!(
+            "Module accessed the state, but no state is available"
+        )),
+    }
+}
+
+/// Returns the memory of the calling module instance
+/// # Arguments
+/// * `caller` - module instance calling a function provided by the host
+fn caller_memory(caller: &mut Caller<'_, WASMState>) -> anyhow::Result<Memory> {
+    match caller.get_export(MEMORY_EXPORT) {
+        Some(export) => match export.into_memory() {
+            Some(memory) => Ok(memory),
+            None => Err(anyhow::anyhow!(
+                "Export `{}` is not a memory",
+                MEMORY_EXPORT
+            )),
+        },
+        None => Err(anyhow::anyhow!(
+            "Memory `{}` is not exported",
+            MEMORY_EXPORT
+        )),
+    }
+}
+
+/// Reads from the memory of the calling module instance
+/// # Arguments
+/// * `caller` - module instance calling a function provided by the host
+/// * `memory` - memory of the module instance
+/// * `offset` - pointer to the data
+/// * `len` - size of the data
+fn caller_read_memory(
+    caller: &Caller<'_, WASMState>,
+    memory: Memory,
+    offset: u64,
+    len: u64,
+) -> anyhow::Result<Vec<u8>> {
+    let data = memory.data(caller);
+    let range = usize::try_from(offset)
+        .ok()
+        .zip(usize::try_from(len).ok())
+        .and_then(|(start, len)| start.checked_add(len).map(|end| start..end));
+    match range.and_then(|range| data.get(range)) {
+        Some(bytes) => Ok(bytes.to_vec()),
+        None => Err(anyhow::anyhow!(
+            "Module passed pointer {} and length {} outside of its memory",
+            offset,
+            len
+        )),
+    }
+}
+
+/// Writes to the memory of the calling module instance
+/// # Arguments
+/// * `caller` - module instance calling a function provided by the host
+/// * `memory` - memory of the module instance
+/// * `offset` - pointer to write to
+/// * `data` - data to write
+fn caller_write_memory(
+    caller: &mut Caller<'_, WASMState>,
+    memory: Memory,
+    offset: u64,
+    data: &[u8],
+) -> anyhow::Result<()> {
+    match usize::try_from(offset) {
+        Ok(offset) => memory.write(caller, offset, data)?,
+        Err(err) => return Err(anyhow::anyhow!("Invalid pointer {}: {}", offset, err)),
+    };
+    Ok(())
+}
+
+/// Allocates memory in the calling module instance
+/// # Arguments
+/// * `caller` - module instance calling a function provided by the host
+/// * `memory` - memory of the module instance
+/// * `size` - size of the memory to allocate
+/// returns a pointer to the allocated memory
+fn caller_allocate(
+    caller: &mut Caller<'_, WASMState>,
+    memory: Memory,
+    size: u64,
+) -> anyhow::Result<u64> {
+    let allocate = match caller.get_export(ALLOCATE_EXPORT) {
+        Some(export) => match export.into_func() {
+            Some(allocate) => allocate,
+            None => {
+                return Err(anyhow::anyhow!(
+                    "Export `{}` is not a function",
+                    ALLOCATE_EXPORT
+                ))
+            }
+        },
+        None => {
+            return Err(anyhow::anyhow!(
+                "Function `{}` is not exported",
+                ALLOCATE_EXPORT
+            ))
+        }
+    };
+    if memory.ty(&caller).is_64() {
+        allocate.typed::<u64, u64>(&caller)?.call(caller, size)
+    } else {
+        match u32::try_from(size) {
+            Ok(size) => Ok(allocate.typed::<u32, u32>(&caller)?.call(caller, size)? as u64),
+            Err(err) => Err(anyhow::anyhow!("Invalid size {}: {}", size, err)),
+        }
+    }
 }
 
 pub struct WASMLibraryManager {
     loaded_modules: HashMap<String, Module>,
     engine: Engine,
+    /// stops the thread incrementing the epoch of the engine
+    epoch_ticker_stop: Arc<AtomicBool>,
+}
+
+impl Drop for WASMLibraryManager {
+    fn drop(&mut self) {
+        self.epoch_ticker_stop.store(true, Ordering::Relaxed);
+    }
+}
+
+impl WASMLibraryManager {
+    /// Returns a compiled module. The module is compiled and validated only once
+    /// # Arguments
+    /// * `path` - path to the module
+    /// returns an error if the module cannot be compiled or does not implement the ABI expected by the host
+    fn load_module(&mut self, path: &String) -> Result<Module, interface::LibraryDefinitionError> {
+        // check if WASM module has already been loaded
+        if let Some(module) = self.loaded_modules.get(path) {
+            return Ok(module.clone());
+        }
+        // if no => load it
+        let module = match Module::from_file(&self.engine, &path) {
+            Ok(module) => module,
+            Err(err) => {
+                return Err(interface::LibraryDefinitionError::ModuleSpecificError(
+                    GeneralError::ErrorMessage(format!(
+                        "WASM Library Manager. Error during loading module: {}",
+                        err.to_string()
+                    )),
+                ))
+            }
+        };
+        let mut violations = validate_module_abi(&module);
+        violations.extend(validate_module_imports(&module));
+        if !violations.is_empty() {
+            return Err(interface::LibraryDefinitionError::AbiViolation(violations));
+        }
+        self.loaded_modules.insert(path.clone(), module.clone());
+        Ok(module)
+    }
 }
 
 impl interface::LibraryManager<WASMLibrary> for WASMLibraryManager {
-    fn new() -> WASMLibraryManager
+    fn new() -> Result<WASMLibraryManager, interface::LibraryDefinitionError>
     where
         Self: interface::LibraryManager<WASMLibrary>,
     {
-        WASMLibraryManager {
+        // running modules are interrupted based on epochs, so they can be cancelled or timed out
+        // fuel is consumed, so the compute of a module can be limited and measured
+        let mut config = Config::new();
+        config.epoch_interruption(true);
+        config.consume_fuel(true);
+        // modules with 64 bit memory are supported
+        config.wasm_memory64(true);
+        let engine = match Engine::new(&config) {
+            Ok(engine) => engine,
+            Err(err) => {
+                return Err(interface::LibraryDefinitionError::ModuleSpecificError(
+                    GeneralError::ErrorMessage(format!(
+                        "WASM Library Manager. Cannot create WASM engine: {}",
+                        err
+                    )),
+                ))
+            }
+        };
+        let epoch_ticker_stop = Arc::new(AtomicBool::new(false));
+        let ticker_engine = engine.clone();
+        let ticker_stop = epoch_ticker_stop.clone();
+        thread::spawn(move || {
+            while !ticker_stop.load(Ordering::Relaxed) {
+                thread::sleep(EPOCH_TICK);
+                ticker_engine.increment_epoch();
+            }
+        });
+        Ok(WASMLibraryManager {
             loaded_modules: HashMap::<String, Module>::new(),
-            engine: Engine::default(),
+            engine: engine,
+            epoch_ticker_stop: epoch_ticker_stop,
+        })
+    }
+    /// Loads a module and validates that it implements the ABI expected by the host
+    ///
+    /// # Arguments
+    /// * `library_definition` - definition of the module
+    ///
+    /// returns all violations of the ABI at once
+    fn validate(
+        &mut self,
+        library_definition: &interface::LibraryDefinition,
+    ) -> Result<(), interface::LibraryDefinitionError> {
+        validate_preopened_dirs(&library_definition.wasi)?;
+        let module = self.load_module(&library_definition.path)?;
+        // the version is read from an instance without access to the host (e.g. its file system), which may only consume limited fuel
+        let (instance, mut store) = self.instantiate(
+            &module,
+            library_definition,
+            WasiCtxBuilder::new().build(),
+            &interface::ExecutionLimits::default(),
+            ABI_VERSION_FUEL,
+        )?;
+        let abi_version = read_abi_version(&instance, &mut store)?;
+        match validate_abi_version(abi_version, is_memory64(&module)) {
+            Some(error) => Err(interface::LibraryDefinitionError::UnsupportedAbiVersion(
+                error,
+            )),
+            None => Ok(()),
         }
     }
+
     fn get_instance(
         &mut self,
-        path: &String,
+        library_definition: &interface::LibraryDefinition,
+        execution_limits: &interface::ExecutionLimits,
     ) -> Result<Box<WASMLibrary>, interface::LibraryDefinitionError> {
-        // check if WASM module has already been loaded
-        let module = match self.loaded_modules.get(path) {
-            Some(x) => Some(x),
-            None => {
-                match Module::from_file(
-                    // if no => load it
-                    &self.engine,
-                    &path,
-                ) {
-                    Ok(x) => {
-                        self.loaded_modules.insert(path.clone(), x);
-                        self.loaded_modules.get(path)
-                    }
-                    Err(err) => {
-                        return Err(interface::LibraryDefinitionError::ModuleSpecificError(
-                            GeneralError::ErrorMessage(format!(
-                                "WASM Library Manager. Error during loading module: {}",
-                                err.to_string()
-                            )),
-                        ))
-                    }
-                }
-            }
+        let path = &library_definition.path;
+        let module = self.load_module(path)?;
+        let wasi = build_wasi_ctx(&library_definition.wasi)?;
+        // the limits already apply to the start function of the module
+        let (instance, mut store) = self.instantiate(
+            &module,
+            library_definition,
+            wasi,
+            execution_limits,
+            execution_limits.fuel.unwrap_or(u64::MAX),
+        )?;
+        // check that the host supports the version of the ABI implemented by the module
+        let memory64 = is_memory64(&module);
+        let abi_version = read_abi_version(&instance, &mut store)?;
+        if let Some(error) = validate_abi_version(abi_version, memory64) {
+            return Err(interface::LibraryDefinitionError::UnsupportedAbiVersion(
+                error,
+            ));
         }
-        .unwrap();
+        // fuel consumed during instantiation is not accounted to the calls of the module
+        match store.set_fuel(u64::MAX) {
+            Ok(()) => (),
+            Err(err) => {
+                return Err(interface::LibraryDefinitionError::ModuleSpecificError(
+                    GeneralError::ErrorMessage(format!(
+                        "WASM Library Manager. Error setting fuel: {}",
+                        err.to_string()
+                    )),
+                ))
+            }
+        };
+        let result: Box<WASMLibrary> = Box::new(WASMLibrary {
+            path: path.clone(),
+            instance: instance,
+            store: store,
+            fuel_consumed_before_refuel: 0,
+            fuel_refueled: u64::MAX,
+            abi_version: abi_version,
+            memory64: memory64,
+            streaming: supports_streaming(&module),
+            lifecycle_hooks: has_lifecycle_hooks(&module),
+        });
+        Ok(result)
+    }
+}
+
+impl WASMLibraryManager {
+    /// Instantiates a compiled module. This runs the start function of the module
+    /// # Arguments
+    /// * `module` - compiled module
+    /// * `library_definition` - definition of the module
+    /// * `wasi` - WASI context of the instance
+    /// * `execution_limits` - deadline and cancellation flag of the instance. They are checked while the module is instantiated as well
+    /// * `fuel` - fuel the module may consume during instantiation
+    /// returns the instance and its store
+    fn instantiate(
+        &mut self,
+        module: &Module,
+        library_definition: &interface::LibraryDefinition,
+        wasi: WasiCtx,
+        execution_limits: &interface::ExecutionLimits,
+        fuel: u64,
+    ) -> Result<(Instance, Store<WASMState>), interface::LibraryDefinitionError> {
         // lets create an instance from it
         // Link WASI into the module
         let mut linker = Linker::new(&self.engine);
-        match wasmtime_wasi::add_to_linker(&mut linker, |state: &mut WASMState| &mut state.wasi) {
+        match wasmtime_wasi::preview2::preview1::add_to_linker_sync(&mut linker) {
             Ok(_x) => (),
             Err(err) => {
                 return Err(interface::LibraryDefinitionError::ModuleSpecificError(
@@ -292,36 +1429,146 @@ impl interface::LibraryManager<WASMLibrary> for WASMLibraryManager {
                 ))
             }
         };
-        let wasi = match WasiCtxBuilder::new().inherit_stdio().inherit_args() {
-            Ok(x) => x.build(),
+        // pointers and sizes of the functions provided by the host have the width of the pointers of the module
+        match add_host_functions(&mut linker, is_memory64(module)) {
+            Ok(()) => (),
             Err(err) => {
                 return Err(interface::LibraryDefinitionError::ModuleSpecificError(
                     GeneralError::ErrorMessage(format!(
-                        "WASM Library Manager. Error creating WASI context: {}",
-                        err.to_string()
+                        "WASM Library Manager. Error adding host functions to Linker: {}",
+                        err
                     )),
                 ))
             }
         };
-        let mut store = Store::new(&self.engine, WASMState { wasi: wasi });
-        match linker.module(&mut store, "", &module) {
-            Ok(_x) => (),
+        let mut store = Store::new(
+            &self.engine,
+            WASMState {
+                wasi: wasi,
+                wasi_table: ResourceTable::new(),
+                wasi_adapter: WasiPreview1Adapter::new(),
+                execution_limits: execution_limits.clone(),
+                interrupted: None,
+                resource_limiter: WASMResourceLimiter {
+                    max_memory_pages: library_definition.max_memory_pages,
+                    max_table_elements: library_definition.max_table_elements,
+                    exceeded: None,
+                },
+                state: None,
+                module_name: module_name(&library_definition.path),
+                job_id: None,
+            },
+        );
+        store.limiter(|state: &mut WASMState| &mut state.resource_limiter);
+        // check the execution limits on every epoch tick
+        store.set_epoch_deadline(1);
+        store.epoch_deadline_callback(epoch_deadline_reached);
+        match store.set_fuel(fuel) {
+            Ok(()) => (),
             Err(err) => {
                 return Err(interface::LibraryDefinitionError::ModuleSpecificError(
                     GeneralError::ErrorMessage(format!(
-                        "WASM Library Manager. Error linking WASI context: {}",
+                        "WASM Library Manager. Error setting fuel: {}",
                         err.to_string()
                     )),
                 ))
             }
         };
-        let instance: Instance = linker.instantiate(&mut store, &module).unwrap();
-        let result: Box<WASMLibrary> = Box::new(WASMLibrary {
-            path: path.clone(),
-            instance: instance,
-            store: store,
-        });
-        Ok(result)
+        // defining the module in the linker instantiates it, so this runs its start function as well
+        match linker.module(&mut store, "", module) {
+            Ok(_x) => (),
+            Err(err) => return Err(instantiation_error(&mut store, err)),
+        };
+        let instance: Instance = match linker.instantiate(&mut store, module) {
+            Ok(instance) => instance,
+            Err(err) => return Err(instantiation_error(&mut store, err)),
+        };
+        Ok((instance, store))
+    }
+}
+
+/// Converts an error during instantiation of a module into an error of the library manager
+/// # Arguments
+/// * `store` - store of the instance
+/// * `err` - error returned by wasmtime
+fn instantiation_error(
+    store: &mut Store<WASMState>,
+    err: wasmtime::Error,
+) -> interface::LibraryDefinitionError {
+    if let Some(reason) = store.data_mut().interrupted.take() {
+        return interface::LibraryDefinitionError::ExecutionInterrupted(
+            GeneralError::ErrorMessage(reason),
+        );
+    }
+    if let Some(reason) = store.data_mut().resource_limiter.exceeded.take() {
+        return interface::LibraryDefinitionError::ModuleSpecificError(GeneralError::ErrorMessage(
+            format!(
+                "WASM Library Manager. Error instantiating module: {}",
+                reason
+            ),
+        ));
+    }
+    // the start function of the module failed
+    if let Some(trap) = err.downcast_ref::<Trap>() {
+        return interface::LibraryDefinitionError::Trap(GeneralError::ErrorMessage(format!(
+            "WASM Library Manager. Module trapped during instantiation: {}",
+            trap
+        )));
+    }
+    interface::LibraryDefinitionError::ModuleSpecificError(GeneralError::ErrorMessage(format!(
+        "WASM Library Manager. Error instantiating module: {}",
+        err
+    )))
+}
+
+/// Validates that all directories preopened for a module exist on the host, so a missing directory is not only detected when a job is run
+/// # Arguments
+/// * `wasi_definition` - WASI context of the module
+fn validate_preopened_dirs(
+    wasi_definition: &interface::WasiDefinition,
+) -> Result<(), interface::LibraryDefinitionError> {
+    for preopened_dir in &wasi_definition.preopened_dirs {
+        if !std::path::Path::new(&preopened_dir.host_path).is_dir() {
+            return Err(interface::LibraryDefinitionError::ModuleSpecificError(
+                GeneralError::ErrorMessage(format!(
+                    "WASM Library Manager. Preopened directory {} does not exist",
+                    preopened_dir.host_path
+                )),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Reads the version of the ABI implemented by a module by calling the function exporting it
+/// # Arguments
+/// * `instance` - instance of the module
+/// * `store` - store of the instance. Its fuel limits the call
+/// returns the version or an error if it cannot be read
+fn read_abi_version(
+    instance: &Instance,
+    store: &mut Store<WASMState>,
+) -> Result<u32, interface::LibraryDefinitionError> {
+    let abi_version_func = match instance.get_typed_func::<(), u32>(&mut *store, ABI_VERSION_EXPORT)
+    {
+        Ok(abi_version_func) => abi_version_func,
+        Err(err) => {
+            return Err(interface::LibraryDefinitionError::ModuleSpecificError(
+                GeneralError::ErrorMessage(format!(
+                    "WASM Library Manager. Error getting {}: {}",
+                    ABI_VERSION_EXPORT, err
+                )),
+            ))
+        }
+    };
+    match abi_version_func.call(&mut *store, ()) {
+        Ok(abi_version) => Ok(abi_version),
+        Err(err) => Err(interface::LibraryDefinitionError::ModuleSpecificError(
+            GeneralError::ErrorMessage(format!(
+                "WASM Library Manager. Error calling {}: {}",
+                ABI_VERSION_EXPORT, err
+            )),
+        )),
     }
 }
 
@@ -330,6 +1577,17 @@ mod tests {
     use crate::modules::library::interface;
 
     const SIMPLE_WAT_PATH: &str = "tests/data/modules/library/wasm/simple.wat";
+    const ECHO_WAT_PATH: &str = "tests/data/modules/library/wasm/echo.wat";
+    const LOOP_WAT_PATH: &str = "tests/data/modules/library/wasm/loop.wat";
+
+    fn library_definition(path: &str) -> interface::LibraryDefinition {
+        interface::LibraryDefinition {
+            path: path.to_string(),
+            max_memory_pages: None,
+            max_table_elements: None,
+            wasi: interface::WasiDefinition::default(),
+        }
+    }
 
     #[test]
     // Test a minimal valid WASM app
@@ -338,9 +1596,12 @@ mod tests {
         use crate::modules::library::wasm::WASMLibraryManager;
         // Create a new library manager
         let mut libmgr: WASMLibraryManager =
-            crate::modules::library::wasm::WASMLibraryManager::new();
+            crate::modules::library::wasm::WASMLibraryManager::new()?;
         // try to load a test
-        let result_simple_wat_library = libmgr.get_instance(&SIMPLE_WAT_PATH.to_string());
+        let result_simple_wat_library = libmgr.get_instance(
+            &library_definition(SIMPLE_WAT_PATH),
+            &interface::ExecutionLimits::default(),
+        );
         assert_eq!(result_simple_wat_library?.path, SIMPLE_WAT_PATH);
         Ok(())
     }
@@ -353,13 +1614,572 @@ mod tests {
         use crate::modules::library::wasm::WASMLibraryManager;
         // Create a new library manager
         let mut libmgr: WASMLibraryManager =
-            crate::modules::library::wasm::WASMLibraryManager::new();
+            crate::modules::library::wasm::WASMLibraryManager::new()?;
         // try to load a test
-        let result_simple_wat_library = &mut *libmgr.get_instance(&SIMPLE_WAT_PATH.to_string())?;
-        assert_eq!(&result_simple_wat_library.path, SIMPLE_WAT_PATH);
+        let result_echo_wat_library = &mut *libmgr.get_instance(
+            &library_definition(ECHO_WAT_PATH),
+            &interface::ExecutionLimits::default(),
+        )?;
+        assert_eq!(&result_echo_wat_library.path, ECHO_WAT_PATH);
         // try to call function
-        let param: Vec<u8> = Vec::new();
-        let result_func = result_simple_wat_library.exec_func(param);
+        let metadata: Vec<u8> = vec![1u8, 2u8];
+        let data: Vec<u8> = vec![3u8, 4u8, 5u8];
+        let result_func = result_echo_wat_library.exec_func(metadata, data.clone());
+        assert_eq!(result_func.unwrap(), data);
+        Ok(())
+    }
+
+    #[test]
+    // Test calling a function of a module with 64 bit memory (memory64)
+    fn test_memory64_call() -> Result<(), interface::LibraryDefinitionError> {
+        use crate::modules::library::interface::Library;
+        use crate::modules::library::interface::LibraryManager;
+        use crate::modules::library::wasm::WASMLibraryManager;
+        let mut libmgr: WASMLibraryManager =
+            crate::modules::library::wasm::WASMLibraryManager::new()?;
+        let echo64_wat_library = &mut *libmgr.get_instance(
+            &library_definition("tests/data/modules/library/wasm/echo64.wat"),
+            &interface::ExecutionLimits::default(),
+        )?;
+        assert!(echo64_wat_library.memory64);
+        let metadata: Vec<u8> = vec![1u8, 2u8];
+        let data: Vec<u8> = vec![3u8, 4u8, 5u8];
+        let result_func = echo64_wat_library.exec_func(metadata, data.clone());
+        assert_eq!(result_func.unwrap(), data);
+        Ok(())
+    }
+
+    #[test]
+    // Test that a module keeps its state from initialization until it is finished
+    fn test_lifecycle_calls() -> Result<(), interface::LibraryDefinitionError> {
+        use crate::modules::library::interface::Library;
+        use crate::modules::library::interface::LibraryManager;
+        use crate::modules::library::wasm::WASMLibraryManager;
+        let mut libmgr: WASMLibraryManager =
+            crate::modules::library::wasm::WASMLibraryManager::new()?;
+        let lifecycle_wat_library = &mut *libmgr.get_instance(
+            &library_definition("tests/data/modules/library/wasm/lifecycle.wat"),
+            &interface::ExecutionLimits::default(),
+        )?;
+        assert!(lifecycle_wat_library.has_lifecycle_hooks());
+        lifecycle_wat_library.init(vec![1u8, 2u8, 3u8]).unwrap();
+        let data: Vec<u8> = vec![4u8, 5u8];
+        assert_eq!(
+            lifecycle_wat_library
+                .exec_func(Vec::new(), data.clone())
+                .unwrap(),
+            data
+        );
+        assert_eq!(
+            lifecycle_wat_library
+                .exec_func(Vec::new(), data.clone())
+                .unwrap(),
+            data
+        );
+        // two calls and three bytes of metadata
+        assert_eq!(
+            lifecycle_wat_library.finish().unwrap(),
+            Some(vec![2u8, 0u8, 0u8, 0u8, 3u8, 0u8, 0u8, 0u8])
+        );
+        // modules without lifecycle functions do not need to be initialized or finished
+        let echo_wat_library = &mut *libmgr.get_instance(
+            &library_definition(ECHO_WAT_PATH),
+            &interface::ExecutionLimits::default(),
+        )?;
+        assert!(!echo_wat_library.has_lifecycle_hooks());
+        assert!(echo_wat_library.init(Vec::new()).is_ok());
+        assert_eq!(echo_wat_library.finish().unwrap(), None);
+        Ok(())
+    }
+
+    #[test]
+    // Test pushing data to a module as a stream
+    fn test_stream_call() -> Result<(), interface::LibraryDefinitionError> {
+        use crate::modules::library::interface::Library;
+        use crate::modules::library::interface::LibraryManager;
+        use crate::modules::library::wasm::WASMLibraryManager;
+        let mut libmgr: WASMLibraryManager =
+            crate::modules::library::wasm::WASMLibraryManager::new()?;
+        let stream_echo_wat_library = &mut *libmgr.get_instance(
+            &library_definition("tests/data/modules/library/wasm/stream_echo.wat"),
+            &interface::ExecutionLimits::default(),
+        )?;
+        assert!(stream_echo_wat_library.supports_streaming());
+        let data: Vec<Vec<u8>> = vec![vec![1u8, 2u8], vec![3u8], vec![4u8, 5u8, 6u8]];
+        let mut output: Vec<Vec<u8>> = Vec::new();
+        stream_echo_wat_library
+            .exec_stream(
+                vec![1u8],
+                &mut data.clone().into_iter(),
+                &mut |serialized_answer| output.push(serialized_answer),
+            )
+            .unwrap();
+        assert_eq!(output, data);
+        // modules without stream functions cannot process streams
+        let echo_wat_library = &mut *libmgr.get_instance(
+            &library_definition(ECHO_WAT_PATH),
+            &interface::ExecutionLimits::default(),
+        )?;
+        assert!(!echo_wat_library.supports_streaming());
+        match echo_wat_library.exec_stream(Vec::new(), &mut std::iter::empty(), &mut |_| ()) {
+            Err(interface::LibraryInstanceError::MissingExport(_)) => (),
+            _ => panic!("Missing stream functions not detected"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    // Test that a function running endlessly is interrupted once it exceeds its deadline
+    fn test_timeout_interrupts_call() -> Result<(), interface::LibraryDefinitionError> {
+        use crate::modules::library::interface::{ExecutionLimits, Library, LibraryManager};
+        use crate::modules::library::wasm::WASMLibraryManager;
+        use std::time::{Duration, Instant};
+        let mut libmgr: WASMLibraryManager =
+            crate::modules::library::wasm::WASMLibraryManager::new()?;
+        let loop_wat_library = &mut *libmgr.get_instance(
+            &library_definition(LOOP_WAT_PATH),
+            &interface::ExecutionLimits::default(),
+        )?;
+        loop_wat_library.set_execution_limits(ExecutionLimits {
+            deadline: Some(Instant::now() + Duration::from_millis(100)),
+            cancel_requested: None,
+            fuel: None,
+        });
+        match loop_wat_library.exec_func(Vec::new(), Vec::new()) {
+            Err(interface::LibraryInstanceError::ExecutionInterrupted(_)) => (),
+            _ => panic!("Endless function not interrupted"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    // Test that a start function running endlessly is interrupted once it exceeds its deadline or is cancelled
+    fn test_execution_limits_interrupt_instantiation(
+    ) -> Result<(), interface::LibraryDefinitionError> {
+        use crate::modules::library::interface::{ExecutionLimits, LibraryManager};
+        use crate::modules::library::wasm::WASMLibraryManager;
+        use std::sync::atomic::AtomicBool;
+        use std::sync::Arc;
+        use std::time::{Duration, Instant};
+        let mut libmgr: WASMLibraryManager =
+            crate::modules::library::wasm::WASMLibraryManager::new()?;
+        let start_loop_definition =
+            library_definition("tests/data/modules/library/wasm/start_loop.wat");
+        match libmgr.get_instance(
+            &start_loop_definition,
+            &ExecutionLimits {
+                deadline: Some(Instant::now() + Duration::from_millis(100)),
+                cancel_requested: None,
+                fuel: None,
+            },
+        ) {
+            Err(interface::LibraryDefinitionError::ExecutionInterrupted(_)) => (),
+            _ => panic!("Endless start function not interrupted by its deadline"),
+        }
+        match libmgr.get_instance(
+            &start_loop_definition,
+            &ExecutionLimits {
+                deadline: None,
+                cancel_requested: Some(Arc::new(AtomicBool::new(true))),
+                fuel: None,
+            },
+        ) {
+            Err(interface::LibraryDefinitionError::ExecutionInterrupted(_)) => (),
+            _ => panic!("Endless start function not interrupted by cancelling it"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    // Test that a function running endlessly is stopped once it consumed its fuel and that the consumed fuel is measured
+    fn test_fuel_limits_call() -> Result<(), interface::LibraryDefinitionError> {
+        use crate::modules::library::interface::{ExecutionLimits, Library, LibraryManager};
+        use crate::modules::library::wasm::WASMLibraryManager;
+        let mut libmgr: WASMLibraryManager =
+            crate::modules::library::wasm::WASMLibraryManager::new()?;
+        let loop_wat_library = &mut *libmgr.get_instance(
+            &library_definition(LOOP_WAT_PATH),
+            &interface::ExecutionLimits::default(),
+        )?;
+        loop_wat_library.set_execution_limits(ExecutionLimits {
+            deadline: None,
+            cancel_requested: None,
+            fuel: Some(10_000),
+        });
+        match loop_wat_library.exec_func(Vec::new(), Vec::new()) {
+            Err(interface::LibraryInstanceError::FuelExhausted(_)) => (),
+            _ => panic!("Endless function not stopped"),
+        }
+        assert_eq!(loop_wat_library.fuel_consumed(), 10_000);
+        // fuel is refueled for each call
+        let echo_wat_library = &mut *libmgr.get_instance(
+            &library_definition(ECHO_WAT_PATH),
+            &interface::ExecutionLimits::default(),
+        )?;
+        echo_wat_library.exec_func(Vec::new(), vec![1u8]).unwrap();
+        let fuel_consumed_first_call = echo_wat_library.fuel_consumed();
+        assert!(fuel_consumed_first_call > 0);
+        echo_wat_library.exec_func(Vec::new(), vec![1u8]).unwrap();
+        assert_eq!(
+            echo_wat_library.fuel_consumed(),
+            2 * fuel_consumed_first_call
+        );
+        Ok(())
+    }
+
+    #[test]
+    // Test that a module growing its memory beyond its limit is stopped with a clear error
+    fn test_memory_limit() -> Result<(), interface::LibraryDefinitionError> {
+        use crate::modules::library::interface::{Library, LibraryManager};
+        use crate::modules::library::wasm::WASMLibraryManager;
+        let mut libmgr: WASMLibraryManager =
+            crate::modules::library::wasm::WASMLibraryManager::new()?;
+        let echo_wat_library = &mut *libmgr.get_instance(
+            &interface::LibraryDefinition {
+                path: ECHO_WAT_PATH.to_string(),
+                max_memory_pages: Some(2),
+                max_table_elements: None,
+                wasi: interface::WasiDefinition::default(),
+            },
+            &interface::ExecutionLimits::default(),
+        )?;
+        // fits into the allowed memory
+        let data: Vec<u8> = vec![1u8; 1000];
+        assert_eq!(
+            echo_wat_library
+                .exec_func(Vec::new(), data.clone())
+                .unwrap(),
+            data
+        );
+        // requires more memory than allowed
+        match echo_wat_library.exec_func(Vec::new(), vec![1u8; 3 * 65536]) {
+            Err(interface::LibraryInstanceError::ResourceLimitExceeded(_)) => (),
+            _ => panic!("Memory limit not enforced"),
+        }
+        // the initial memory of a module must be within the limit
+        match libmgr.get_instance(
+            &interface::LibraryDefinition {
+                path: ECHO_WAT_PATH.to_string(),
+                max_memory_pages: Some(0),
+                max_table_elements: None,
+                wasi: interface::WasiDefinition::default(),
+            },
+            &interface::ExecutionLimits::default(),
+        ) {
+            Err(interface::LibraryDefinitionError::ModuleSpecificError(_)) => (),
+            _ => panic!("Memory limit not enforced on instantiation"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    // Test that malformed modules return an error instead of crashing the host
+    fn test_malformed_modules() -> Result<(), interface::LibraryDefinitionError> {
+        use crate::modules::library::interface::{Library, LibraryManager};
+        use crate::modules::library::wasm::WASMLibraryManager;
+        let mut libmgr: WASMLibraryManager =
+            crate::modules::library::wasm::WASMLibraryManager::new()?;
+        let mut exec_malformed = |path: &str| {
+            libmgr
+                .get_instance(
+                    &library_definition(path),
+                    &interface::ExecutionLimits::default(),
+                )
+                .unwrap()
+                .exec_func(Vec::new(), vec![1u8])
+        };
+        match exec_malformed("tests/data/modules/library/wasm/trap.wat") {
+            Err(interface::LibraryInstanceError::Trap(
+                crate::error::error::GeneralError::ErrorMessage(message),
+            )) => {
+                assert!(message.contains("unreachable"))
+            }
+            _ => panic!("Trap not detected"),
+        }
+        match exec_malformed("tests/data/modules/library/wasm/out_of_bounds.wat") {
+            Err(interface::LibraryInstanceError::OutOfBounds(_)) => (),
+            _ => panic!("Out of bounds result not detected"),
+        }
+        match exec_malformed("tests/data/modules/library/wasm/process_error.wat") {
+            Err(interface::LibraryInstanceError::ProcessFailed(
+                crate::error::error::GeneralError::ErrorMessage(message),
+            )) => {
+                assert_eq!(message, "invalid parameter")
+            }
+            _ => panic!("Error reported by module not detected"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    // Test that modules can keep state across instances using the functions provided by the host
+    fn test_state_calls() -> Result<(), interface::LibraryDefinitionError> {
+        use crate::modules::library::interface::Library;
+        use crate::modules::library::interface::LibraryManager;
+        use crate::modules::library::wasm::WASMLibraryManager;
+        use crate::state::interface::{StateHandle, StateManager, StateStore};
+        use std::sync::{Arc, Mutex};
+        let mut libmgr: WASMLibraryManager =
+            crate::modules::library::wasm::WASMLibraryManager::new()?;
+        let state_store: StateStore = StateManager::in_memory();
+        let state = StateHandle {
+            store: Arc::new(Mutex::new(state_store)),
+            job_id: "job".to_string(),
+            namespace: "example_1/count".to_string(),
+        };
+        for expected_count in [1u8, 2u8] {
+            let state_wat_library = &mut *libmgr.get_instance(
+                &library_definition("tests/data/modules/library/wasm/state_counter.wat"),
+                &interface::ExecutionLimits::default(),
+            )?;
+            state_wat_library.set_state(state.clone());
+            assert_eq!(
+                state_wat_library.exec_func(Vec::new(), Vec::new()).unwrap(),
+                vec![expected_count]
+            );
+        }
+        assert_eq!(
+            state
+                .store
+                .lock()
+                .unwrap()
+                .get("job", "example_1/count", b"count"),
+            Some(vec![2u8])
+        );
+        // accessing the state fails if no state is available
+        let state_wat_library = &mut *libmgr.get_instance(
+            &library_definition("tests/data/modules/library/wasm/state_counter.wat"),
+            &interface::ExecutionLimits::default(),
+        )?;
+        assert!(state_wat_library.exec_func(Vec::new(), Vec::new()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    // Test that modules can log messages using the function provided by the host
+    fn test_log_calls() -> Result<(), interface::LibraryDefinitionError> {
+        use crate::modules::library::interface::Library;
+        use crate::modules::library::interface::LibraryManager;
+        use crate::modules::library::wasm::WASMLibraryManager;
+        let mut libmgr: WASMLibraryManager =
+            crate::modules::library::wasm::WASMLibraryManager::new()?;
+        let log_wat_library = &mut *libmgr.get_instance(
+            &library_definition("tests/data/modules/library/wasm/log.wat"),
+            &interface::ExecutionLimits::default(),
+        )?;
+        assert_eq!(log_wat_library.store.data().module_name, "log.wat");
+        log_wat_library.set_job_id("job".to_string());
+        log::set_logger(&CAPTURING_LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+        // logging with an invalid level fails (-1)
+        assert_eq!(
+            log_wat_library.exec_func(Vec::new(), Vec::new()).unwrap(),
+            vec![0u8, 255u8]
+        );
+        // only the message with a valid level is forwarded to the log of the host
+        let records = CAPTURING_LOGGER.records.lock().unwrap();
+        assert_eq!(
+            *records,
+            vec![(
+                log::Level::Info,
+                "zustdp_module::log.wat".to_string(),
+                "[job job] Hello from module".to_string()
+            )]
+        );
+        Ok(())
+    }
+
+    /// Logger keeping the messages of modules (level, target and message), so tests can check them
+    struct CapturingLogger {
+        records: std::sync::Mutex<Vec<(log::Level, String, String)>>,
+    }
+
+    static CAPTURING_LOGGER: CapturingLogger = CapturingLogger {
+        records: std::sync::Mutex::new(Vec::new()),
+    };
+
+    impl log::Log for CapturingLogger {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.target().starts_with("zustdp_module::")
+        }
+
+        fn log(&self, record: &log::Record) {
+            if self.enabled(record.metadata()) {
+                self.records.lock().unwrap().push((
+                    record.level(),
+                    record.target().to_string(),
+                    record.args().to_string(),
+                ));
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    // Test that modules only get the WASI context of their definition
+    fn test_wasi_sandbox() -> Result<(), interface::LibraryDefinitionError> {
+        use crate::modules::library::interface::Library;
+        use crate::modules::library::interface::LibraryManager;
+        use crate::modules::library::wasm::WASMLibraryManager;
+        let mut libmgr: WASMLibraryManager =
+            crate::modules::library::wasm::WASMLibraryManager::new()?;
+        let path = std::env::temp_dir().join(format!("zustdp-wasi-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&path).unwrap();
+        let wasi_definition = |access: interface::WasiDirAccess| interface::WasiDefinition {
+            preopened_dirs: vec![interface::WasiPreopenedDirDefinition {
+                host_path: path.to_str().unwrap().to_string(),
+                guest_path: "data".to_string(),
+                access: access,
+            }],
+            env: std::collections::HashMap::from([("ZUSTDP_ENV".to_string(), "test".to_string())]),
+            args: vec!["wasi.wat".to_string(), "--test".to_string()],
+            inherit_stdio: false,
+            inherit_stdin: false,
+        };
+        let mut wasi_wat_definition =
+            library_definition("tests/data/modules/library/wasm/wasi.wat");
+        // modules cannot create directories in read only directories
+        wasi_wat_definition.wasi = wasi_definition(interface::WasiDirAccess::ReadOnly);
+        let read_only_library = &mut *libmgr
+            .get_instance(&wasi_wat_definition, &interface::ExecutionLimits::default())?;
+        let answer = read_only_library.exec_func(Vec::new(), Vec::new()).unwrap();
+        assert_ne!(answer[0], 0u8);
+        assert_eq!(answer[1..], [1u8, 2u8]);
+        assert!(!path.join("output").exists());
+        // modules can create directories in read write directories
+        wasi_wat_definition.wasi = wasi_definition(interface::WasiDirAccess::ReadWrite);
+        let read_write_library = &mut *libmgr
+            .get_instance(&wasi_wat_definition, &interface::ExecutionLimits::default())?;
+        assert_eq!(
+            read_write_library
+                .exec_func(Vec::new(), Vec::new())
+                .unwrap(),
+            vec![0u8, 1u8, 2u8]
+        );
+        assert!(path.join("output").is_dir());
+        // without preopened directories the module has no file system
+        let no_dir_library = &mut *libmgr.get_instance(
+            &library_definition("tests/data/modules/library/wasm/wasi.wat"),
+            &interface::ExecutionLimits::default(),
+        )?;
+        assert_eq!(
+            no_dir_library.exec_func(Vec::new(), Vec::new()).unwrap()[1..],
+            [0u8, 0u8]
+        );
+        // preopened directories must exist
+        std::fs::remove_dir_all(&path).unwrap();
+        assert!(libmgr
+            .get_instance(&wasi_wat_definition, &interface::ExecutionLimits::default())
+            .is_err());
+        // modules write to stdout and stderr, but do not read from stdin unless defined
+        let default_wasi_definition: interface::WasiDefinition =
+            serde_yaml::from_str("args: []").unwrap();
+        assert!(default_wasi_definition.inherit_stdio);
+        assert!(!default_wasi_definition.inherit_stdin);
+        Ok(())
+    }
+
+    #[test]
+    // Test that modules not implementing the ABI are rejected when they are loaded
+    fn test_abi_violations() {
+        use crate::modules::library::interface::LibraryManager;
+        use crate::modules::library::wasm::WASMLibraryManager;
+        let mut libmgr: WASMLibraryManager =
+            crate::modules::library::wasm::WASMLibraryManager::new().unwrap();
+        assert!(libmgr.validate(&library_definition(ECHO_WAT_PATH)).is_ok());
+        match libmgr.get_instance(
+            &library_definition("tests/data/modules/library/wasm/missing_export.wat"),
+            &interface::ExecutionLimits::default(),
+        ) {
+            Err(interface::LibraryDefinitionError::AbiViolation(violations)) => {
+                assert_eq!(violations.len(), 3)
+            }
+            _ => panic!("Missing exports not detected"),
+        }
+        match libmgr.validate(&library_definition(
+            "tests/data/modules/library/wasm/signature_mismatch.wat",
+        )) {
+            Err(interface::LibraryDefinitionError::AbiViolation(violations)) => {
+                assert_eq!(violations.len(), 1)
+            }
+            _ => panic!("Signature mismatch not detected"),
+        }
+    }
+
+    #[test]
+    // Test that modules are validated on an instance without access to the host and imports not provided by the host are rejected
+    fn test_validate_instance() -> Result<(), interface::LibraryDefinitionError> {
+        use crate::modules::library::interface::LibraryManager;
+        use crate::modules::library::wasm::WASMLibraryManager;
+        let mut libmgr: WASMLibraryManager =
+            crate::modules::library::wasm::WASMLibraryManager::new()?;
+        // the start function traps once the module is instantiated
+        let start_trap_definition =
+            library_definition("tests/data/modules/library/wasm/start_trap.wat");
+        match libmgr.validate(&start_trap_definition) {
+            Err(interface::LibraryDefinitionError::Trap(_)) => (),
+            _ => panic!("Trap in start function not detected"),
+        }
+        assert!(libmgr
+            .get_instance(
+                &start_trap_definition,
+                &interface::ExecutionLimits::default()
+            )
+            .is_err());
+        // the version does not need to be a constant
+        libmgr.validate(&library_definition(
+            "tests/data/modules/library/wasm/abi_version_call.wat",
+        ))?;
+        // reading the version may only consume limited fuel
+        assert!(libmgr
+            .validate(&library_definition(
+                "tests/data/modules/library/wasm/abi_version_loop.wat",
+            ))
+            .is_err());
+        // the preopened directories must exist, but are not opened when validating the module
+        let mut wasi_definition = library_definition("tests/data/modules/library/wasm/wasi.wat");
+        wasi_definition.wasi.preopened_dirs = vec![interface::WasiPreopenedDirDefinition {
+            host_path: "tests/data".to_string(),
+            guest_path: "data".to_string(),
+            access: interface::WasiDirAccess::ReadOnly,
+        }];
+        libmgr.validate(&wasi_definition)?;
+        wasi_definition.wasi.preopened_dirs[0].host_path = "tests/data/missing".to_string();
+        match libmgr.validate(&wasi_definition) {
+            Err(interface::LibraryDefinitionError::ModuleSpecificError(_)) => (),
+            _ => panic!("Missing preopened directory not detected"),
+        }
+        match libmgr.validate(&library_definition(
+            "tests/data/modules/library/wasm/unknown_import.wat",
+        )) {
+            Err(interface::LibraryDefinitionError::AbiViolation(violations)) => {
+                assert_eq!(violations.len(), 2)
+            }
+            _ => panic!("Imports not provided by the host not detected"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    // Test that modules implementing an unsupported version of the ABI are rejected
+    fn test_unsupported_abi_version() -> Result<(), interface::LibraryDefinitionError> {
+        use crate::modules::library::interface::LibraryManager;
+        use crate::modules::library::wasm::WASMLibraryManager;
+        let mut libmgr: WASMLibraryManager =
+            crate::modules::library::wasm::WASMLibraryManager::new()?;
+        assert!(libmgr
+            .get_instance(
+                &library_definition(SIMPLE_WAT_PATH),
+                &interface::ExecutionLimits::default()
+            )
+            .is_ok());
+        match libmgr.validate(&library_definition(
+            "tests/data/modules/library/wasm/unsupported_abi_version.wat",
+        )) {
+            Err(interface::LibraryDefinitionError::UnsupportedAbiVersion(_)) => (),
+            _ => panic!("Unsupported ABI version not detected"),
+        }
         Ok(())
     }
 }
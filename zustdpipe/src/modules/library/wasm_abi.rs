@@ -0,0 +1,403 @@
+//! Interface (ABI) between the host and WASM modules. A module must export its memory and the functions the host calls to exchange data with it.
+//! A module reports the version of the ABI it implements, so the interface can evolve without the host misreading the data returned by a module.
+//!
+//! Versions:
+//! * 1 - the raw process entry returns a pointer to two little endian u32: pointer to and length of the result in Arrow IPC format
+//! * 2 - the raw process entry returns a pointer to two little endian u64: pointer to and length of the result in Arrow IPC format. The layout does not depend on the width of pointers of the module
+//!
+//! A module may additionally export `zustdp_module_wasm_last_error`. If the raw process entry returns 0, then the host calls it to get the reason. It returns 0 or a pointer to a result descriptor of the error message (UTF-8)
+//!
+//! A module may additionally export lifecycle functions, so it can keep state while processing all data of a job (e.g. aggregations). The host keeps the instance alive across all calls in between:
+//! * `zustdp_module_init(meta_ptr, meta_len) -> i32` - called once before the first data is processed with the metadata (parameters) in Arrow IPC format. Returns 0 on success
+//! * `zustdp_module_finish() -> ptr` - called once after all data has been processed. Returns a result descriptor of the final output (Arrow IPC stream) or 0 if there is none
+//!
+//! A module may additionally export the stream functions to process data batch by batch, so memory stays bounded for large inputs. It must export either all or none of them:
+//! * `zustdp_module_wasm_stream_start(meta_ptr, meta_len) -> i32` - starts a stream with the given metadata (parameters) in Arrow IPC format. Returns 0 on success
+//! * `zustdp_module_wasm_stream_push(data_ptr, data_len) -> i32` - pushes an Arrow IPC stream containing one record batch. Returns 0 if accepted, 1 if the module has pending output that must be pulled first (back-pressure) and -1 on error
+//! * `zustdp_module_wasm_stream_pull() -> ptr` - returns a result descriptor of the next pending output (Arrow IPC stream) or 0 if there is none
+//! * `zustdp_module_wasm_stream_end() -> i32` - signals that all data has been pushed. Pending output can be pulled afterwards. Returns 0 on success
+//! The host pulls all pending output after each push, so the module does not need to buffer more than the output of one record batch
+//!
+//! The host provides functions a module may import from the module `zustdp_host`:
+//! * `zustdp_host_state_get(key_ptr, key_len) -> ptr` - returns a result descriptor (two little endian u64) of the value of a key in the state of the step or 0 if the key does not exist. Value and result descriptor are allocated using `zustdp_module_wasm_allocate` and must be deallocated by the module
+//! * `zustdp_host_state_put(key_ptr, key_len, value_ptr, value_len) -> i32` - sets the value of a key in the state of the step. Returns 0 on success
+//! * `zustdp_host_state_delete(key_ptr, key_len) -> i32` - deletes a key from the state of the step. Returns 0 on success
+//! * `zustdp_host_log(level, message_ptr, message_len) -> i32` - logs a message (UTF-8) with the name of the module and the id of the job as context. The level is an i32: 1 error, 2 warn, 3 info, 4 debug, 5 trace. Returns 0 on success
+//!
+//! Pointers and sizes passed to and returned by the functions of a module are i32 for modules with 32 bit memory and i64 for modules with 64 bit memory (memory64). 64 bit memory requires version 2
+
+use wasmtime::{ExternType, Module, ValType};
+
+use crate::error::error::GeneralError;
+
+/// Name of the exported memory of a module
+pub const MEMORY_EXPORT: &str = "memory";
+/// Name of the exported function returning the version of the ABI implemented by a module
+pub const ABI_VERSION_EXPORT: &str = "zustdp_module_abi_version";
+/// Name of the optional exported function returning the error of the last failed call of the raw process entry
+pub const LAST_ERROR_EXPORT: &str = "zustdp_module_wasm_last_error";
+/// Names of the optional exported lifecycle functions
+pub const INIT_EXPORT: &str = "zustdp_module_init";
+pub const FINISH_EXPORT: &str = "zustdp_module_finish";
+/// Names of the optional exported functions to process data as a stream
+pub const STREAM_START_EXPORT: &str = "zustdp_module_wasm_stream_start";
+pub const STREAM_PUSH_EXPORT: &str = "zustdp_module_wasm_stream_push";
+pub const STREAM_PULL_EXPORT: &str = "zustdp_module_wasm_stream_pull";
+pub const STREAM_END_EXPORT: &str = "zustdp_module_wasm_stream_end";
+/// Name of the module containing the functions provided by the host
+pub const HOST_MODULE: &str = "zustdp_host";
+/// Name of the module containing the WASI functions provided by the host
+pub const WASI_MODULE: &str = "wasi_snapshot_preview1";
+/// Names of the functions provided by the host to access the state of a step
+pub const STATE_GET_IMPORT: &str = "zustdp_host_state_get";
+pub const STATE_PUT_IMPORT: &str = "zustdp_host_state_put";
+pub const STATE_DELETE_IMPORT: &str = "zustdp_host_state_delete";
+/// Name of the function provided by the host to log messages
+pub const LOG_IMPORT: &str = "zustdp_host_log";
+/// Status code of a function provided by the host if it failed
+pub const STATUS_ERROR: i32 = -1;
+/// Name of the exported function allocating memory in a module
+pub const ALLOCATE_EXPORT: &str = "zustdp_module_wasm_allocate";
+/// Name of the exported function deallocating memory in a module
+pub const DEALLOCATE_EXPORT: &str = "zustdp_module_wasm_deallocate";
+/// Name of the exported raw process entry of a module
+pub const RAW_PROCESS_ENTRY_EXPORT: &str = "zustdp_module_wasm_raw_process_entry";
+/// Status code of a function if it was successful
+pub const STATUS_OK: i32 = 0;
+/// Status code of the stream push function if the module has pending output that must be pulled first
+pub const STREAM_STATUS_OUTPUT_PENDING: i32 = 1;
+/// Versions of the ABI supported by the host
+pub const SUPPORTED_ABI_VERSIONS: [u32; 2] = [1, 2];
+
+/// Returns the functions a module must export: name, parameter types and result types
+/// # Arguments
+/// * `pointer` - type of pointers and sizes, i32 for 32 bit memory and i64 for 64 bit memory
+fn required_functions(pointer: ValType) -> Vec<(&'static str, Vec<ValType>, Vec<ValType>)> {
+    vec![
+        (ABI_VERSION_EXPORT, vec![], vec![ValType::I32]),
+        (
+            ALLOCATE_EXPORT,
+            vec![pointer.clone()],
+            vec![pointer.clone()],
+        ),
+        (DEALLOCATE_EXPORT, vec![pointer.clone()], vec![ValType::I32]),
+        (
+            RAW_PROCESS_ENTRY_EXPORT,
+            vec![
+                pointer.clone(),
+                pointer.clone(),
+                pointer.clone(),
+                pointer.clone(),
+            ],
+            vec![pointer],
+        ),
+    ]
+}
+
+/// Returns the functions a module may export: name, parameter types and result types
+/// # Arguments
+/// * `pointer` - type of pointers and sizes, i32 for 32 bit memory and i64 for 64 bit memory
+fn optional_functions(pointer: ValType) -> Vec<(&'static str, Vec<ValType>, Vec<ValType>)> {
+    vec![
+        (LAST_ERROR_EXPORT, vec![], vec![pointer.clone()]),
+        (
+            INIT_EXPORT,
+            vec![pointer.clone(), pointer.clone()],
+            vec![ValType::I32],
+        ),
+        (FINISH_EXPORT, vec![], vec![pointer.clone()]),
+        (
+            STREAM_START_EXPORT,
+            vec![pointer.clone(), pointer.clone()],
+            vec![ValType::I32],
+        ),
+        (
+            STREAM_PUSH_EXPORT,
+            vec![pointer.clone(), pointer.clone()],
+            vec![ValType::I32],
+        ),
+        (STREAM_PULL_EXPORT, vec![], vec![pointer]),
+        (STREAM_END_EXPORT, vec![], vec![ValType::I32]),
+    ]
+}
+
+/// Returns the functions the host provides to modules: name, parameter types and result types
+/// # Arguments
+/// * `pointer` - type of pointers and sizes, i32 for 32 bit memory and i64 for 64 bit memory
+fn host_functions(pointer: ValType) -> Vec<(&'static str, Vec<ValType>, Vec<ValType>)> {
+    vec![
+        (
+            STATE_GET_IMPORT,
+            vec![pointer.clone(), pointer.clone()],
+            vec![pointer.clone()],
+        ),
+        (
+            STATE_PUT_IMPORT,
+            vec![
+                pointer.clone(),
+                pointer.clone(),
+                pointer.clone(),
+                pointer.clone(),
+            ],
+            vec![ValType::I32],
+        ),
+        (
+            STATE_DELETE_IMPORT,
+            vec![pointer.clone(), pointer.clone()],
+            vec![ValType::I32],
+        ),
+        (
+            LOG_IMPORT,
+            vec![ValType::I32, pointer.clone(), pointer],
+            vec![ValType::I32],
+        ),
+    ]
+}
+
+/// Checks if a module uses 64 bit memory. Pointers and sizes are 64 bit then
+/// # Arguments
+/// * `module` - compiled module
+pub fn is_memory64(module: &Module) -> bool {
+    match module.get_export(MEMORY_EXPORT) {
+        Some(ExternType::Memory(memory_type)) => memory_type.is_64(),
+        _ => false,
+    }
+}
+
+/// Compares the signature of a function with the signature required by the host
+/// # Arguments
+/// * `name` - name of the function
+/// * `actual_params` - parameter types of the function
+/// * `actual_results` - result types of the function
+/// * `params` - required parameter types
+/// * `results` - required result types
+/// returns a violation if the signatures differ
+fn validate_signature(
+    name: &str,
+    actual_params: impl Iterator<Item = ValType>,
+    actual_results: impl Iterator<Item = ValType>,
+    params: &[ValType],
+    results: &[ValType],
+) -> Option<GeneralError> {
+    let actual_params: Vec<String> = actual_params.map(|param| param.to_string()).collect();
+    let actual_results: Vec<String> = actual_results.map(|result| result.to_string()).collect();
+    let expected_params: Vec<String> = params.iter().map(|param| param.to_string()).collect();
+    let expected_results: Vec<String> = results.iter().map(|result| result.to_string()).collect();
+    if actual_params != expected_params || actual_results != expected_results {
+        return Some(GeneralError::ErrorMessage(format!(
+            "Function `{}` has signature ({}) -> ({}), but ({}) -> ({}) is required",
+            name,
+            actual_params.join(", "),
+            actual_results.join(", "),
+            expected_params.join(", "),
+            expected_results.join(", ")
+        )));
+    }
+    None
+}
+
+/// Validates that a module exports everything the host requires with the right signatures
+/// # Arguments
+/// * `module` - compiled module
+/// returns all violations of the ABI. The module is valid if there are none
+pub fn validate_module_abi(module: &Module) -> Vec<GeneralError> {
+    let mut violations: Vec<GeneralError> = Vec::new();
+    let mut pointer = ValType::I32;
+    match module.get_export(MEMORY_EXPORT) {
+        Some(ExternType::Memory(memory_type)) => {
+            if memory_type.is_64() {
+                pointer = ValType::I64;
+            }
+        }
+        Some(_extern_type) => violations.push(GeneralError::ErrorMessage(format!(
+            "Export `{}` is not a memory",
+            MEMORY_EXPORT
+        ))),
+        None => violations.push(GeneralError::ErrorMessage(format!(
+            "Memory `{}` is not exported",
+            MEMORY_EXPORT
+        ))),
+    };
+    let functions = required_functions(pointer.clone())
+        .into_iter()
+        .map(|function| (function, true))
+        .chain(
+            optional_functions(pointer)
+                .into_iter()
+                .map(|function| (function, false)),
+        );
+    for ((name, params, results), required) in functions {
+        match module.get_export(name) {
+            Some(ExternType::Func(func_type)) => violations.extend(validate_signature(
+                name,
+                func_type.params(),
+                func_type.results(),
+                &params,
+                &results,
+            )),
+            Some(_extern_type) => violations.push(GeneralError::ErrorMessage(format!(
+                "Export `{}` is not a function",
+                name
+            ))),
+            None => {
+                if required {
+                    violations.push(GeneralError::ErrorMessage(format!(
+                        "Function `{}` is not exported",
+                        name
+                    )))
+                }
+            }
+        };
+    }
+    let stream_functions = [
+        STREAM_START_EXPORT,
+        STREAM_PUSH_EXPORT,
+        STREAM_PULL_EXPORT,
+        STREAM_END_EXPORT,
+    ];
+    let exported_stream_functions = stream_functions
+        .iter()
+        .filter(|name| module.get_export(name).is_some())
+        .count();
+    if exported_stream_functions > 0 && exported_stream_functions < stream_functions.len() {
+        violations.push(GeneralError::ErrorMessage(format!(
+            "Module exports only some of the stream functions {}",
+            stream_functions.join(", ")
+        )))
+    }
+    violations
+}
+
+/// Validates that a module only imports functions the host provides with the right signatures
+/// # Arguments
+/// * `module` - compiled module
+/// returns all imports the host cannot provide. WASI functions are provided by the WASI implementation of the host and are not checked
+pub fn validate_module_imports(module: &Module) -> Vec<GeneralError> {
+    let mut violations: Vec<GeneralError> = Vec::new();
+    let pointer = if is_memory64(module) {
+        ValType::I64
+    } else {
+        ValType::I32
+    };
+    let host_functions = host_functions(pointer);
+    for import in module.imports() {
+        match import.module() {
+            WASI_MODULE => (),
+            HOST_MODULE => {
+                match (
+                    host_functions
+                        .iter()
+                        .find(|(name, _params, _results)| *name == import.name()),
+                    import.ty(),
+                ) {
+                    (Some((name, params, results)), ExternType::Func(func_type)) => violations
+                        .extend(validate_signature(
+                            name,
+                            func_type.params(),
+                            func_type.results(),
+                            params,
+                            results,
+                        )),
+                    (Some(_host_function), _extern_type) => {
+                        violations.push(GeneralError::ErrorMessage(format!(
+                            "Import `{}` is not a function",
+                            import.name()
+                        )))
+                    }
+                    (None, _extern_type) => violations.push(GeneralError::ErrorMessage(format!(
+                        "Function `{}` is not provided by the host",
+                        import.name()
+                    ))),
+                };
+            }
+            other_module => violations.push(GeneralError::ErrorMessage(format!(
+                "Import `{}` of module `{}` is not provided by the host",
+                import.name(),
+                other_module
+            ))),
+        };
+    }
+    violations
+}
+
+/// Validates that the host supports the version of the ABI implemented by a module
+/// # Arguments
+/// * `abi_version` - version of the ABI implemented by the module
+/// * `memory64` - if the module uses 64 bit memory
+/// returns an error if the version is not supported
+pub fn validate_abi_version(abi_version: u32, memory64: bool) -> Option<GeneralError> {
+    // version 1 only supports 32 bit pointers in the result descriptor
+    if !SUPPORTED_ABI_VERSIONS.contains(&abi_version) || (memory64 && abi_version < 2) {
+        return Some(GeneralError::ErrorMessage(format!(
+            "WASM Library Manager. Module implements ABI version {} (64 bit memory: {}), but only versions {:?} (64 bit memory: from version 2) are supported",
+            abi_version, memory64, SUPPORTED_ABI_VERSIONS
+        )));
+    }
+    None
+}
+
+/// Checks if a module exports an init or finish function
+/// # Arguments
+/// * `module` - compiled module
+/// returns true if the module keeps state from its initialization until it is finished
+pub fn has_lifecycle_hooks(module: &Module) -> bool {
+    module.get_export(INIT_EXPORT).is_some() || module.get_export(FINISH_EXPORT).is_some()
+}
+
+/// Checks if a module exports the stream functions
+/// # Arguments
+/// * `module` - compiled module
+/// returns true if the module can process data as a stream
+pub fn supports_streaming(module: &Module) -> bool {
+    module.get_export(STREAM_START_EXPORT).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use wasmtime::{Config, Engine, Module};
+
+    #[test]
+    // Test that all violations of the ABI are reported at once
+    fn test_validate_module_abi() {
+        let mut config = Config::new();
+        config.wasm_memory64(true);
+        let engine = Engine::new(&config).unwrap();
+        let echo_module =
+            Module::from_file(&engine, "tests/data/modules/library/wasm/echo.wat").unwrap();
+        assert!(super::validate_module_abi(&echo_module).is_empty());
+        let missing_export_module = Module::from_file(
+            &engine,
+            "tests/data/modules/library/wasm/missing_export.wat",
+        )
+        .unwrap();
+        assert_eq!(super::validate_module_abi(&missing_export_module).len(), 3);
+        let simple_module = Module::new(
+            &engine,
+            "(module (func (export \"zustdp_module_wasm_raw_process_entry\") (result i64) i64.const 1))",
+        )
+        .unwrap();
+        let violations = super::validate_module_abi(&simple_module);
+        assert_eq!(violations.len(), 5);
+        let crate::error::error::GeneralError::ErrorMessage(message) = &violations[0];
+        assert!(message.contains("memory"));
+        let crate::error::error::GeneralError::ErrorMessage(message) = &violations[4];
+        assert!(message.contains("() -> (i64)"));
+        // pointers are 64 bit for modules with 64 bit memory
+        let echo64_module =
+            Module::from_file(&engine, "tests/data/modules/library/wasm/echo64.wat").unwrap();
+        assert!(super::validate_module_abi(&echo64_module).is_empty());
+        // either all or none of the stream functions must be exported
+        let stream_echo_module =
+            Module::from_file(&engine, "tests/data/modules/library/wasm/stream_echo.wat").unwrap();
+        assert!(super::validate_module_abi(&stream_echo_module).is_empty());
+        assert!(super::supports_streaming(&stream_echo_module));
+        let partial_stream_module = Module::new(
+            &engine,
+            "(module (func (export \"zustdp_module_wasm_stream_end\") (result i32) i32.const 0))",
+        )
+        .unwrap();
+        let violations = super::validate_module_abi(&partial_stream_module);
+        let crate::error::error::GeneralError::ErrorMessage(message) = violations.last().unwrap();
+        assert!(message.contains("only some of the stream functions"));
+    }
+}
@@ -1,10 +1,13 @@
+use std::collections::HashMap;
 use std::path::Path;
 
 use super::interface::{
-    ModuleDefinitionError, ModuleManager, ModuleManagerList, ModuleType, ModulesDefinition,
+    ModuleDefinitionError, ModuleManager, ModuleManagerList, ModuleManifest, ModuleType,
+    ModulesDefinition,
 };
-use super::library::interface::{Library, LibraryManager};
+use super::library::interface::{ExecutionLimits, Library, LibraryDefinition, LibraryManager};
 use super::library::wasm::WASMLibraryManager;
+use super::manifest::{read_module_manifest, validate_parameters};
 use crate::error::error::GeneralError;
 use crate::pipeline::interface::ProcessDefinition;
 
@@ -16,7 +19,7 @@ impl ModuleManager for ModuleManagerList {
     /// ```
     /// use zustdpipe::modules::interface;
     /// let wasm_module_path = vec!(".".to_string());
-    /// let modules_definition = zustdpipe::modules::interface::ModulesDefinition {wasm: Some((interface::WasmModulesDefinition {module_path_base: wasm_module_path}))};
+    /// let modules_definition = zustdpipe::modules::interface::ModulesDefinition {wasm: Some((interface::WasmModulesDefinition {module_path_base: wasm_module_path, modules: std::collections::HashMap::new()}))};
     /// let mut modulemgr: interface::ModuleManagerList = interface::ModuleManager::new(&modules_definition).expect("Wrong module definition");
     /// ```
     fn new(
@@ -28,33 +31,115 @@ impl ModuleManager for ModuleManagerList {
         };
         // load wasm modules
         let wasm_library_manager = match &modules_definition.wasm {
-            Some(_wasm) => Some(WASMLibraryManager::new()),
+            Some(_wasm) => match WASMLibraryManager::new() {
+                Ok(wasm_library_manager) => Some(wasm_library_manager),
+                Err(error) => return Err(ModuleDefinitionError::ModuleCannotBeInstantiated(error)),
+            },
             None => None,
         };
+        let wasm_module_definitions = match &modules_definition.wasm {
+            Some(wasm) => wasm.modules.clone(),
+            None => HashMap::new(),
+        };
 
         Ok(ModuleManagerList {
             wasm_library_manager: wasm_library_manager,
             module_paths: module_paths,
+            wasm_module_definitions: wasm_module_definitions,
+            manifests: HashMap::new(),
         })
     }
 
     fn get_module_instance(
         &mut self,
         process_definition: &ProcessDefinition,
+        execution_limits: &ExecutionLimits,
     ) -> Result<Box<dyn Library>, ModuleDefinitionError> {
-        let library_manager = match &process_definition.module.r#type {
-            ModuleType::Wasm => match &mut self.wasm_library_manager {
-                Some(library_manager) => library_manager,
-                None => {
-                    return Err(ModuleDefinitionError::ModuleTypeNotFound(
-                        GeneralError::ErrorMessage(format!(
-                            "No library manager found for {:?}",
-                            &process_definition.module.r#type
-                        )),
-                    ))
+        let library_definition = self.library_definition(process_definition)?;
+        let library_manager = self.library_manager(process_definition)?;
+        match library_manager.get_instance(&library_definition, execution_limits) {
+            Ok(module_instance) => Ok(module_instance),
+            Err(error) => Err(ModuleDefinitionError::ModuleCannotBeInstantiated(
+                error.clone(),
+            )),
+        }
+    }
+
+    /// Validates that the module of a process can be found and implements the interface expected by the host
+    ///
+    /// # Arguments
+    /// * `process_definition` - definition of the process
+    ///
+    fn validate_module(
+        &mut self,
+        process_definition: &ProcessDefinition,
+    ) -> Result<(), ModuleDefinitionError> {
+        let library_definition = self.library_definition(process_definition)?;
+        let library_manager = self.library_manager(process_definition)?;
+        match library_manager.validate(&library_definition) {
+            Ok(()) => (),
+            Err(error) => return Err(ModuleDefinitionError::ModuleCannotBeInstantiated(error)),
+        };
+        // modules without manifest accept any parameters
+        match self.get_module_manifest(process_definition)? {
+            Some(module_manifest) => {
+                let violations = validate_parameters(&module_manifest, process_definition);
+                if violations.is_empty() {
+                    Ok(())
+                } else {
+                    Err(ModuleDefinitionError::ProcessParametersInvalid(violations))
                 }
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the manifest of the module of a process. The manifest is read only once
+    ///
+    /// # Arguments
+    /// * `process_definition` - definition of the process
+    ///
+    /// returns None if the module does not have a manifest
+    fn get_module_manifest(
+        &mut self,
+        process_definition: &ProcessDefinition,
+    ) -> Result<Option<ModuleManifest>, ModuleDefinitionError> {
+        let module_full_path =
+            find_module_in_module_paths(&self.module_paths, &process_definition.module.name)?;
+        if let Some(module_manifest) = self.manifests.get(&module_full_path) {
+            return Ok(module_manifest.clone());
+        }
+        let module_manifest = read_module_manifest(&module_full_path)?;
+        self.manifests
+            .insert(module_full_path, module_manifest.clone());
+        Ok(module_manifest)
+    }
+}
+
+impl ModuleManagerList {
+    /// Returns the library manager for the module of a process
+    fn library_manager(
+        &mut self,
+        process_definition: &ProcessDefinition,
+    ) -> Result<&mut WASMLibraryManager, ModuleDefinitionError> {
+        match &process_definition.module.r#type {
+            ModuleType::Wasm => match &mut self.wasm_library_manager {
+                Some(library_manager) => Ok(library_manager),
+                None => Err(ModuleDefinitionError::ModuleTypeNotFound(
+                    GeneralError::ErrorMessage(format!(
+                        "No library manager found for {:?}",
+                        &process_definition.module.r#type
+                    )),
+                )),
             },
-        };
+        }
+    }
+
+    /// Returns the definition of the library implementing the module of a process
+    fn library_definition(
+        &self,
+        process_definition: &ProcessDefinition,
+    ) -> Result<LibraryDefinition, ModuleDefinitionError> {
         let module_full_path = match find_module_in_module_paths(
             &self.module_paths,
             &process_definition.module.name,
@@ -62,11 +147,33 @@ impl ModuleManager for ModuleManagerList {
             Ok(module_full_path) => module_full_path,
             Err(error) => return Err(error),
         };
-        match library_manager.get_instance(&module_full_path) {
-            Ok(module_instance) => Ok(module_instance),
-            Err(error) => Err(ModuleDefinitionError::ModuleCannotBeInstantiated(
-                error.clone(),
-            )),
+        let wasm_module_definition = self
+            .wasm_module_definitions
+            .get(&process_definition.module.name)
+            .cloned()
+            .unwrap_or_default();
+        Ok(LibraryDefinition {
+            path: module_full_path,
+            max_memory_pages: wasm_module_definition.max_memory_pages,
+            max_table_elements: wasm_module_definition.max_table_elements,
+            wasi: wasm_module_definition.wasi,
+        })
+    }
+
+    /// Returns the maximum fuel a single call of the module of a process may consume
+    /// The limit of the process takes precedence over the limit of the module
+    /// # Arguments
+    /// * `process_definition` - definition of the process
+    /// returns None if the fuel is not limited
+    pub(crate) fn fuel_limit(&self, process_definition: &ProcessDefinition) -> Option<u64> {
+        match process_definition.fuel {
+            Some(fuel) => Some(fuel),
+            None => match process_definition.module.r#type {
+                ModuleType::Wasm => self
+                    .wasm_module_definitions
+                    .get(&process_definition.module.name)
+                    .and_then(|wasm_module_definition| wasm_module_definition.fuel),
+            },
         }
     }
 }
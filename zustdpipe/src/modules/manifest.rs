@@ -0,0 +1,367 @@
+//! Reads the manifest of a module and checks the definition of processes against it.
+//! The manifest is a YAML document. It is read from the sidecar file <module>.manifest.yml if it exists. Otherwise it is read from the custom section "zustdp_manifest" of a WASM module.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use wasmparser::{Parser, Payload};
+
+use crate::error::error::GeneralError;
+use crate::pipeline::interface::ProcessDefinition;
+
+use super::interface::{ModuleDefinitionError, ModuleManifest};
+
+/// Name of the custom section of a WASM module containing the manifest
+pub const MANIFEST_SECTION_NAME: &str = "zustdp_manifest";
+/// Suffix of the sidecar file containing the manifest of a module
+pub const MANIFEST_FILE_SUFFIX: &str = ".manifest.yml";
+
+/// Reads the manifest of a module
+/// # Arguments
+/// * `module_path` - path to the module
+/// returns the manifest or None if the module does not have a manifest
+pub fn read_module_manifest(
+    module_path: &str,
+) -> Result<Option<ModuleManifest>, ModuleDefinitionError> {
+    let sidecar_path = format!("{}{}", module_path, MANIFEST_FILE_SUFFIX);
+    if Path::new(&sidecar_path).exists() {
+        let manifest_content = match fs::read(&sidecar_path) {
+            Ok(manifest_content) => manifest_content,
+            Err(err) => {
+                return Err(ModuleDefinitionError::ModuleManifestInvalid(
+                    GeneralError::ErrorMessage(format!(
+                        "Cannot read manifest {}: {}",
+                        sidecar_path, err
+                    )),
+                ))
+            }
+        };
+        return parse_module_manifest(&manifest_content).map(Some);
+    }
+    let module_content = match fs::read(module_path) {
+        Ok(module_content) => module_content,
+        Err(err) => {
+            return Err(ModuleDefinitionError::ModuleManifestInvalid(
+                GeneralError::ErrorMessage(format!("Cannot read module {}: {}", module_path, err)),
+            ))
+        }
+    };
+    match manifest_section(&module_content)? {
+        Some(manifest_content) => parse_module_manifest(manifest_content).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Returns the content of the manifest section of a WASM module
+/// # Arguments
+/// * `module_content` - binary WASM module. Modules in text format do not have custom sections
+/// returns None if the module does not contain a manifest section
+fn manifest_section(module_content: &[u8]) -> Result<Option<&[u8]>, ModuleDefinitionError> {
+    if !wasmparser::Parser::is_core_wasm(module_content) {
+        return Ok(None);
+    }
+    for payload in Parser::new(0).parse_all(module_content) {
+        match payload {
+            Ok(Payload::CustomSection(reader)) if reader.name() == MANIFEST_SECTION_NAME => {
+                return Ok(Some(reader.data()))
+            }
+            Ok(_payload) => (),
+            Err(err) => {
+                return Err(ModuleDefinitionError::ModuleManifestInvalid(
+                    GeneralError::ErrorMessage(format!("Cannot parse module: {}", err)),
+                ))
+            }
+        };
+    }
+    Ok(None)
+}
+
+/// Parses a manifest in YAML format
+fn parse_module_manifest(manifest_content: &[u8]) -> Result<ModuleManifest, ModuleDefinitionError> {
+    match serde_yaml::from_slice(manifest_content) {
+        Ok(module_manifest) => Ok(module_manifest),
+        Err(err) => Err(ModuleDefinitionError::ModuleManifestInvalid(
+            GeneralError::ErrorMessage(format!("Cannot parse manifest: {}", err)),
+        )),
+    }
+}
+
+/// Validates the parameters of a process against the parameters declared in the manifest of its module
+/// # Arguments
+/// * `module_manifest` - manifest of the module
+/// * `process_definition` - definition of the process
+/// returns all parameters that are not declared and all required parameters that are missing
+pub fn validate_parameters(
+    module_manifest: &ModuleManifest,
+    process_definition: &ProcessDefinition,
+) -> Vec<GeneralError> {
+    let mut violations: Vec<GeneralError> = Vec::new();
+    let declared_parameters: HashSet<&String> = module_manifest
+        .parameters
+        .iter()
+        .map(|parameter| &parameter.name)
+        .collect();
+    let defined_parameters: HashSet<&String> = process_definition
+        .parameters
+        .iter()
+        .flat_map(|parameter| parameter.keys())
+        .collect();
+    for parameter in process_definition
+        .parameters
+        .iter()
+        .flat_map(|parameter| parameter.keys())
+    {
+        if !declared_parameters.contains(parameter) {
+            violations.push(GeneralError::ErrorMessage(format!(
+                "Parameter \"{}\" is not accepted by module {} {}",
+                parameter, module_manifest.name, module_manifest.version
+            )));
+        }
+    }
+    for parameter in &module_manifest.parameters {
+        if parameter.required && !defined_parameters.contains(&parameter.name) {
+            violations.push(GeneralError::ErrorMessage(format!(
+                "Parameter \"{}\" is required by module {} {}",
+                parameter.name, module_manifest.name, module_manifest.version
+            )));
+        }
+    }
+    violations
+}
+
+/// Validates that the output of a process can be the input of another process
+/// Each field of the input schema must be in the output schema with the same data type. A field that is not nullable in the input schema must not be nullable in the output schema
+/// # Arguments
+/// * `output_process` - name of the process providing the input
+/// * `output_manifest` - manifest of the module of the process providing the input
+/// * `input_process` - name of the process receiving the input
+/// * `input_manifest` - manifest of the module of the process receiving the input
+/// returns all incompatibilities. Schemas not declared in a manifest are not checked
+pub fn validate_schema_compatibility(
+    output_process: &str,
+    output_manifest: &ModuleManifest,
+    input_process: &str,
+    input_manifest: &ModuleManifest,
+) -> Vec<GeneralError> {
+    let mut violations: Vec<GeneralError> = Vec::new();
+    if output_manifest.output_schema.is_empty() || input_manifest.input_schema.is_empty() {
+        return violations;
+    }
+    for input_field in &input_manifest.input_schema {
+        match output_manifest
+            .output_schema
+            .iter()
+            .find(|output_field| output_field.name == input_field.name)
+        {
+            Some(output_field) => {
+                if output_field.data_type != input_field.data_type {
+                    violations.push(GeneralError::ErrorMessage(format!(
+                        "Field \"{}\" is {} in the output of process \"{}\", but process \"{}\" expects {}",
+                        input_field.name,
+                        output_field.data_type,
+                        output_process,
+                        input_process,
+                        input_field.data_type
+                    )));
+                } else if output_field.nullable && !input_field.nullable {
+                    violations.push(GeneralError::ErrorMessage(format!(
+                        "Field \"{}\" is nullable in the output of process \"{}\", but process \"{}\" does not accept null values",
+                        input_field.name, output_process, input_process
+                    )));
+                }
+            }
+            None => violations.push(GeneralError::ErrorMessage(format!(
+                "Field \"{}\" expected by process \"{}\" is not in the output of process \"{}\"",
+                input_field.name, input_process, output_process
+            ))),
+        };
+    }
+    violations
+}
+
+/// Serializes the data type of a field declared in a manifest by its name, e.g. Utf8, Int64 or Timestamp(Millisecond, None)
+pub mod arrow_data_type {
+    use arrow::datatypes::{DataType, TimeUnit};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        data_type: &DataType,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&data_type.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DataType, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        match parse_data_type(&name) {
+            Some(data_type) => Ok(data_type),
+            None => Err(serde::de::Error::custom(format!(
+                "Unknown Arrow data type \"{}\"",
+                name
+            ))),
+        }
+    }
+
+    /// Parses the name of an Arrow data type as written by its Display implementation. Nested data types are not supported
+    /// # Arguments
+    /// * `name` - name of the data type, e.g. Utf8
+    /// returns None if the name is not a supported data type
+    pub fn parse_data_type(name: &str) -> Option<DataType> {
+        let data_type = match name {
+            "Null" => DataType::Null,
+            "Boolean" => DataType::Boolean,
+            "Int8" => DataType::Int8,
+            "Int16" => DataType::Int16,
+            "Int32" => DataType::Int32,
+            "Int64" => DataType::Int64,
+            "UInt8" => DataType::UInt8,
+            "UInt16" => DataType::UInt16,
+            "UInt32" => DataType::UInt32,
+            "UInt64" => DataType::UInt64,
+            "Float16" => DataType::Float16,
+            "Float32" => DataType::Float32,
+            "Float64" => DataType::Float64,
+            "Utf8" => DataType::Utf8,
+            "LargeUtf8" => DataType::LargeUtf8,
+            "Binary" => DataType::Binary,
+            "LargeBinary" => DataType::LargeBinary,
+            "Date32" => DataType::Date32,
+            "Date64" => DataType::Date64,
+            "Timestamp(Second, None)" => DataType::Timestamp(TimeUnit::Second, None),
+            "Timestamp(Millisecond, None)" => DataType::Timestamp(TimeUnit::Millisecond, None),
+            "Timestamp(Microsecond, None)" => DataType::Timestamp(TimeUnit::Microsecond, None),
+            "Timestamp(Nanosecond, None)" => DataType::Timestamp(TimeUnit::Nanosecond, None),
+            _ => return None,
+        };
+        Some(data_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::datatypes::{DataType, TimeUnit};
+
+    use crate::pipeline::interface::ProcessDefinition;
+
+    const MANIFEST_STRING: &str = "name: echo\nversion: 0.1.0\ninput_schema:\n - name: text\n   data_type: Utf8\noutput_schema:\n - name: text\n   data_type: Utf8\nparameters:\n - name: text\n   required: true\n - name: language\n";
+
+    #[test]
+    // Test that the manifest is read from the custom section of a WASM module
+    fn test_manifest_section() {
+        // WASM header followed by a custom section (id 0) containing the manifest
+        let mut module_content: Vec<u8> = vec![0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+        let name = super::MANIFEST_SECTION_NAME.as_bytes();
+        let section_size = 1 + name.len() + MANIFEST_STRING.len();
+        assert!(section_size < 0x4000);
+        module_content.push(0x00);
+        module_content.push((section_size & 0x7f) as u8 | 0x80);
+        module_content.push((section_size >> 7) as u8);
+        module_content.push(name.len() as u8);
+        module_content.extend_from_slice(name);
+        module_content.extend_from_slice(MANIFEST_STRING.as_bytes());
+        let manifest_content = super::manifest_section(&module_content).unwrap().unwrap();
+        let module_manifest = super::parse_module_manifest(manifest_content).unwrap();
+        assert_eq!(module_manifest.name, "echo");
+        assert_eq!(module_manifest.input_schema[0].data_type, DataType::Utf8);
+        assert_eq!(module_manifest.parameters.len(), 2);
+        // modules in text format do not have a manifest section
+        assert!(super::manifest_section(b"(module)").unwrap().is_none());
+    }
+
+    #[test]
+    // Test that the manifest is read from a sidecar file
+    fn test_read_module_manifest() {
+        let module_manifest =
+            super::read_module_manifest("tests/data/modules/library/wasm/echo.wat")
+                .unwrap()
+                .unwrap();
+        assert_eq!(module_manifest.name, "echo");
+        assert_eq!(module_manifest.parameters[0].name, "text");
+        assert!(
+            super::read_module_manifest("tests/data/modules/library/wasm/loop.wat")
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    // Test that renamed fields, different data types and nullable fields are detected
+    fn test_validate_schema_compatibility() {
+        let output_manifest = super::parse_module_manifest(
+            b"name: parse\nversion: 1.0.0\noutput_schema:\n - name: id\n   data_type: Int64\n - name: txt\n   data_type: Utf8\n - name: score\n   data_type: Float64\n   nullable: true\n",
+        )
+        .unwrap();
+        let input_manifest = super::parse_module_manifest(
+            b"name: index\nversion: 1.0.0\ninput_schema:\n - name: id\n   data_type: Int64\n",
+        )
+        .unwrap();
+        assert!(super::validate_schema_compatibility(
+            "parse",
+            &output_manifest,
+            "index",
+            &input_manifest
+        )
+        .is_empty());
+        let input_manifest = super::parse_module_manifest(
+            b"name: index\nversion: 1.0.0\ninput_schema:\n - name: id\n   data_type: Utf8\n - name: text\n   data_type: Utf8\n - name: score\n   data_type: Float64\n",
+        )
+        .unwrap();
+        assert_eq!(
+            super::validate_schema_compatibility(
+                "parse",
+                &output_manifest,
+                "index",
+                &input_manifest
+            )
+            .len(),
+            3
+        );
+    }
+
+    #[test]
+    // Test that data types are parsed when the manifest is read and unknown data types are rejected
+    fn test_manifest_data_types() {
+        let module_manifest = super::parse_module_manifest(
+            b"name: parse\nversion: 1.0.0\noutput_schema:\n - name: id\n   data_type: Int64\n - name: created\n   data_type: Timestamp(Millisecond, None)\n",
+        )
+        .unwrap();
+        assert_eq!(module_manifest.output_schema[0].data_type, DataType::Int64);
+        assert_eq!(
+            module_manifest.output_schema[1].data_type,
+            DataType::Timestamp(TimeUnit::Millisecond, None)
+        );
+        // data types are written with the same name they are read
+        let serialized_manifest = serde_yaml::to_string(&module_manifest).unwrap();
+        assert!(serialized_manifest.contains("data_type: Timestamp(Millisecond, None)"));
+        for invalid_data_type in ["utf8", "String", "Int128"] {
+            assert!(super::parse_module_manifest(
+                format!(
+                    "name: parse\nversion: 1.0.0\noutput_schema:\n - name: id\n   data_type: {}\n",
+                    invalid_data_type
+                )
+                .as_bytes()
+            )
+            .is_err());
+        }
+    }
+
+    #[test]
+    // Test that undeclared and missing required parameters are reported
+    fn test_validate_parameters() {
+        let module_manifest = super::parse_module_manifest(MANIFEST_STRING.as_bytes()).unwrap();
+        let process_definition: ProcessDefinition = serde_yaml::from_str(
+            "module:\n name: echo.wasm\n type: wasm\nparameters:\n - text: Hello\n - language: en\n",
+        )
+        .unwrap();
+        assert!(super::validate_parameters(&module_manifest, &process_definition).is_empty());
+        let process_definition: ProcessDefinition = serde_yaml::from_str(
+            "module:\n name: echo.wasm\n type: wasm\nparameters:\n - langauge: en\n",
+        )
+        .unwrap();
+        assert_eq!(
+            super::validate_parameters(&module_manifest, &process_definition).len(),
+            2
+        );
+    }
+}
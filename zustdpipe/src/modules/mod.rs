@@ -6,3 +6,4 @@ pub mod library;
 // module
 pub mod interface;
 pub mod manager;
+pub mod manifest;
@@ -19,5 +19,14 @@ pub struct ProcessModuleRequirements {
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct ProcessDefinition {
     pub module: ProcessModuleRequirements,
+    /// names of the processes whose output is the input of this process. If not defined then the output of the previous process is the input
+    #[serde(default)]
+    pub inputs: Option<Vec<String>>,
+    /// maximum time in seconds the process may run. The process is interrupted if it runs longer
+    #[serde(default)]
+    pub timeout_seconds: Option<u64>,
+    /// maximum fuel (roughly the number of executed instructions) a single call of the module may consume. Overrides the limit of the module
+    #[serde(default)]
+    pub fuel: Option<u64>,
     pub parameters: Vec<HashMap<String, String>>,
 }
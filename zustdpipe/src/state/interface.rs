@@ -0,0 +1,101 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::error::GeneralError;
+use crate::jobs::interface::JobCheckpoint;
+
+/// Definition of the state store of an app
+#[derive(Deserialize, Serialize, Clone)]
+pub struct StateDefinition {
+    /// directory where the state and its checkpoints are stored
+    pub path: String,
+}
+
+#[derive(Debug)]
+pub enum StateStoreError {
+    StateStoreIOError(GeneralError),
+    StateStoreSerializationError(GeneralError),
+    CheckpointNotFound(GeneralError),
+    InvalidJobId(GeneralError),
+}
+
+/// Key value state of all namespaces (key is the namespace, e.g. pipeline and process)
+pub type StateNamespaces = HashMap<String, BTreeMap<Vec<u8>, Vec<u8>>>;
+
+/// Changes of a job to the state of all namespaces that have not been committed yet (key is the namespace). A deleted key has no value
+pub type StateChanges = HashMap<String, BTreeMap<Vec<u8>, Option<Vec<u8>>>>;
+
+/// Checkpoint of a job as written to the directory of the store
+#[derive(Deserialize, Serialize)]
+pub(crate) struct StateCheckpoint {
+    /// progress of the job
+    pub(crate) job_checkpoint: JobCheckpoint,
+    /// changes of the job committed together with the checkpoint that may not have been written to their namespaces yet. They are written when the store is opened again
+    pub(crate) changes: StateChanges,
+}
+
+/// Embedded key value store. Each job changes the state in its own transaction. A committed namespace is written to its own file in the directory of the store, so a crash does not lose committed changes and a job rolling back does not affect the state committed by other jobs
+/// Checkpoints of jobs are only taken if the store has a directory. A store keeping the state only in memory does not keep the input and outputs of failed jobs, ie its jobs cannot be resumed
+pub struct StateStore {
+    /// directory of the store. None if the state is only kept in memory
+    pub(crate) path: Option<PathBuf>,
+    /// committed state of all namespaces
+    pub(crate) namespaces: StateNamespaces,
+    /// changes of the running jobs that have not been committed yet (key is the job id)
+    pub(crate) transactions: HashMap<String, StateChanges>,
+}
+
+/// State store shared by all module instances of a job
+pub type SharedStateStore = Arc<Mutex<StateStore>>;
+
+/// Access of a module instance to the state of one namespace
+#[derive(Clone)]
+pub struct StateHandle {
+    pub store: SharedStateStore,
+    /// unique id of the job whose transaction contains the changes of the module instance
+    pub job_id: String,
+    pub namespace: String,
+}
+
+/// Manages the state of stateful modules
+pub trait StateManager {
+    fn open(state_definition: &StateDefinition) -> Result<Self, StateStoreError>
+    where
+        Self: Sized;
+    fn in_memory() -> Self
+    where
+        Self: Sized;
+    fn get(&self, job_id: &str, namespace: &str, key: &[u8]) -> Option<Vec<u8>>;
+    fn put(&mut self, job_id: &str, namespace: &str, key: &[u8], value: &[u8]);
+    fn delete(&mut self, job_id: &str, namespace: &str, key: &[u8]);
+    fn commit(&mut self, job_id: &str) -> Result<(), StateStoreError>;
+    fn rollback(&mut self, job_id: &str);
+    fn checkpoint(
+        &mut self,
+        job_id: &str,
+        job_checkpoint: &JobCheckpoint,
+    ) -> Result<(), StateStoreError>;
+    fn commit_checkpoint(
+        &mut self,
+        job_id: &str,
+        job_checkpoint: &JobCheckpoint,
+    ) -> Result<(), StateStoreError>;
+    fn checkpoint_input(
+        &mut self,
+        job_id: &str,
+        serialized_input: &[u8],
+    ) -> Result<(), StateStoreError>;
+    fn checkpoint_output(
+        &mut self,
+        job_id: &str,
+        process_name: &str,
+        serialized_output: &[u8],
+    ) -> Result<(), StateStoreError>;
+    fn restore_checkpoint(&mut self, job_id: &str) -> Result<JobCheckpoint, StateStoreError>;
+    fn restore_input(&self, job_id: &str) -> Result<Vec<u8>, StateStoreError>;
+    fn restore_output(&self, job_id: &str, process_name: &str) -> Result<Vec<u8>, StateStoreError>;
+    fn remove_checkpoint(&mut self, job_id: &str) -> Result<(), StateStoreError>;
+}
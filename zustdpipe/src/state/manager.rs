@@ -0,0 +1,802 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::error::error::GeneralError;
+use crate::jobs::interface::JobCheckpoint;
+
+use super::interface::{
+    StateChanges, StateCheckpoint, StateDefinition, StateManager, StateNamespaces, StateStore,
+    StateStoreError,
+};
+
+/// Directory containing the state of all namespaces (one file per namespace) in the directory of the store
+const NAMESPACE_DIRECTORY_NAME: &str = "namespaces";
+/// Directory containing the checkpoints in the directory of the store
+const CHECKPOINT_DIRECTORY_NAME: &str = "checkpoints";
+/// Extension of the files containing the state of a namespace or a checkpoint
+const STATE_FILE_EXTENSION: &str = "bin";
+/// Name of the checkpointed input of a job
+const INPUT_CHECKPOINT_NAME: &str = "input";
+/// Prefix of the names of the checkpointed outputs of the processes of a job
+const OUTPUT_CHECKPOINT_PREFIX: &str = "output_";
+
+impl StateManager for StateStore {
+    /// Opens the state store in the given directory. The directory is created if it does not exist
+    /// Changes committed together with a checkpoint that have not been written to their namespaces, e.g. because of a crash, are written first
+    ///
+    /// # Arguments
+    /// * `state_definition` - definition of the state store
+    ///
+    fn open(state_definition: &StateDefinition) -> Result<StateStore, StateStoreError> {
+        let path = PathBuf::from(&state_definition.path);
+        for directory in [NAMESPACE_DIRECTORY_NAME, CHECKPOINT_DIRECTORY_NAME] {
+            if let Err(err) = fs::create_dir_all(path.join(directory)) {
+                return Err(StateStoreError::StateStoreIOError(
+                    GeneralError::ErrorMessage(format!(
+                        "Cannot create state directory {}: {}",
+                        path.display(),
+                        err
+                    )),
+                ));
+            }
+        }
+        let namespaces = read_namespaces(&path.join(NAMESPACE_DIRECTORY_NAME))?;
+        let mut state_store = StateStore {
+            path: Some(path),
+            namespaces: namespaces,
+            transactions: HashMap::new(),
+        };
+        state_store.write_pending_changes()?;
+        Ok(state_store)
+    }
+
+    /// Creates a state store that only keeps the state in memory
+    fn in_memory() -> StateStore {
+        StateStore {
+            path: None,
+            namespaces: HashMap::new(),
+            transactions: HashMap::new(),
+        }
+    }
+
+    /// Returns the value of a key. The changes of the job that have not been committed yet are taken into account
+    ///
+    /// # Arguments
+    /// * `job_id` - unique id of the job reading the key
+    /// * `namespace` - namespace of the key
+    /// * `key` - key
+    ///
+    fn get(&self, job_id: &str, namespace: &str, key: &[u8]) -> Option<Vec<u8>> {
+        let change = self
+            .transactions
+            .get(job_id)
+            .and_then(|changes| changes.get(namespace))
+            .and_then(|entries| entries.get(key));
+        match change {
+            Some(value) => value.clone(),
+            None => match self.namespaces.get(namespace) {
+                Some(entries) => entries.get(key).cloned(),
+                None => None,
+            },
+        }
+    }
+
+    /// Sets the value of a key. The change is visible to other jobs once it has been committed
+    ///
+    /// # Arguments
+    /// * `job_id` - unique id of the job changing the key
+    /// * `namespace` - namespace of the key
+    /// * `key` - key
+    /// * `value` - value
+    ///
+    fn put(&mut self, job_id: &str, namespace: &str, key: &[u8], value: &[u8]) {
+        self.transactions
+            .entry(job_id.to_string())
+            .or_default()
+            .entry(namespace.to_string())
+            .or_default()
+            .insert(key.to_vec(), Some(value.to_vec()));
+    }
+
+    /// Deletes a key. The change is visible to other jobs once it has been committed
+    ///
+    /// # Arguments
+    /// * `job_id` - unique id of the job deleting the key
+    /// * `namespace` - namespace of the key
+    /// * `key` - key
+    ///
+    fn delete(&mut self, job_id: &str, namespace: &str, key: &[u8]) {
+        self.transactions
+            .entry(job_id.to_string())
+            .or_default()
+            .entry(namespace.to_string())
+            .or_default()
+            .insert(key.to_vec(), None);
+    }
+
+    /// Commits the changes of a job. Each changed namespace is written to its own file, so the namespaces changed by other jobs are not overwritten
+    ///
+    /// # Arguments
+    /// * `job_id` - unique id of the job
+    ///
+    fn commit(&mut self, job_id: &str) -> Result<(), StateStoreError> {
+        match self.transactions.remove(job_id) {
+            Some(changes) => self.write_changes(changes),
+            None => Ok(()),
+        }
+    }
+
+    /// Discards the changes of a job that have not been committed yet, e.g. because a step of the job failed
+    ///
+    /// # Arguments
+    /// * `job_id` - unique id of the job
+    ///
+    fn rollback(&mut self, job_id: &str) {
+        self.transactions.remove(job_id);
+    }
+
+    /// Takes a checkpoint of the progress of a job. A previous checkpoint of the job is replaced. Nothing is checkpointed if the state is only kept in memory
+    ///
+    /// # Arguments
+    /// * `job_id` - unique id of the job
+    /// * `job_checkpoint` - progress of the job
+    ///
+    fn checkpoint(
+        &mut self,
+        job_id: &str,
+        job_checkpoint: &JobCheckpoint,
+    ) -> Result<(), StateStoreError> {
+        match &self.path {
+            Some(path) => write_state(
+                &checkpoint_file(path, job_id)?,
+                &StateCheckpoint {
+                    job_checkpoint: job_checkpoint.clone(),
+                    changes: HashMap::new(),
+                },
+            ),
+            None => Ok(()),
+        }
+    }
+
+    /// Commits the changes of a job and takes a checkpoint of its progress at once, e.g. after a step of the job succeeded
+    /// The changes are written together with the checkpoint before they are written to their namespaces. If the job is interrupted in between then they are written when the store is opened again, so a resumed job does not apply them a second time by executing the step again
+    ///
+    /// # Arguments
+    /// * `job_id` - unique id of the job
+    /// * `job_checkpoint` - progress of the job including the step that made the changes
+    ///
+    fn commit_checkpoint(
+        &mut self,
+        job_id: &str,
+        job_checkpoint: &JobCheckpoint,
+    ) -> Result<(), StateStoreError> {
+        let changes = self.transactions.remove(job_id).unwrap_or_default();
+        let checkpoint_file = match &self.path {
+            Some(path) => checkpoint_file(path, job_id)?,
+            None => return self.write_changes(changes),
+        };
+        let mut state_checkpoint = StateCheckpoint {
+            job_checkpoint: job_checkpoint.clone(),
+            changes: changes,
+        };
+        write_state(&checkpoint_file, &state_checkpoint)?;
+        self.write_changes(std::mem::take(&mut state_checkpoint.changes))?;
+        // the changes are not written again once they are part of their namespaces
+        write_state(&checkpoint_file, &state_checkpoint)
+    }
+
+    /// Checkpoints the input of a job once before its first step
+    ///
+    /// # Arguments
+    /// * `job_id` - unique id of the job
+    /// * `serialized_input` - input of the job in Arrow IPC format
+    ///
+    fn checkpoint_input(
+        &mut self,
+        job_id: &str,
+        serialized_input: &[u8],
+    ) -> Result<(), StateStoreError> {
+        self.write_checkpoint_data(job_id, INPUT_CHECKPOINT_NAME, serialized_input)
+    }
+
+    /// Checkpoints the output of a completed step of a job. It is written once, ie independent of the outputs of the other steps
+    ///
+    /// # Arguments
+    /// * `job_id` - unique id of the job
+    /// * `process_name` - name of the process executed by the step
+    /// * `serialized_output` - output of the step in Arrow IPC format
+    ///
+    fn checkpoint_output(
+        &mut self,
+        job_id: &str,
+        process_name: &str,
+        serialized_output: &[u8],
+    ) -> Result<(), StateStoreError> {
+        self.write_checkpoint_data(
+            job_id,
+            &output_checkpoint_name(process_name),
+            serialized_output,
+        )
+    }
+
+    /// Returns the last checkpoint of a job. The changes of the job that have not been committed yet are discarded
+    ///
+    /// # Arguments
+    /// * `job_id` - unique id of the job
+    ///
+    /// returns the progress of the job at the checkpoint
+    fn restore_checkpoint(&mut self, job_id: &str) -> Result<JobCheckpoint, StateStoreError> {
+        self.rollback(job_id);
+        match &self.path {
+            Some(path) => {
+                let checkpoint_file = checkpoint_file(path, job_id)?;
+                if !checkpoint_file.exists() {
+                    return Err(checkpoint_not_found(job_id));
+                }
+                let state_checkpoint: StateCheckpoint = read_state(&checkpoint_file)?;
+                Ok(state_checkpoint.job_checkpoint)
+            }
+            None => Err(checkpoint_not_found(job_id)),
+        }
+    }
+
+    /// Returns the checkpointed input of a job
+    ///
+    /// # Arguments
+    /// * `job_id` - unique id of the job
+    ///
+    fn restore_input(&self, job_id: &str) -> Result<Vec<u8>, StateStoreError> {
+        self.read_checkpoint_data(job_id, INPUT_CHECKPOINT_NAME)
+    }
+
+    /// Returns the checkpointed output of a completed step of a job
+    ///
+    /// # Arguments
+    /// * `job_id` - unique id of the job
+    /// * `process_name` - name of the process executed by the step
+    ///
+    fn restore_output(&self, job_id: &str, process_name: &str) -> Result<Vec<u8>, StateStoreError> {
+        self.read_checkpoint_data(job_id, &output_checkpoint_name(process_name))
+    }
+
+    /// Removes the checkpoint of a job including its checkpointed input and outputs, e.g. because the job succeeded
+    ///
+    /// # Arguments
+    /// * `job_id` - unique id of the job
+    ///
+    fn remove_checkpoint(&mut self, job_id: &str) -> Result<(), StateStoreError> {
+        match &self.path {
+            Some(path) => {
+                let checkpoint_file = checkpoint_file(path, job_id)?;
+                let checkpoint_data_directory = checkpoint_data_directory(path, job_id)?;
+                let mut result = Ok(());
+                if checkpoint_file.exists() {
+                    result = fs::remove_file(&checkpoint_file);
+                }
+                if result.is_ok() && checkpoint_data_directory.exists() {
+                    result = fs::remove_dir_all(&checkpoint_data_directory);
+                }
+                match result {
+                    Ok(()) => Ok(()),
+                    Err(err) => Err(StateStoreError::StateStoreIOError(
+                        GeneralError::ErrorMessage(format!(
+                            "Cannot remove checkpoint of job {}: {}",
+                            job_id, err
+                        )),
+                    )),
+                }
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl StateStore {
+    /// Writes changes to their namespaces. Each changed namespace is written to its own file, so the namespaces changed by other jobs are not overwritten
+    ///
+    /// # Arguments
+    /// * `changes` - changes of a job
+    ///
+    fn write_changes(&mut self, changes: StateChanges) -> Result<(), StateStoreError> {
+        for (namespace, namespace_changes) in changes {
+            let mut entries = match &self.path {
+                // the namespace is read again, so changes committed by another process using the same directory are kept
+                Some(path) => {
+                    let namespace_file = namespace_file(path, &namespace);
+                    if namespace_file.exists() {
+                        read_state(&namespace_file)?
+                    } else {
+                        BTreeMap::new()
+                    }
+                }
+                None => self.namespaces.remove(&namespace).unwrap_or_default(),
+            };
+            for (key, value) in namespace_changes {
+                match value {
+                    Some(value) => entries.insert(key, value),
+                    None => entries.remove(&key),
+                };
+            }
+            if let Some(path) = &self.path {
+                write_state(&namespace_file(path, &namespace), &entries)?;
+            }
+            self.namespaces.insert(namespace, entries);
+        }
+        Ok(())
+    }
+
+    /// Writes the changes committed together with a checkpoint that have not been written to their namespaces yet. A change sets or deletes a key, so writing it again has the same result
+    fn write_pending_changes(&mut self) -> Result<(), StateStoreError> {
+        let checkpoint_directory = match &self.path {
+            Some(path) => path.join(CHECKPOINT_DIRECTORY_NAME),
+            None => return Ok(()),
+        };
+        let directory_entries = match fs::read_dir(&checkpoint_directory) {
+            Ok(directory_entries) => directory_entries,
+            Err(err) => {
+                return Err(StateStoreError::StateStoreIOError(
+                    GeneralError::ErrorMessage(format!(
+                        "Cannot read checkpoint directory {}: {}",
+                        checkpoint_directory.display(),
+                        err
+                    )),
+                ))
+            }
+        };
+        for directory_entry in directory_entries.flatten() {
+            let checkpoint_file = directory_entry.path();
+            // the directories of the checkpointed data and temporary files of an interrupted checkpoint are ignored
+            if !checkpoint_file.is_file()
+                || checkpoint_file
+                    .extension()
+                    .and_then(|extension| extension.to_str())
+                    != Some(STATE_FILE_EXTENSION)
+            {
+                continue;
+            }
+            let mut state_checkpoint: StateCheckpoint = read_state(&checkpoint_file)?;
+            if state_checkpoint.changes.is_empty() {
+                continue;
+            }
+            self.write_changes(std::mem::take(&mut state_checkpoint.changes))?;
+            write_state(&checkpoint_file, &state_checkpoint)?;
+        }
+        Ok(())
+    }
+
+    /// Checkpoints data of a job, e.g. its input. Data with the same name is replaced. Nothing is checkpointed if the state is only kept in memory
+    ///
+    /// # Arguments
+    /// * `job_id` - unique id of the job
+    /// * `name` - name of the data
+    /// * `data` - data
+    ///
+    fn write_checkpoint_data(
+        &mut self,
+        job_id: &str,
+        name: &str,
+        data: &[u8],
+    ) -> Result<(), StateStoreError> {
+        match &self.path {
+            Some(path) => {
+                let checkpoint_data_directory = checkpoint_data_directory(path, job_id)?;
+                if let Err(err) = fs::create_dir_all(&checkpoint_data_directory) {
+                    return Err(StateStoreError::StateStoreIOError(
+                        GeneralError::ErrorMessage(format!(
+                            "Cannot create checkpoint directory {}: {}",
+                            checkpoint_data_directory.display(),
+                            err
+                        )),
+                    ));
+                }
+                write_file(
+                    &checkpoint_data_directory.join(format!("{}.{}", name, STATE_FILE_EXTENSION)),
+                    data,
+                )
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Reads data of a job written by write_checkpoint_data
+    ///
+    /// # Arguments
+    /// * `job_id` - unique id of the job
+    /// * `name` - name of the data
+    ///
+    fn read_checkpoint_data(&self, job_id: &str, name: &str) -> Result<Vec<u8>, StateStoreError> {
+        match &self.path {
+            Some(path) => {
+                let data_file = checkpoint_data_directory(path, job_id)?
+                    .join(format!("{}.{}", name, STATE_FILE_EXTENSION));
+                if !data_file.exists() {
+                    return Err(checkpoint_not_found(job_id));
+                }
+                match fs::read(&data_file) {
+                    Ok(data) => Ok(data),
+                    Err(err) => Err(StateStoreError::StateStoreIOError(
+                        GeneralError::ErrorMessage(format!(
+                            "Cannot read checkpoint {}: {}",
+                            data_file.display(),
+                            err
+                        )),
+                    )),
+                }
+            }
+            None => Err(checkpoint_not_found(job_id)),
+        }
+    }
+}
+
+/// Returns the file of the checkpoint of a job
+/// # Arguments
+/// * `path` - directory of the store
+/// * `job_id` - unique id of the job
+fn checkpoint_file(path: &Path, job_id: &str) -> Result<PathBuf, StateStoreError> {
+    validate_job_id(job_id)?;
+    Ok(path
+        .join(CHECKPOINT_DIRECTORY_NAME)
+        .join(format!("{}.{}", job_id, STATE_FILE_EXTENSION)))
+}
+
+/// Returns the directory containing the checkpointed input and outputs of a job
+/// # Arguments
+/// * `path` - directory of the store
+/// * `job_id` - unique id of the job
+fn checkpoint_data_directory(path: &Path, job_id: &str) -> Result<PathBuf, StateStoreError> {
+    validate_job_id(job_id)?;
+    Ok(path.join(CHECKPOINT_DIRECTORY_NAME).join(job_id))
+}
+
+/// Validates that a job id is a UUID, so it cannot refer to a file outside of the checkpoint directory
+/// # Arguments
+/// * `job_id` - unique id of the job, e.g. provided by the user to resume a job
+fn validate_job_id(job_id: &str) -> Result<(), StateStoreError> {
+    match Uuid::parse_str(job_id) {
+        Ok(_uuid) => Ok(()),
+        Err(err) => Err(StateStoreError::InvalidJobId(GeneralError::ErrorMessage(
+            format!("Invalid job id {}: {}", job_id, err),
+        ))),
+    }
+}
+
+/// Returns the file of the state of a namespace. The namespace is hex encoded, because it may contain characters that are not allowed in file names, e.g. a slash
+/// # Arguments
+/// * `path` - directory of the store
+/// * `namespace` - namespace
+fn namespace_file(path: &Path, namespace: &str) -> PathBuf {
+    path.join(NAMESPACE_DIRECTORY_NAME).join(format!(
+        "{}.{}",
+        encode_file_name(namespace),
+        STATE_FILE_EXTENSION
+    ))
+}
+
+/// Returns the name of the checkpointed output of a process. The name of the process is hex encoded like a namespace, so it cannot be confused with the input
+/// # Arguments
+/// * `process_name` - name of the process
+fn output_checkpoint_name(process_name: &str) -> String {
+    format!(
+        "{}{}",
+        OUTPUT_CHECKPOINT_PREFIX,
+        encode_file_name(process_name)
+    )
+}
+
+/// Hex encodes a name, so it can be used as a file name
+/// # Arguments
+/// * `name` - name, e.g. a namespace
+fn encode_file_name(name: &str) -> String {
+    name.bytes().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Reads the state of all namespaces written by commit
+/// # Arguments
+/// * `namespace_directory` - directory containing one file per namespace
+fn read_namespaces(namespace_directory: &Path) -> Result<StateNamespaces, StateStoreError> {
+    let directory_entries = match fs::read_dir(namespace_directory) {
+        Ok(directory_entries) => directory_entries,
+        Err(err) => {
+            return Err(StateStoreError::StateStoreIOError(
+                GeneralError::ErrorMessage(format!(
+                    "Cannot read state directory {}: {}",
+                    namespace_directory.display(),
+                    err
+                )),
+            ))
+        }
+    };
+    let mut namespaces: StateNamespaces = HashMap::new();
+    for directory_entry in directory_entries.flatten() {
+        let namespace_file = directory_entry.path();
+        // temporary files of an interrupted commit are ignored
+        if namespace_file
+            .extension()
+            .and_then(|extension| extension.to_str())
+            != Some(STATE_FILE_EXTENSION)
+        {
+            continue;
+        }
+        let namespace = match namespace_file
+            .file_stem()
+            .and_then(|file_stem| file_stem.to_str())
+            .and_then(decode_namespace)
+        {
+            Some(namespace) => namespace,
+            None => {
+                return Err(StateStoreError::StateStoreSerializationError(
+                    GeneralError::ErrorMessage(format!(
+                        "Invalid namespace file {}",
+                        namespace_file.display()
+                    )),
+                ))
+            }
+        };
+        namespaces.insert(namespace, read_state(&namespace_file)?);
+    }
+    Ok(namespaces)
+}
+
+/// Decodes a hex encoded namespace. Returns None if it is not a valid encoding
+/// # Arguments
+/// * `file_name` - hex encoded namespace
+fn decode_namespace(file_name: &str) -> Option<String> {
+    if !file_name.len().is_multiple_of(2) {
+        return None;
+    }
+    let bytes: Option<Vec<u8>> = (0..file_name.len())
+        .step_by(2)
+        .map(|index| {
+            file_name
+                .get(index..index + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+        })
+        .collect();
+    bytes.and_then(|bytes| String::from_utf8(bytes).ok())
+}
+
+fn checkpoint_not_found(job_id: &str) -> StateStoreError {
+    StateStoreError::CheckpointNotFound(GeneralError::ErrorMessage(format!(
+        "No checkpoint found for job {}",
+        job_id
+    )))
+}
+
+/// Reads the state of a namespace or a checkpoint written by write_state
+/// # Arguments
+/// * `file` - file containing the state
+fn read_state<T: DeserializeOwned>(file: &Path) -> Result<T, StateStoreError> {
+    let serialized_state = match fs::read(file) {
+        Ok(serialized_state) => serialized_state,
+        Err(err) => {
+            return Err(StateStoreError::StateStoreIOError(
+                GeneralError::ErrorMessage(format!(
+                    "Cannot read state {}: {}",
+                    file.display(),
+                    err
+                )),
+            ))
+        }
+    };
+    match bincode::deserialize(&serialized_state) {
+        Ok(state) => Ok(state),
+        Err(err) => Err(StateStoreError::StateStoreSerializationError(
+            GeneralError::ErrorMessage(format!(
+                "Cannot deserialize state {}: {}",
+                file.display(),
+                err
+            )),
+        )),
+    }
+}
+
+/// Writes the state of a namespace or a checkpoint
+/// # Arguments
+/// * `file` - file to write the state to
+/// * `state` - state or checkpoint
+fn write_state<T: Serialize>(file: &Path, state: &T) -> Result<(), StateStoreError> {
+    let serialized_state = match bincode::serialize(state) {
+        Ok(serialized_state) => serialized_state,
+        Err(err) => {
+            return Err(StateStoreError::StateStoreSerializationError(
+                GeneralError::ErrorMessage(format!("Cannot serialize state: {}", err)),
+            ))
+        }
+    };
+    write_file(file, &serialized_state)
+}
+
+/// Writes a file. The data is first written to a temporary file that replaces the file afterwards, so an interruption does not leave a partially written file
+/// # Arguments
+/// * `file` - file to write
+/// * `data` - data to write
+fn write_file(file: &Path, data: &[u8]) -> Result<(), StateStoreError> {
+    let temporary_file = file.with_extension("tmp");
+    let result = fs::write(&temporary_file, data).and_then(|()| fs::rename(&temporary_file, file));
+    match result {
+        Ok(()) => Ok(()),
+        Err(err) => Err(StateStoreError::StateStoreIOError(
+            GeneralError::ErrorMessage(format!("Cannot write file {}: {}", file.display(), err)),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::jobs::interface::{JobCheckpoint, JobCheckpointStep};
+    use crate::pipeline::interface::PipelineDefinition;
+    use crate::state::interface::{
+        StateCheckpoint, StateDefinition, StateManager, StateStore, StateStoreError,
+    };
+
+    #[test]
+    // Test that committed changes are persisted and checkpoints can be restored
+    fn test_state_store_checkpoint() {
+        let path = std::env::temp_dir().join(format!("zustdp-state-{}", uuid::Uuid::new_v4()));
+        let state_definition = StateDefinition {
+            path: path.display().to_string(),
+        };
+        let job_id = uuid::Uuid::new_v4().to_string();
+        let mut state_store: StateStore = StateManager::open(&state_definition).unwrap();
+        state_store.put(&job_id, "example_1/count", b"count", b"1");
+        // changes are only visible to other jobs once they have been committed
+        assert_eq!(
+            state_store.get(&job_id, "example_1/count", b"count"),
+            Some(b"1".to_vec())
+        );
+        assert_eq!(
+            state_store.get("other_job", "example_1/count", b"count"),
+            None
+        );
+        state_store.commit(&job_id).unwrap();
+        assert_eq!(
+            state_store.get("other_job", "example_1/count", b"count"),
+            Some(b"1".to_vec())
+        );
+        let job_checkpoint = JobCheckpoint {
+            pipeline: "example_1".to_string(),
+            pipeline_definition: PipelineDefinition {
+                process: Vec::new(),
+            },
+            threads: 1,
+            completed_steps: vec![JobCheckpointStep {
+                name: "count".to_string(),
+                fuel_consumed: 1,
+            }],
+        };
+        state_store.checkpoint_input(&job_id, b"input").unwrap();
+        state_store
+            .checkpoint_output(&job_id, "count", b"output")
+            .unwrap();
+        state_store.checkpoint(&job_id, &job_checkpoint).unwrap();
+        state_store.put(&job_id, "example_1/count", b"count", b"2");
+        state_store.put(&job_id, "example_1/count", b"other", b"3");
+        // the store is opened again, e.g. after a crash. Only the committed changes are kept
+        let mut state_store: StateStore = StateManager::open(&state_definition).unwrap();
+        assert_eq!(
+            state_store.get(&job_id, "example_1/count", b"count"),
+            Some(b"1".to_vec())
+        );
+        assert_eq!(state_store.get(&job_id, "example_1/count", b"other"), None);
+        let job_checkpoint = state_store.restore_checkpoint(&job_id).unwrap();
+        assert_eq!(job_checkpoint.completed_steps.len(), 1);
+        assert_eq!(state_store.restore_input(&job_id).unwrap(), b"input");
+        assert_eq!(
+            state_store.restore_output(&job_id, "count").unwrap(),
+            b"output"
+        );
+        assert!(state_store.restore_output(&job_id, "other").is_err());
+        state_store.remove_checkpoint(&job_id).unwrap();
+        assert!(state_store.restore_checkpoint(&job_id).is_err());
+        assert!(state_store.restore_input(&job_id).is_err());
+        // job ids that are not UUIDs could refer to files outside of the checkpoint directory
+        match state_store.restore_checkpoint("../state") {
+            Err(StateStoreError::InvalidJobId(_)) => (),
+            _ => panic!("Invalid job id accepted"),
+        }
+        assert!(state_store
+            .checkpoint_input("../../input", b"input")
+            .is_err());
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    // Test that changes committed together with a checkpoint are written to their namespaces when the store is opened again after the job has been interrupted in between
+    fn test_state_store_commit_checkpoint() {
+        let path = std::env::temp_dir().join(format!("zustdp-state-{}", uuid::Uuid::new_v4()));
+        let state_definition = StateDefinition {
+            path: path.display().to_string(),
+        };
+        let job_id = uuid::Uuid::new_v4().to_string();
+        let mut job_checkpoint = JobCheckpoint {
+            pipeline: "example_1".to_string(),
+            pipeline_definition: PipelineDefinition {
+                process: Vec::new(),
+            },
+            threads: 1,
+            completed_steps: vec![JobCheckpointStep {
+                name: "first".to_string(),
+                fuel_consumed: 1,
+            }],
+        };
+        let mut state_store: StateStore = StateManager::open(&state_definition).unwrap();
+        state_store.put(&job_id, "example_1/count", b"count", b"1");
+        state_store
+            .commit_checkpoint(&job_id, &job_checkpoint)
+            .unwrap();
+        assert_eq!(
+            state_store.get("other_job", "example_1/count", b"count"),
+            Some(b"1".to_vec())
+        );
+        // the job is interrupted after the checkpoint has been written, but before its changes have been written to their namespaces
+        state_store.put(&job_id, "example_1/count", b"count", b"2");
+        job_checkpoint.completed_steps.push(JobCheckpointStep {
+            name: "second".to_string(),
+            fuel_consumed: 1,
+        });
+        let checkpoint_file = super::checkpoint_file(&path, &job_id).unwrap();
+        super::write_state(
+            &checkpoint_file,
+            &StateCheckpoint {
+                job_checkpoint: job_checkpoint,
+                changes: state_store.transactions.remove(&job_id).unwrap(),
+            },
+        )
+        .unwrap();
+        for _ in 0..2 {
+            let mut state_store: StateStore = StateManager::open(&state_definition).unwrap();
+            assert_eq!(
+                state_store.get("other_job", "example_1/count", b"count"),
+                Some(b"2".to_vec())
+            );
+            let job_checkpoint = state_store.restore_checkpoint(&job_id).unwrap();
+            assert_eq!(job_checkpoint.completed_steps.len(), 2);
+            let state_checkpoint: StateCheckpoint = super::read_state(&checkpoint_file).unwrap();
+            assert!(state_checkpoint.changes.is_empty());
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    // Test that a job rolling back its changes does not affect the changes committed by another job running at the same time
+    fn test_state_store_concurrent_jobs() {
+        let path = std::env::temp_dir().join(format!("zustdp-state-{}", uuid::Uuid::new_v4()));
+        let state_definition = StateDefinition {
+            path: path.display().to_string(),
+        };
+        let mut state_store: StateStore = StateManager::open(&state_definition).unwrap();
+        state_store.put("job_1", "example_1/count", b"count", b"1");
+        state_store.put("job_1", "example_1/count", b"deleted", b"1");
+        state_store.commit("job_1").unwrap();
+        // both jobs change the state at the same time. The second job fails after the first job committed its changes
+        state_store.put("job_1", "example_1/count", b"count", b"2");
+        state_store.delete("job_1", "example_1/count", b"deleted");
+        state_store.put("job_2", "example_1/count", b"other", b"1");
+        state_store.put("job_2", "example_2/count", b"count", b"1");
+        assert_eq!(
+            state_store.get("job_1", "example_1/count", b"deleted"),
+            None
+        );
+        state_store.commit("job_1").unwrap();
+        state_store.rollback("job_2");
+        let reopened_state_store: StateStore = StateManager::open(&state_definition).unwrap();
+        for state_store in [&state_store, &reopened_state_store] {
+            assert_eq!(
+                state_store.get("job_2", "example_1/count", b"count"),
+                Some(b"2".to_vec())
+            );
+            assert_eq!(
+                state_store.get("job_2", "example_1/count", b"deleted"),
+                None
+            );
+            assert_eq!(state_store.get("job_2", "example_1/count", b"other"), None);
+            assert_eq!(state_store.get("job_2", "example_2/count", b"count"), None);
+        }
+        std::fs::remove_dir_all(path).unwrap();
+    }
+}
@@ -0,0 +1,5 @@
+//! The state manager provides a key value store for stateful modules. The state of each step of a pipeline is kept across job runs.
+//! The changes of a job are committed after each successful step and a checkpoint of its progress is taken, so an interrupted job can resume from its last checkpoint.
+
+pub mod interface;
+pub mod manager;
@@ -0,0 +1,33 @@
+(module
+  ;; Module implementing the ABI whose version is computed like in a module compiled without optimizations: a stack pointer prologue and a call
+  (memory (export "memory") 1)
+  (global $stack_pointer (mut i32) (i32.const 65536))
+  (func $version (result i32)
+    i32.const 1
+    i32.const 1
+    i32.add)
+  (func (export "zustdp_module_abi_version") (result i32)
+    (local $sp i32)
+    global.get $stack_pointer
+    i32.const 16
+    i32.sub
+    local.tee $sp
+    global.set $stack_pointer
+    local.get $sp
+    call $version
+    i32.store
+    local.get $sp
+    i32.load
+    local.get $sp
+    i32.const 16
+    i32.add
+    global.set $stack_pointer)
+  (func (export "zustdp_module_wasm_allocate") (param $size i32) (result i32)
+    i32.const 1024)
+  (func (export "zustdp_module_wasm_deallocate") (param $ptr i32) (result i32)
+    i32.const 0)
+  (func (export "zustdp_module_wasm_raw_process_entry")
+    (param $meta_ptr i32) (param $meta_len i32) (param $data_ptr i32) (param $data_len i32)
+    (result i32)
+    i32.const 0
+  ))
@@ -0,0 +1,35 @@
+(module
+  ;; Minimal module implementing the raw process entry ABI. It returns the data it received unchanged.
+  (memory (export "memory") 1)
+  (func (export "zustdp_module_abi_version") (result i32)
+    i32.const 2)
+  (global $heap (mut i32) (i32.const 1024))
+  (func $allocate (export "zustdp_module_wasm_allocate") (param $size i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $heap))
+    ;; keep allocations 8 byte aligned
+    (global.set $heap
+      (i32.and
+        (i32.add (i32.add (local.get $ptr) (local.get $size)) (i32.const 7))
+        (i32.const -8)))
+    ;; grow memory if the allocation does not fit anymore
+    (if (i32.gt_u (global.get $heap) (i32.mul (memory.size) (i32.const 65536)))
+      (then
+        (drop (memory.grow
+          (i32.add
+            (i32.div_u
+              (i32.sub (global.get $heap) (i32.mul (memory.size) (i32.const 65536)))
+              (i32.const 65536))
+            (i32.const 1))))))
+    (local.get $ptr))
+  (func (export "zustdp_module_wasm_deallocate") (param $ptr i32) (result i32)
+    i32.const 0)
+  (func (export "zustdp_module_wasm_raw_process_entry")
+    (param $meta_ptr i32) (param $meta_len i32) (param $data_ptr i32) (param $data_len i32)
+    (result i32)
+    (local $result i32)
+    ;; the result descriptor contains pointer and length as u64
+    (local.set $result (call $allocate (i32.const 16)))
+    (i64.store (local.get $result) (i64.extend_i32_u (local.get $data_ptr)))
+    (i64.store offset=8 (local.get $result) (i64.extend_i32_u (local.get $data_len)))
+    (local.get $result)))
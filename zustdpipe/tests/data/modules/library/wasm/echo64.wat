@@ -0,0 +1,34 @@
+(module
+  ;; Minimal module with 64 bit memory (memory64) implementing the raw process entry ABI. It returns the data it received unchanged.
+  (memory (export "memory") i64 1)
+  (func (export "zustdp_module_abi_version") (result i32)
+    i32.const 2)
+  (global $heap (mut i64) (i64.const 1024))
+  (func $allocate (export "zustdp_module_wasm_allocate") (param $size i64) (result i64)
+    (local $ptr i64)
+    (local.set $ptr (global.get $heap))
+    ;; keep allocations 8 byte aligned
+    (global.set $heap
+      (i64.and
+        (i64.add (i64.add (local.get $ptr) (local.get $size)) (i64.const 7))
+        (i64.const -8)))
+    ;; grow memory if the allocation does not fit anymore
+    (if (i64.gt_u (global.get $heap) (i64.mul (memory.size) (i64.const 65536)))
+      (then
+        (drop (memory.grow
+          (i64.add
+            (i64.div_u
+              (i64.sub (global.get $heap) (i64.mul (memory.size) (i64.const 65536)))
+              (i64.const 65536))
+            (i64.const 1))))))
+    (local.get $ptr))
+  (func (export "zustdp_module_wasm_deallocate") (param $ptr i64) (result i32)
+    i32.const 0)
+  (func (export "zustdp_module_wasm_raw_process_entry")
+    (param $meta_ptr i64) (param $meta_len i64) (param $data_ptr i64) (param $data_len i64)
+    (result i64)
+    (local $result i64)
+    (local.set $result (call $allocate (i64.const 16)))
+    (i64.store (local.get $result) (local.get $data_ptr))
+    (i64.store offset=8 (local.get $result) (local.get $data_len))
+    (local.get $result)))
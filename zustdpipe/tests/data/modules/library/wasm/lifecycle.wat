@@ -0,0 +1,41 @@
+(module
+  ;; Minimal module with state. It returns the data it received unchanged and counts the calls.
+  ;; Finishing returns the number of calls and the size of the metadata given on initialization as two little endian u32.
+  (memory (export "memory") 1)
+  (func (export "zustdp_module_abi_version") (result i32)
+    i32.const 2)
+  (global $heap (mut i32) (i32.const 1024))
+  (global $calls (mut i32) (i32.const 0))
+  (global $meta_len (mut i32) (i32.const 0))
+  (func $allocate (export "zustdp_module_wasm_allocate") (param $size i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $heap))
+    ;; keep allocations 8 byte aligned
+    (global.set $heap
+      (i32.and
+        (i32.add (i32.add (local.get $ptr) (local.get $size)) (i32.const 7))
+        (i32.const -8)))
+    (local.get $ptr))
+  (func (export "zustdp_module_wasm_deallocate") (param $ptr i32) (result i32)
+    i32.const 0)
+  (func $result (param $ptr i32) (param $len i32) (result i32)
+    (local $result i32)
+    ;; the result descriptor contains pointer and length as u64
+    (local.set $result (call $allocate (i32.const 16)))
+    (i64.store (local.get $result) (i64.extend_i32_u (local.get $ptr)))
+    (i64.store offset=8 (local.get $result) (i64.extend_i32_u (local.get $len)))
+    (local.get $result))
+  (func (export "zustdp_module_init") (param $meta_ptr i32) (param $meta_len i32) (result i32)
+    (global.set $meta_len (local.get $meta_len))
+    i32.const 0)
+  (func (export "zustdp_module_wasm_raw_process_entry")
+    (param $meta_ptr i32) (param $meta_len i32) (param $data_ptr i32) (param $data_len i32)
+    (result i32)
+    (global.set $calls (i32.add (global.get $calls) (i32.const 1)))
+    (call $result (local.get $data_ptr) (local.get $data_len)))
+  (func (export "zustdp_module_finish") (result i32)
+    (local $state i32)
+    (local.set $state (call $allocate (i32.const 8)))
+    (i32.store (local.get $state) (global.get $calls))
+    (i32.store offset=4 (local.get $state) (global.get $meta_len))
+    (call $result (local.get $state) (i32.const 8))))
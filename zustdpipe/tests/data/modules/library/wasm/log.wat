@@ -0,0 +1,31 @@
+(module
+  ;; Module logging a message using the function provided by the host. It returns the status codes of logging with a valid and an invalid level.
+  (import "zustdp_host" "zustdp_host_log" (func $log (param i32 i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "Hello from module")
+  (func (export "zustdp_module_abi_version") (result i32)
+    i32.const 2)
+  (global $heap (mut i32) (i32.const 1024))
+  (func $allocate (export "zustdp_module_wasm_allocate") (param $size i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $heap))
+    ;; keep allocations 8 byte aligned
+    (global.set $heap
+      (i32.and
+        (i32.add (i32.add (local.get $ptr) (local.get $size)) (i32.const 7))
+        (i32.const -8)))
+    (local.get $ptr))
+  (func (export "zustdp_module_wasm_deallocate") (param $ptr i32) (result i32)
+    i32.const 0)
+  (func (export "zustdp_module_wasm_raw_process_entry")
+    (param $meta_ptr i32) (param $meta_len i32) (param $data_ptr i32) (param $data_len i32)
+    (result i32)
+    (local $result i32)
+    ;; the status codes are two bytes at offset 32
+    (i32.store8 (i32.const 32) (call $log (i32.const 3) (i32.const 0) (i32.const 17)))
+    (i32.store8 (i32.const 33) (call $log (i32.const 9) (i32.const 0) (i32.const 17)))
+    ;; the result descriptor contains pointer and length as u64
+    (local.set $result (call $allocate (i32.const 16)))
+    (i64.store (local.get $result) (i64.const 32))
+    (i64.store offset=8 (local.get $result) (i64.const 2))
+    (local.get $result)))
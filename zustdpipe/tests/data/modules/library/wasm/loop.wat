@@ -0,0 +1,22 @@
+(module
+  ;; Module implementing the raw process entry ABI that never returns. It is used to test interrupting modules.
+  (memory (export "memory") 1)
+  (func (export "zustdp_module_abi_version") (result i32)
+    i32.const 1)
+  (global $heap (mut i32) (i32.const 1024))
+  (func $allocate (export "zustdp_module_wasm_allocate") (param $size i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $heap))
+    (global.set $heap
+      (i32.and
+        (i32.add (i32.add (local.get $ptr) (local.get $size)) (i32.const 7))
+        (i32.const -8)))
+    (local.get $ptr))
+  (func (export "zustdp_module_wasm_deallocate") (param $ptr i32) (result i32)
+    i32.const 0)
+  (func (export "zustdp_module_wasm_raw_process_entry")
+    (param $meta_ptr i32) (param $meta_len i32) (param $data_ptr i32) (param $data_len i32)
+    (result i32)
+    (loop $endless
+      (br $endless))
+    (i32.const 0)))
@@ -0,0 +1,34 @@
+(module
+  ;; Minimal module implementing the raw process entry ABI. It returns the metadata (parameters) it received as data.
+  (memory (export "memory") 1)
+  (func (export "zustdp_module_abi_version") (result i32)
+    i32.const 1)
+  (global $heap (mut i32) (i32.const 1024))
+  (func $allocate (export "zustdp_module_wasm_allocate") (param $size i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $heap))
+    ;; keep allocations 8 byte aligned
+    (global.set $heap
+      (i32.and
+        (i32.add (i32.add (local.get $ptr) (local.get $size)) (i32.const 7))
+        (i32.const -8)))
+    ;; grow memory if the allocation does not fit anymore
+    (if (i32.gt_u (global.get $heap) (i32.mul (memory.size) (i32.const 65536)))
+      (then
+        (drop (memory.grow
+          (i32.add
+            (i32.div_u
+              (i32.sub (global.get $heap) (i32.mul (memory.size) (i32.const 65536)))
+              (i32.const 65536))
+            (i32.const 1))))))
+    (local.get $ptr))
+  (func (export "zustdp_module_wasm_deallocate") (param $ptr i32) (result i32)
+    i32.const 0)
+  (func (export "zustdp_module_wasm_raw_process_entry")
+    (param $meta_ptr i32) (param $meta_len i32) (param $data_ptr i32) (param $data_len i32)
+    (result i32)
+    (local $result i32)
+    (local.set $result (call $allocate (i32.const 8)))
+    (i32.store (local.get $result) (local.get $meta_ptr))
+    (i32.store offset=4 (local.get $result) (local.get $meta_len))
+    (local.get $result)))
@@ -0,0 +1,7 @@
+(module
+  ;; Malformed module: it does not export the functions to allocate and deallocate memory
+  (memory (export "memory") 1)
+  (func (export "zustdp_module_wasm_raw_process_entry")
+    (param $meta_ptr i32) (param $meta_len i32) (param $data_ptr i32) (param $data_len i32)
+    (result i32)
+    (i32.const 0)))
@@ -0,0 +1,15 @@
+(module
+  ;; Malformed module: returns a result outside of its memory
+  (memory (export "memory") 1)
+  (func (export "zustdp_module_abi_version") (result i32)
+    i32.const 1)
+  (func (export "zustdp_module_wasm_allocate") (param $size i32) (result i32)
+    i32.const 1024)
+  (func (export "zustdp_module_wasm_deallocate") (param $ptr i32) (result i32)
+    i32.const 0)
+  (func (export "zustdp_module_wasm_raw_process_entry")
+    (param $meta_ptr i32) (param $meta_len i32) (param $data_ptr i32) (param $data_len i32)
+    (result i32)
+    (i32.store (i32.const 8) (i32.const 65000))
+    (i32.store (i32.const 12) (i32.const 1000))
+    (i32.const 8)))
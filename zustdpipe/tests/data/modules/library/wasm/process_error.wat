@@ -0,0 +1,18 @@
+(module
+  ;; Module whose process always fails and reports the reason
+  (memory (export "memory") 1)
+  ;; result descriptor of the error message: pointer 64 and length 17 as u64
+  (data (i32.const 16) "\40\00\00\00\00\00\00\00\11\00\00\00\00\00\00\00")
+  (data (i32.const 64) "invalid parameter")
+  (func (export "zustdp_module_abi_version") (result i32)
+    i32.const 2)
+  (func (export "zustdp_module_wasm_allocate") (param $size i32) (result i32)
+    i32.const 1024)
+  (func (export "zustdp_module_wasm_deallocate") (param $ptr i32) (result i32)
+    i32.const 0)
+  (func (export "zustdp_module_wasm_last_error") (result i32)
+    i32.const 16)
+  (func (export "zustdp_module_wasm_raw_process_entry")
+    (param $meta_ptr i32) (param $meta_len i32) (param $data_ptr i32) (param $data_len i32)
+    (result i32)
+    i32.const 0))
@@ -0,0 +1,13 @@
+(module
+  ;; Malformed module: the function to allocate memory has a wrong signature
+  (memory (export "memory") 1)
+  (func (export "zustdp_module_abi_version") (result i32)
+    i32.const 1)
+  (func (export "zustdp_module_wasm_allocate") (param $size i64) (result i64)
+    i64.const 1024)
+  (func (export "zustdp_module_wasm_deallocate") (param $ptr i32) (result i32)
+    i32.const 0)
+  (func (export "zustdp_module_wasm_raw_process_entry")
+    (param $meta_ptr i32) (param $meta_len i32) (param $data_ptr i32) (param $data_len i32)
+    (result i32)
+    (i32.const 0)))
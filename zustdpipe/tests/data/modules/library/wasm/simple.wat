@@ -1,4 +1,14 @@
 (module
-  (func $simple (export "zustdp_module_wasm_raw_process_entry") (result i64)
-    i64.const 1
+  ;; Minimal module implementing the ABI. It does not process any data
+  (memory (export "memory") 1)
+  (func (export "zustdp_module_abi_version") (result i32)
+    i32.const 1)
+  (func (export "zustdp_module_wasm_allocate") (param $size i32) (result i32)
+    i32.const 1024)
+  (func (export "zustdp_module_wasm_deallocate") (param $ptr i32) (result i32)
+    i32.const 0)
+  (func $simple (export "zustdp_module_wasm_raw_process_entry")
+    (param $meta_ptr i32) (param $meta_len i32) (param $data_ptr i32) (param $data_len i32)
+    (result i32)
+    i32.const 0
   ))
@@ -0,0 +1,18 @@
+(module
+  ;; Module implementing the ABI whose start function never returns. It is used to test interrupting the instantiation of modules.
+  (memory (export "memory") 1)
+  (func $start
+    (loop $forever
+      br $forever))
+  (start $start)
+  (func (export "zustdp_module_abi_version") (result i32)
+    i32.const 2)
+  (func (export "zustdp_module_wasm_allocate") (param $size i32) (result i32)
+    i32.const 1024)
+  (func (export "zustdp_module_wasm_deallocate") (param $ptr i32) (result i32)
+    i32.const 0)
+  (func (export "zustdp_module_wasm_raw_process_entry")
+    (param $meta_ptr i32) (param $meta_len i32) (param $data_ptr i32) (param $data_len i32)
+    (result i32)
+    i32.const 0
+  ))
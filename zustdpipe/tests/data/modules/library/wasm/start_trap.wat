@@ -0,0 +1,17 @@
+(module
+  ;; Module implementing the ABI whose start function traps, so it can neither be validated nor instantiated
+  (memory (export "memory") 1)
+  (func $start
+    unreachable)
+  (start $start)
+  (func (export "zustdp_module_abi_version") (result i32)
+    i32.const 2)
+  (func (export "zustdp_module_wasm_allocate") (param $size i32) (result i32)
+    i32.const 1024)
+  (func (export "zustdp_module_wasm_deallocate") (param $ptr i32) (result i32)
+    i32.const 0)
+  (func (export "zustdp_module_wasm_raw_process_entry")
+    (param $meta_ptr i32) (param $meta_len i32) (param $data_ptr i32) (param $data_len i32)
+    (result i32)
+    i32.const 0
+  ))
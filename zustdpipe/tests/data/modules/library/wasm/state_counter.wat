@@ -0,0 +1,39 @@
+(module
+  ;; Module counting its calls in the state provided by the host. It returns the number of calls including previous instances.
+  (import "zustdp_host" "zustdp_host_state_get" (func $state_get (param i32 i32) (result i32)))
+  (import "zustdp_host" "zustdp_host_state_put" (func $state_put (param i32 i32 i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  ;; key of the counter
+  (data (i32.const 0) "count")
+  (func (export "zustdp_module_abi_version") (result i32)
+    i32.const 2)
+  (global $heap (mut i32) (i32.const 1024))
+  (func $allocate (export "zustdp_module_wasm_allocate") (param $size i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $heap))
+    ;; keep allocations 8 byte aligned
+    (global.set $heap
+      (i32.and
+        (i32.add (i32.add (local.get $ptr) (local.get $size)) (i32.const 7))
+        (i32.const -8)))
+    (local.get $ptr))
+  (func (export "zustdp_module_wasm_deallocate") (param $ptr i32) (result i32)
+    i32.const 0)
+  (func (export "zustdp_module_wasm_raw_process_entry")
+    (param $meta_ptr i32) (param $meta_len i32) (param $data_ptr i32) (param $data_len i32)
+    (result i32)
+    (local $descriptor i32)
+    (local $result i32)
+    ;; the counter is a single byte at offset 16
+    (local.set $descriptor (call $state_get (i32.const 0) (i32.const 5)))
+    (if (i32.ne (local.get $descriptor) (i32.const 0))
+      (then
+        (i32.store8 (i32.const 16) (i32.load8_u (i32.wrap_i64 (i64.load (local.get $descriptor)))))))
+    (i32.store8 (i32.const 16) (i32.add (i32.load8_u (i32.const 16)) (i32.const 1)))
+    (if (i32.ne (call $state_put (i32.const 0) (i32.const 5) (i32.const 16) (i32.const 1)) (i32.const 0))
+      (then (return (i32.const 0))))
+    ;; the result descriptor contains pointer and length as u64
+    (local.set $result (call $allocate (i32.const 16)))
+    (i64.store (local.get $result) (i64.const 16))
+    (i64.store offset=8 (local.get $result) (i64.const 1))
+    (local.get $result)))
@@ -0,0 +1,56 @@
+(module
+  ;; Minimal module implementing the stream functions. It returns each record batch pushed to it unchanged.
+  ;; It holds at most one pending output and asks the host to pull it before accepting the next record batch.
+  (memory (export "memory") 1)
+  (func (export "zustdp_module_abi_version") (result i32)
+    i32.const 2)
+  (global $heap (mut i32) (i32.const 1024))
+  (global $pending_ptr (mut i32) (i32.const 0))
+  (global $pending_len (mut i32) (i32.const 0))
+  (func $allocate (export "zustdp_module_wasm_allocate") (param $size i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $heap))
+    ;; keep allocations 8 byte aligned
+    (global.set $heap
+      (i32.and
+        (i32.add (i32.add (local.get $ptr) (local.get $size)) (i32.const 7))
+        (i32.const -8)))
+    ;; grow memory if the allocation does not fit anymore
+    (if (i32.gt_u (global.get $heap) (i32.mul (memory.size) (i32.const 65536)))
+      (then
+        (drop (memory.grow
+          (i32.add
+            (i32.div_u
+              (i32.sub (global.get $heap) (i32.mul (memory.size) (i32.const 65536)))
+              (i32.const 65536))
+            (i32.const 1))))))
+    (local.get $ptr))
+  (func (export "zustdp_module_wasm_deallocate") (param $ptr i32) (result i32)
+    i32.const 0)
+  (func (export "zustdp_module_wasm_raw_process_entry")
+    (param $meta_ptr i32) (param $meta_len i32) (param $data_ptr i32) (param $data_len i32)
+    (result i32)
+    i32.const 0)
+  (func (export "zustdp_module_wasm_stream_start") (param $meta_ptr i32) (param $meta_len i32) (result i32)
+    i32.const 0)
+  (func (export "zustdp_module_wasm_stream_push") (param $data_ptr i32) (param $data_len i32) (result i32)
+    ;; back-pressure: the pending output must be pulled first
+    (if (i32.ne (global.get $pending_ptr) (i32.const 0))
+      (then (return (i32.const 1))))
+    ;; the host deallocates the data after pushing it, so keep a copy
+    (global.set $pending_ptr (call $allocate (local.get $data_len)))
+    (global.set $pending_len (local.get $data_len))
+    (memory.copy (global.get $pending_ptr) (local.get $data_ptr) (local.get $data_len))
+    i32.const 0)
+  (func (export "zustdp_module_wasm_stream_pull") (result i32)
+    (local $result i32)
+    (if (i32.eqz (global.get $pending_ptr))
+      (then (return (i32.const 0))))
+    ;; the result descriptor contains pointer and length as u64
+    (local.set $result (call $allocate (i32.const 16)))
+    (i64.store (local.get $result) (i64.extend_i32_u (global.get $pending_ptr)))
+    (i64.store offset=8 (local.get $result) (i64.extend_i32_u (global.get $pending_len)))
+    (global.set $pending_ptr (i32.const 0))
+    (local.get $result))
+  (func (export "zustdp_module_wasm_stream_end") (result i32)
+    i32.const 0))
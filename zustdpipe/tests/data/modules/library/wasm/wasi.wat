@@ -0,0 +1,37 @@
+(module
+  ;; Module using WASI functions. It returns the error code of creating a directory in the first preopened directory and the number of environment variables and arguments.
+  (import "wasi_snapshot_preview1" "path_create_directory" (func $path_create_directory (param i32 i32 i32) (result i32)))
+  (import "wasi_snapshot_preview1" "environ_sizes_get" (func $environ_sizes_get (param i32 i32) (result i32)))
+  (import "wasi_snapshot_preview1" "args_sizes_get" (func $args_sizes_get (param i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "output")
+  (func (export "zustdp_module_abi_version") (result i32)
+    i32.const 2)
+  (global $heap (mut i32) (i32.const 1024))
+  (func $allocate (export "zustdp_module_wasm_allocate") (param $size i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $heap))
+    ;; keep allocations 8 byte aligned
+    (global.set $heap
+      (i32.and
+        (i32.add (i32.add (local.get $ptr) (local.get $size)) (i32.const 7))
+        (i32.const -8)))
+    (local.get $ptr))
+  (func (export "zustdp_module_wasm_deallocate") (param $ptr i32) (result i32)
+    i32.const 0)
+  (func (export "zustdp_module_wasm_raw_process_entry")
+    (param $meta_ptr i32) (param $meta_len i32) (param $data_ptr i32) (param $data_len i32)
+    (result i32)
+    (local $result i32)
+    ;; the first preopened directory has the file descriptor 3
+    (i32.store8 (i32.const 32) (call $path_create_directory (i32.const 3) (i32.const 0) (i32.const 6)))
+    ;; the counts are written to offset 64, the sizes of the buffers to offset 68
+    (drop (call $environ_sizes_get (i32.const 64) (i32.const 68)))
+    (i32.store8 (i32.const 33) (i32.load (i32.const 64)))
+    (drop (call $args_sizes_get (i32.const 64) (i32.const 68)))
+    (i32.store8 (i32.const 34) (i32.load (i32.const 64)))
+    ;; the result descriptor contains pointer and length as u64
+    (local.set $result (call $allocate (i32.const 16)))
+    (i64.store (local.get $result) (i64.const 32))
+    (i64.store offset=8 (local.get $result) (i64.const 3))
+    (local.get $result)))
@@ -1 +1,80 @@
 //! Integration tests related to ZuSearch to test different integration scenarios
+
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+
+use arrow::array::Int64Array;
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::ipc::reader::StreamReader;
+use arrow::ipc::writer::StreamWriter;
+use arrow::record_batch::RecordBatch;
+
+use zustdpipe::jobs::executor::execute_process;
+use zustdpipe::modules::library::interface::{
+    ExecutionLimits, Library, LibraryDefinition, LibraryManager, WasiDefinition,
+};
+use zustdpipe::modules::library::wasm::WASMLibraryManager;
+
+#[test]
+#[ignore = "builds the echo module and requires the wasm32-unknown-unknown target"]
+// Test that a module exported with export_process! does not have lifecycle hooks, so several module instances process its record batches in parallel
+fn test_echo_module_parallel() {
+    let module_directory = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../zustdp-modules-common/zustdp-module-process-echo");
+    let status = Command::new(env!("CARGO"))
+        .args(["build", "--release", "--target", "wasm32-unknown-unknown"])
+        .current_dir(&module_directory)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let library_definition = LibraryDefinition {
+        path: module_directory
+            .join("target/wasm32-unknown-unknown/release/zustdp_module_process_echo.wasm")
+            .to_string_lossy()
+            .to_string(),
+        max_memory_pages: None,
+        max_table_elements: None,
+        wasi: WasiDefinition::default(),
+    };
+    let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)]));
+    let batches: Vec<RecordBatch> = (0..5)
+        .map(|batch_number| {
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![Arc::new(Int64Array::from(vec![batch_number]))],
+            )
+            .unwrap()
+        })
+        .collect();
+    let mut stream_writer = StreamWriter::try_new(Vec::new(), &schema).unwrap();
+    for batch in batches.iter() {
+        stream_writer.write(batch).unwrap();
+    }
+    stream_writer.finish().unwrap();
+    let serialized_data = stream_writer.into_inner().unwrap();
+    let mut libmgr: WASMLibraryManager = WASMLibraryManager::new().unwrap();
+    let mut module_instances: Vec<bool> = Vec::new();
+    let mut serialized_answer: Vec<u8> = Vec::new();
+    execute_process(
+        || {
+            let module_instance = libmgr
+                .get_instance(&library_definition, &ExecutionLimits::default())
+                .unwrap();
+            module_instances.push(module_instance.has_lifecycle_hooks());
+            Ok(module_instance as Box<dyn Library>)
+        },
+        3,
+        &[],
+        &[serialized_data.as_slice()],
+        &mut serialized_answer,
+    )
+    .unwrap();
+    assert_eq!(module_instances, vec![false, false, false]);
+    let answer_batches: Vec<RecordBatch> =
+        StreamReader::try_new(serialized_answer.as_slice(), None)
+            .unwrap()
+            .map(|batch| batch.unwrap())
+            .collect();
+    assert_eq!(answer_batches, batches);
+}